@@ -0,0 +1,16 @@
+-- Track dataset versions so cached profiles can be invalidated when the data changes
+ALTER TABLE datasets ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Cached data profiles, one per dataset version
+CREATE TABLE IF NOT EXISTS dataset_profiles (
+    dataset_id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending', -- 'pending', 'running', 'completed', 'failed'
+    profile TEXT, -- JSON-serialized DataProfile
+    error TEXT,
+    duration_ms INTEGER,
+    computed_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (dataset_id, version),
+    FOREIGN KEY (dataset_id) REFERENCES datasets(id) ON DELETE CASCADE
+);
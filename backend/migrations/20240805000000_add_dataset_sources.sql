@@ -0,0 +1,4 @@
+-- Datasets extracted from a table of a multi-table upload (SQLite database, Excel workbook)
+-- keep the uploaded file and the selected table so another table can be picked later
+ALTER TABLE datasets ADD COLUMN source_path TEXT;
+ALTER TABLE datasets ADD COLUMN source_table TEXT;
@@ -0,0 +1,13 @@
+-- External sources (SQL databases, S3-compatible buckets, HTTP URLs) that datasets can be snapshotted from
+CREATE TABLE IF NOT EXISTS connections (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL, -- 'sql', 's3', 'http'
+    config TEXT NOT NULL, -- JSON connector config, encrypted (see data::secrets)
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Datasets materialized from a connection, refreshed on demand
+ALTER TABLE datasets ADD COLUMN connection_id TEXT REFERENCES connections(id);
+ALTER TABLE datasets ADD COLUMN refreshed_at TIMESTAMP;
@@ -0,0 +1,16 @@
+-- Every snapshot taken of a connection-backed dataset, kept after a refresh so
+-- the versions experiments recorded still point at their data
+CREATE TABLE IF NOT EXISTS dataset_snapshots (
+    dataset_id TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    file_path TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (dataset_id, version),
+    FOREIGN KEY (dataset_id) REFERENCES datasets(id) ON DELETE CASCADE
+);
+
+INSERT OR IGNORE INTO dataset_snapshots (dataset_id, version, file_path, size, created_at)
+SELECT id, version, file_path, size, COALESCE(refreshed_at, created_at)
+FROM datasets
+WHERE connection_id IS NOT NULL;
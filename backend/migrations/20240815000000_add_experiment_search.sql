@@ -0,0 +1,42 @@
+-- Parameter values and latest metric values of each experiment, one row per key, so
+-- listings can filter and sort on them with indexed lookups instead of parsing JSON.
+-- Kept in sync with the `parameters`/`metrics` columns by `Database`.
+CREATE TABLE IF NOT EXISTS experiment_params (
+    experiment_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL, -- strings as-is, other JSON values serialized
+    PRIMARY KEY (experiment_id, key),
+    FOREIGN KEY (experiment_id) REFERENCES experiments(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS experiment_metric_summaries (
+    experiment_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value REAL NOT NULL,
+    PRIMARY KEY (experiment_id, key),
+    FOREIGN KEY (experiment_id) REFERENCES experiments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_experiment_params_key_value ON experiment_params(key, value);
+CREATE INDEX IF NOT EXISTS idx_experiment_metric_summaries_key_value ON experiment_metric_summaries(key, value);
+CREATE INDEX IF NOT EXISTS idx_experiments_dataset ON experiments(dataset_id);
+CREATE INDEX IF NOT EXISTS idx_experiments_created_at ON experiments(created_at);
+
+-- Backfill from existing experiments; rows with malformed JSON are skipped
+INSERT OR IGNORE INTO experiment_params (experiment_id, key, value)
+SELECT e.id, j.key,
+       CASE j.type
+           WHEN 'text' THEN j.value
+           WHEN 'true' THEN 'true'
+           WHEN 'false' THEN 'false'
+           ELSE CAST(j.value AS TEXT)
+       END
+FROM experiments e,
+     json_each(CASE WHEN json_valid(e.parameters) AND json_type(e.parameters) = 'object' THEN e.parameters ELSE '{}' END) j
+WHERE j.type != 'null';
+
+INSERT OR IGNORE INTO experiment_metric_summaries (experiment_id, key, value)
+SELECT e.id, j.key, j.value
+FROM experiments e,
+     json_each(CASE WHEN json_valid(e.metrics) AND json_type(e.metrics) = 'object' THEN e.metrics ELSE '{}' END) j
+WHERE j.type IN ('integer', 'real');
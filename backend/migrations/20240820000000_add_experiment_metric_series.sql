@@ -0,0 +1,30 @@
+-- Every logged metric value, so training curves are kept rather than overwritten
+CREATE TABLE IF NOT EXISTS experiment_metrics (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    experiment_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    step INTEGER NOT NULL,
+    value REAL NOT NULL,
+    timestamp TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (experiment_id) REFERENCES experiments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_experiment_metrics_series ON experiment_metrics(experiment_id, key, step);
+
+-- Summaries now describe the whole series: `value` is the latest point, `best_value`
+-- the minimum or maximum depending on the metric (see tracking::metrics::MetricGoal)
+ALTER TABLE experiment_metric_summaries ADD COLUMN step INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE experiment_metric_summaries ADD COLUMN best_value REAL NOT NULL DEFAULT 0;
+ALTER TABLE experiment_metric_summaries ADD COLUMN min_value REAL NOT NULL DEFAULT 0;
+ALTER TABLE experiment_metric_summaries ADD COLUMN max_value REAL NOT NULL DEFAULT 0;
+ALTER TABLE experiment_metric_summaries ADD COLUMN count INTEGER NOT NULL DEFAULT 1;
+
+UPDATE experiment_metric_summaries SET best_value = value, min_value = value, max_value = value;
+
+CREATE INDEX IF NOT EXISTS idx_experiment_metric_summaries_key_best ON experiment_metric_summaries(key, best_value);
+
+-- Existing values become the first point of their series
+INSERT INTO experiment_metrics (experiment_id, key, step, value, timestamp)
+SELECT s.experiment_id, s.key, 0, s.value, COALESCE(e.updated_at, CURRENT_TIMESTAMP)
+FROM experiment_metric_summaries s
+JOIN experiments e ON e.id = s.experiment_id;
@@ -0,0 +1,7 @@
+-- Version of the dataset an experiment was run against, so runs can be compared fairly
+ALTER TABLE experiments ADD COLUMN dataset_version INTEGER;
+
+-- Best guess for existing experiments: the dataset's current version
+UPDATE experiments
+SET dataset_version = (SELECT version FROM datasets WHERE datasets.id = experiments.dataset_id)
+WHERE dataset_id IS NOT NULL;
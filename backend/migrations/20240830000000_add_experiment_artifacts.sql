@@ -0,0 +1,15 @@
+-- Files attached to experiments. Content is stored once per SHA-256 under data/artifacts;
+-- each row maps an experiment-relative path to it.
+CREATE TABLE IF NOT EXISTS experiment_artifacts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    experiment_id TEXT NOT NULL,
+    path TEXT NOT NULL,
+    sha256 TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    mime_type TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (experiment_id, path),
+    FOREIGN KEY (experiment_id) REFERENCES experiments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_experiment_artifacts_sha256 ON experiment_artifacts(sha256);
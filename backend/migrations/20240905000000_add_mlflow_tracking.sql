@@ -0,0 +1,36 @@
+-- MLflow groups runs into experiments; each row of `experiments` is one MLflow run.
+-- Runs created through our own API belong to no MLflow experiment.
+CREATE TABLE IF NOT EXISTS mlflow_experiments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    artifact_location TEXT,
+    lifecycle_stage TEXT NOT NULL DEFAULT 'active', -- 'active', 'deleted'
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- MLflow clients log to experiment 0 unless told otherwise
+INSERT OR IGNORE INTO mlflow_experiments (id, name, artifact_location)
+VALUES (0, 'Default', 'mlflow-artifacts:/0');
+
+CREATE TABLE IF NOT EXISTS mlflow_experiment_tags (
+    experiment_id INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (experiment_id, key),
+    FOREIGN KEY (experiment_id) REFERENCES mlflow_experiments(id) ON DELETE CASCADE
+);
+
+ALTER TABLE experiments ADD COLUMN mlflow_experiment_id INTEGER REFERENCES mlflow_experiments(id);
+
+-- Free-form run tags such as `mlflow.user` or `mlflow.source.name`
+CREATE TABLE IF NOT EXISTS experiment_tags (
+    experiment_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (experiment_id, key),
+    FOREIGN KEY (experiment_id) REFERENCES experiments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_experiments_mlflow_experiment ON experiments(mlflow_experiment_id);
+CREATE INDEX IF NOT EXISTS idx_experiment_tags_key_value ON experiment_tags(key, value);
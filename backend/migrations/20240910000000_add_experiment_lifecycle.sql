@@ -0,0 +1,16 @@
+-- When an experiment started running and finished, and why it failed.
+-- `duration_ms` is finished_at - started_at, kept for sorting and comparison.
+ALTER TABLE experiments ADD COLUMN started_at TIMESTAMP;
+ALTER TABLE experiments ADD COLUMN finished_at TIMESTAMP;
+ALTER TABLE experiments ADD COLUMN duration_ms INTEGER;
+ALTER TABLE experiments ADD COLUMN failure_reason TEXT;
+
+-- Best guess for existing experiments: status changes only touched updated_at
+UPDATE experiments
+SET started_at = created_at
+WHERE status IN ('running', 'completed', 'failed', 'cancelled');
+
+UPDATE experiments
+SET finished_at = updated_at,
+    duration_ms = CAST(ROUND((julianday(updated_at) - julianday(created_at)) * 86400000) AS INTEGER)
+WHERE status IN ('completed', 'failed', 'cancelled');
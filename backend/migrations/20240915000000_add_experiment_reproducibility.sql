@@ -0,0 +1,19 @@
+-- What an experiment ran with, captured when it starts so it can be rerun.
+-- The tracking client reports its own interpreter, script and seeds; the
+-- backend fills in the rest.
+CREATE TABLE IF NOT EXISTS experiment_reproducibility (
+    experiment_id TEXT PRIMARY KEY,
+    environment_id TEXT,      -- RuntimeEnvironment the run used
+    python_version TEXT,
+    packages TEXT,            -- `pip freeze` output
+    git_commit TEXT,
+    git_dirty BOOLEAN,        -- uncommitted changes on top of git_commit
+    notebook_revision TEXT,   -- SHA-256 of notebook_snapshot
+    notebook_snapshot TEXT,   -- JSON array of the notebook's cells
+    script_path TEXT,
+    script_sha256 TEXT,
+    seeds TEXT,               -- JSON object of library to seed, e.g. {"numpy": 42}
+    dataset_version INTEGER,
+    captured_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (experiment_id) REFERENCES experiments(id) ON DELETE CASCADE
+);
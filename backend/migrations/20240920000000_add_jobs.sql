@@ -0,0 +1,21 @@
+-- Background work (AutoML searches, training scripts, notebook runs), kept in
+-- the database so queued and interrupted jobs survive a restart.
+CREATE TABLE IF NOT EXISTS jobs (
+    id TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,                   -- 'automl', 'training' or 'notebook'
+    payload TEXT NOT NULL,                -- JSON arguments of the kind
+    experiment_id TEXT,                   -- experiment the job runs, if any
+    status TEXT NOT NULL DEFAULT 'queued', -- queued, running, completed, failed or cancelled
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 3,
+    run_after TIMESTAMP NOT NULL,         -- not started before; pushed back between retries
+    cancel_requested BOOLEAN NOT NULL DEFAULT FALSE,
+    error TEXT,                           -- why the last attempt failed
+    created_at TIMESTAMP NOT NULL,
+    started_at TIMESTAMP,                 -- of the last attempt
+    finished_at TIMESTAMP,
+    FOREIGN KEY (experiment_id) REFERENCES experiments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_status_run_after ON jobs(status, run_after);
+CREATE INDEX IF NOT EXISTS idx_jobs_experiment_id ON jobs(experiment_id);
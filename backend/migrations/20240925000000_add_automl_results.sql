@@ -0,0 +1,10 @@
+-- What an AutoML search produced beyond the metrics and model path kept on
+-- its experiment.
+CREATE TABLE IF NOT EXISTS automl_results (
+    experiment_id TEXT PRIMARY KEY,
+    task_type TEXT,              -- as requested or detected
+    results_path TEXT,           -- results.json the script wrote
+    feature_importances TEXT,    -- JSON object of feature to importance
+    created_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (experiment_id) REFERENCES experiments(id) ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+-- Child runs: the candidates of an AutoML search or the trials of a tuning
+-- run, grouped under the experiment that launched them.
+ALTER TABLE experiments ADD COLUMN parent_id TEXT REFERENCES experiments(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_experiments_parent_id ON experiments(parent_id);
+
+-- Metric an AutoML search ranked its candidates by
+ALTER TABLE automl_results ADD COLUMN metric TEXT;
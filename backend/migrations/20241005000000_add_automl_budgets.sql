@@ -0,0 +1,3 @@
+-- Which budget of the request stopped an AutoML search early, if one did:
+-- 'max_runtime_seconds', 'max_trials' or 'max_memory_mb'.
+ALTER TABLE automl_results ADD COLUMN budget_exhausted TEXT;
@@ -1,6 +1,10 @@
 import os
 import json
 import logging
+import math
+import time
+import importlib
+import importlib.util
 import numpy as np
 import pandas as pd
 from typing import Dict, Any, Optional, List, Union
@@ -19,16 +23,238 @@ class TaskType(Enum):
     CLASSIFICATION = "classification"
     REGRESSION = "regression"
     CLUSTERING = "clustering"
+    FORECASTING = "forecasting"
+
+# Candidate estimators by task type: name -> (module, class, default arguments).
+# Models of optional libraries are only tried by default when they are installed.
+CANDIDATES = {
+    TaskType.CLASSIFICATION: {
+        "logistic_regression": ("sklearn.linear_model", "LogisticRegression", {"max_iter": 1000}),
+        "random_forest": ("sklearn.ensemble", "RandomForestClassifier", {}),
+        "extra_trees": ("sklearn.ensemble", "ExtraTreesClassifier", {}),
+        "gradient_boosting": ("sklearn.ensemble", "GradientBoostingClassifier", {}),
+        "xgboost": ("xgboost", "XGBClassifier", {}),
+        "lightgbm": ("lightgbm", "LGBMClassifier", {"verbose": -1}),
+    },
+    TaskType.REGRESSION: {
+        "ridge": ("sklearn.linear_model", "Ridge", {}),
+        "random_forest": ("sklearn.ensemble", "RandomForestRegressor", {}),
+        "extra_trees": ("sklearn.ensemble", "ExtraTreesRegressor", {}),
+        "gradient_boosting": ("sklearn.ensemble", "GradientBoostingRegressor", {}),
+        "xgboost": ("xgboost", "XGBRegressor", {}),
+        "lightgbm": ("lightgbm", "LGBMRegressor", {"verbose": -1}),
+    },
+}
+# Forecasts regress each step on the series' lags
+CANDIDATES[TaskType.FORECASTING] = CANDIDATES[TaskType.REGRESSION]
+
+# Forecasts every search compares its models against: the last value, and the
+# value one season earlier
+BASELINES = ("naive", "seasonal_naive")
+
+# Forecast errors, averaged over the backtest windows; lower is better
+FORECASTING_METRICS = ("mae", "rmse", "mape")
+
+# Metric name -> scikit-learn scorer. Scorers of errors are negated, so their
+# scores are flipped back and lower is better.
+SCORERS = {
+    TaskType.CLASSIFICATION: {
+        "accuracy": "accuracy",
+        "f1": "f1_weighted",
+        "precision": "precision_weighted",
+        "recall": "recall_weighted",
+    },
+    TaskType.REGRESSION: {
+        "r2": "r2",
+        "mse": "neg_mean_squared_error",
+        "rmse": "neg_root_mean_squared_error",
+        "mae": "neg_mean_absolute_error",
+    },
+}
+
+DEFAULT_METRICS = {
+    TaskType.CLASSIFICATION: "accuracy",
+    TaskType.REGRESSION: "r2",
+    TaskType.FORECASTING: "mae",
+}
+
+# Family of each model named differently from its family, for `model_families`
+FAMILIES = {
+    "logistic_regression": "linear",
+    "ridge": "linear",
+}
+
+# The configuration the backend writes to config.json, with its defaults
+DEFAULT_CONFIG = {
+    "metric": None,
+    "cv_folds": 5,
+    "stratify": True,
+    "class_weight": "none",
+    "categorical_encoding": "one_hot",
+    "imputation": "median",
+    "seed": 42,
+    "model_families": None,
+    "input_format": "csv",
+    "parameters": None,
+    "timestamp_column": None,
+    "horizon": None,
+    "group_column": None,
+    "season_length": None,
+}
+
+# Season length by sampling interval, in seconds: minutely data repeats
+# hourly, hourly data daily, daily data weekly, and so on
+SEASONS = [
+    (60, 60),
+    (3600, 24),
+    (86400, 7),
+    (7 * 86400, 52),
+    (31 * 86400, 12),
+    (92 * 86400, 4),
+]
+
+
+def load_config(path: Optional[str]) -> Dict[str, Any]:
+    """The configuration in `path` over the defaults; null values keep the default."""
+    config = dict(DEFAULT_CONFIG)
+    if path:
+        with open(path) as f:
+            config.update({key: value for key, value in json.load(f).items() if value is not None})
+    return config
+
+
+def load_data(path: str, input_format: str = "csv") -> pd.DataFrame:
+    """Read the dataset in the format the backend handed it over in."""
+    if input_format == "parquet":
+        return pd.read_parquet(path)
+    if input_format == "json":
+        return pd.read_json(path, orient="records")
+    return pd.read_csv(path)
+
+
+def _finite(value) -> Optional[float]:
+    """The value as a float, or None for NaN and infinity, which JSON can't hold."""
+    value = float(value)
+    return value if math.isfinite(value) else None
+
+
+def _rank(leaderboard: List[Dict[str, Any]], metric: str, lower_is_better: bool) -> List[Dict[str, Any]]:
+    """Candidates best first by `metric`, followed by those without a score."""
+    scored = [e for e in leaderboard if e["status"] == "completed" and e["metrics"].get(metric) is not None]
+    scored.sort(key=lambda e: e["metrics"][metric], reverse=not lower_is_better)
+    return scored + [e for e in leaderboard if e not in scored]
+
+
+def _json_params(model) -> Dict[str, Any]:
+    """The estimator's parameters that are plain JSON values."""
+    return {
+        key: value
+        for key, value in model.get_params(deep=False).items()
+        if value is None or isinstance(value, (bool, int, float, str))
+    }
+
+
+def _forecast_errors(actual: np.ndarray, predicted: np.ndarray) -> Dict[str, Optional[float]]:
+    """MAE, RMSE and MAPE of a forecast; MAPE skips steps whose actual value is zero."""
+    errors = predicted - actual
+    nonzero = actual != 0
+    return {
+        "mae": _finite(np.mean(np.abs(errors))),
+        "rmse": _finite(np.sqrt(np.mean(errors ** 2))),
+        "mape": _finite(np.mean(np.abs(errors[nonzero] / actual[nonzero]))) if nonzero.any() else None,
+    }
+
+
+def _frequency(timestamps: pd.DatetimeIndex):
+    """Step between a series' timestamps: its calendar frequency if pandas can
+    infer one, so months stay months, else the median step."""
+    if len(timestamps) >= 3:
+        freq = pd.infer_freq(timestamps)
+        if freq is not None:
+            return pd.tseries.frequencies.to_offset(freq)
+    return pd.Series(timestamps).diff().median()
+
+
+def _season_length(timestamp: pd.Timestamp, step) -> int:
+    """Season of a series sampled every `step`, or 1 if it has none."""
+    seconds = ((timestamp + step) - timestamp).total_seconds()
+    for interval, season in SEASONS:
+        if seconds <= interval:
+            return season
+    return 1
+
+
+class SeriesFeatures:
+    """Lag, rolling and calendar features of a series at each step, computed the
+    same way for the steps it is trained on and the steps it forecasts."""
+    
+    def __init__(self, season_length: int):
+        self.season_length = season_length
+        self.lags = sorted({1, 2, 3, season_length})
+        self.windows = sorted({3, season_length}) if season_length > 3 else [3]
+        # Steps of history the first training row needs
+        self.min_history = max(self.lags + self.windows)
+        self.names = (
+            [f"lag_{lag}" for lag in self.lags]
+            + [f"rolling_{stat}_{window}" for window in self.windows for stat in ("mean", "std")]
+            + ["month", "day_of_week", "day_of_year", "hour", "series"]
+        )
+    
+    def row(self, values, t: int, timestamp: pd.Timestamp, series: int) -> List[float]:
+        """Features of step `t`, from the values before it."""
+        features = [values[t - lag] for lag in self.lags]
+        for window in self.windows:
+            recent = np.asarray(values[t - window:t], dtype=float)
+            features += [recent.mean(), recent.std()]
+        return features + [timestamp.month, timestamp.dayofweek, timestamp.dayofyear, timestamp.hour, series]
+    
+    def frame(self, series: List[Dict[str, Any]], cutoffs: Dict[int, int]) -> tuple:
+        """Training rows of each series' steps before its cutoff."""
+        rows, targets = [], []
+        for s in series:
+            values = s["values"]
+            for t in range(self.min_history, cutoffs[s["code"]]):
+                rows.append(self.row(values, t, s["timestamps"][t], s["code"]))
+                targets.append(values[t])
+        return pd.DataFrame(rows, columns=self.names), np.array(targets)
+    
+    def predict(self, model, series: List[Dict[str, Any]], cutoffs: Dict[int, int], timestamps: Dict[int, pd.DatetimeIndex]) -> Dict[int, np.ndarray]:
+        """Forecast each series from its cutoff one step at a time, feeding each
+        prediction back as the next step's history."""
+        histories = {s["code"]: list(s["values"][:cutoffs[s["code"]]]) for s in series}
+        for k in range(max(len(ts) for ts in timestamps.values())):
+            codes = [code for code in histories if k < len(timestamps[code])]
+            rows = [self.row(histories[code], len(histories[code]), timestamps[code][k], code) for code in codes]
+            for code, value in zip(codes, model.predict(pd.DataFrame(rows, columns=self.names))):
+                histories[code].append(float(value))
+        return {code: np.array(history[cutoffs[code]:]) for code, history in histories.items()}
+
+
+def _baseline_forecaster(name: str, season_length: int):
+    """Forecasts repeating the last value, or the last season's values."""
+    def forecast(series, cutoffs, timestamps):
+        predictions = {}
+        for s in series:
+            history = s["values"][:cutoffs[s["code"]]]
+            horizon = len(timestamps[s["code"]])
+            season = min(season_length, len(history)) if name == "seasonal_naive" else 1
+            last = history[-season:]
+            predictions[s["code"]] = np.array([last[k % season] for k in range(horizon)])
+        return predictions
+    return forecast
+
 
 class AutoMLService:
-    def __init__(self, experiment_id: str, output_dir: str = "data/experiments"):
+    def __init__(self, experiment_id: str, output_dir: str = "data/experiments", config: Optional[Dict[str, Any]] = None):
         """Initialize the AutoML service.
         
         Args:
             experiment_id: Unique identifier for the experiment
             output_dir: Directory to store experiment outputs
+            config: Search configuration; see DEFAULT_CONFIG
         """
         self.experiment_id = experiment_id
+        self.config = {**DEFAULT_CONFIG, **(config or {})}
         self.output_dir = Path(output_dir) / experiment_id
         self.output_dir.mkdir(parents=True, exist_ok=True)
         
@@ -36,6 +262,14 @@ class AutoMLService:
         self.models = {}
         self.metrics = {}
         self.feature_importances = {}
+        self.leaderboard = []
+        self.task_type = None
+        self.metric = None
+        self.best_model = None
+        self.model_path = None
+        self.forecast_path = None
+        # Name of the request budget that cut the search short, if any
+        self.budget_exhausted = None
         
         logger.info(f"Initialized AutoML service for experiment {experiment_id}")
     
@@ -43,7 +277,11 @@ class AutoMLService:
         """Log a metric for the experiment."""
         if name not in self.metrics:
             self.metrics[name] = []
-        self.metrics[name].append({"step": step or len(self.metrics[name]), "value": value})
+        self.metrics[name].append({
+            "step": len(self.metrics[name]) if step is None else step,
+            "value": value,
+            "timestamp": datetime.utcnow().isoformat(),
+        })
         logger.info(f"Metric '{name}': {value} (step: {step or 'latest'})")
     
     def detect_task_type(self, target: pd.Series) -> TaskType:
@@ -55,45 +293,414 @@ class AutoMLService:
         return TaskType.REGRESSION
     
     def preprocess_data(self, data: pd.DataFrame, target_col: str) -> tuple:
-        """Preprocess the input data."""
+        """Separate the features from the target. Imputation and encoding are
+        learnt by each model's pipeline, from the rows it is fitted on; see
+        `make_pipeline`."""
         logger.info("Preprocessing data...")
         
-        # Handle missing values
+        # Rows without a target can't be learnt from
         data = data.dropna(how='all')
+        data = data[data[target_col].notna()]
         
         # Separate features and target
         X = data.drop(columns=[target_col])
         y = data[target_col]
         
-        # Detect and encode categorical variables
-        categorical_cols = X.select_dtypes(include=['object', 'category']).columns
-        if not categorical_cols.empty:
-            X = pd.get_dummies(X, columns=categorical_cols, drop_first=True)
+        # Categories are encoded as strings, and missing ones as NaN whether
+        # they were read as None or NaN
+        for col in X.select_dtypes(include=['object', 'category', 'bool']).columns:
+            X[col] = X[col].astype(str).where(X[col].notna(), np.nan)
         
         return X, y
     
-    def train_model(self, X: pd.DataFrame, y: pd.Series, task_type: TaskType, **kwargs):
-        """Train a model based on the task type."""
-        logger.info(f"Training {task_type.value} model...")
+    def make_preprocessor(self, X: pd.DataFrame):
+        """Imputes and encodes the features of `X` as configured: numbers by the
+        configured strategy, categories by their most frequent value or, with
+        `constant`, a "missing" category. Other columns are dropped."""
+        from sklearn.compose import ColumnTransformer
+        from sklearn.impute import SimpleImputer
+        from sklearn.pipeline import Pipeline
+        from sklearn.preprocessing import OneHotEncoder, OrdinalEncoder
         
-        # Import models here to avoid unnecessary imports
-        if task_type == TaskType.CLASSIFICATION:
-            from sklearn.ensemble import RandomForestClassifier
-            model = RandomForestClassifier(**kwargs)
-        elif task_type == TaskType.REGRESSION:
-            from sklearn.ensemble import RandomForestRegressor
-            model = RandomForestRegressor(**kwargs)
-        else:
+        categorical_cols = list(X.select_dtypes(include=['object', 'category', 'bool']).columns)
+        numeric_cols = list(X.select_dtypes(include='number').columns)
+        imputation = self.config["imputation"]
+        
+        transformers = []
+        if numeric_cols:
+            numeric = SimpleImputer(strategy=imputation, fill_value=0) if imputation == "constant" else SimpleImputer(strategy=imputation)
+            transformers.append(("numeric", numeric, numeric_cols))
+        if categorical_cols:
+            if imputation == "constant":
+                imputer = SimpleImputer(strategy="constant", fill_value="missing")
+            else:
+                imputer = SimpleImputer(strategy="most_frequent")
+            # Categories a fold or later data has that training didn't are ignored
+            if self.config["categorical_encoding"] == "ordinal":
+                encoder = OrdinalEncoder(handle_unknown="use_encoded_value", unknown_value=-1)
+            else:
+                encoder = OneHotEncoder(drop="first", handle_unknown="ignore")
+            transformers.append(("categorical", Pipeline([("impute", imputer), ("encode", encoder)]), categorical_cols))
+        
+        return ColumnTransformer(transformers, sparse_threshold=0, verbose_feature_names_out=False)
+    
+    def make_pipeline(self, task_type: TaskType, name: str, X: pd.DataFrame, **kwargs):
+        """A candidate estimator behind the preprocessing of `X`'s columns, so
+        both are fitted on the same rows and saved together."""
+        from sklearn.pipeline import Pipeline
+        
+        return Pipeline([
+            ("preprocess", self.make_preprocessor(X)),
+            ("model", self.make_model(task_type, name, **kwargs)),
+        ])
+    
+    def make_model(self, task_type: TaskType, name: str, **kwargs):
+        """Instantiate a candidate estimator by name, seeded and weighted as
+        configured. Keyword arguments it doesn't accept are left out."""
+        candidates = CANDIDATES.get(task_type)
+        if candidates is None:
             raise ValueError(f"Unsupported task type: {task_type}")
+        if name not in candidates:
+            raise ValueError(
+                f"Unknown {task_type.value} model '{name}'; expected one of {', '.join(candidates)}"
+            )
+        
+        module_name, class_name, defaults = candidates[name]
+        try:
+            module = importlib.import_module(module_name)
+        except ImportError:
+            raise ValueError(f"{name} needs the {module_name.split('.')[0]} package, which is not installed")
+        
+        model = getattr(module, class_name)(**defaults)
+        accepted = model.get_params()
+        if "random_state" in accepted:
+            model.set_params(random_state=self.config["seed"])
+        if task_type == TaskType.CLASSIFICATION and self.config["class_weight"] == "balanced":
+            if "class_weight" in accepted:
+                model.set_params(class_weight="balanced")
+            else:
+                logger.warning(f"{name} does not support class weighting")
+        params = {**(self.config["parameters"] or {}), **kwargs}
+        params = {key: value for key, value in params.items() if key in accepted}
+        if params:
+            model.set_params(**params)
+        return model
+    
+    def default_candidates(self, task_type: TaskType) -> List[str]:
+        """Candidates whose libraries are installed."""
+        return [
+            name
+            for name, (module_name, _, _) in CANDIDATES[task_type].items()
+            if importlib.util.find_spec(module_name.split(".")[0]) is not None
+        ]
+    
+    def configured_candidates(self, task_type: TaskType) -> List[str]:
+        """Candidates of the configured model families, or all installed ones."""
+        families = self.config["model_families"]
+        if not families:
+            return self.default_candidates(task_type)
+        return [name for name in CANDIDATES[task_type] if FAMILIES.get(name, name) in families]
+    
+    def cv_splitter(self, task_type: TaskType):
+        """Shuffled folds, stratified by class for classification if configured."""
+        from sklearn.model_selection import KFold, StratifiedKFold
+        
+        folds, seed = self.config["cv_folds"], self.config["seed"]
+        if task_type == TaskType.CLASSIFICATION and self.config["stratify"]:
+            return StratifiedKFold(n_splits=folds, shuffle=True, random_state=seed)
+        return KFold(n_splits=folds, shuffle=True, random_state=seed)
+    
+    def search(
+        self,
+        X: pd.DataFrame,
+        y: pd.Series,
+        task_type: TaskType,
+        max_trials: Optional[int] = None,
+        on_best=None,
+    ) -> tuple:
+        """Cross-validate each configured candidate and rank them by the
+        configured metric.
+        
+        Only the first `max_trials` candidates are tried. `on_best` is called with
+        each candidate that takes the lead, as soon as it does.
+        
+        Returns the metric and the leaderboard: one entry per candidate with its
+        parameters and mean cross-validated scores, best first, failed candidates last.
+        """
+        from sklearn.model_selection import cross_validate
+        
+        scorers = SCORERS[task_type]
+        metric = self.config["metric"] or DEFAULT_METRICS[task_type]
+        if metric not in scorers:
+            raise ValueError(
+                f"Unknown {task_type.value} metric '{metric}'; expected one of {', '.join(scorers)}"
+            )
+        names = self.configured_candidates(task_type)
+        if not names:
+            raise ValueError(f"No {task_type.value} model of families {', '.join(self.config['model_families'])}")
+        self.check_parameters(task_type, names)
+        cv = self.cv_splitter(task_type)
+        if max_trials is not None and len(names) > max_trials:
+            logger.warning(f"Trying {max_trials} of {len(names)} candidates (max_trials)")
+            names = names[:max_trials]
+            self.budget_exhausted = "max_trials"
+        self.task_type = task_type.value
+        self.metric = metric
+        lower_is_better = scorers[metric].startswith("neg_")
+        
+        leaderboard = []
+        for name in names:
+            entry = {"model": name, "params": {}, "metrics": {}, "status": "completed"}
+            try:
+                model = self.make_pipeline(task_type, name, X)
+                entry["params"] = _json_params(model.named_steps["model"])
+                logger.info(f"Cross-validating {name} with {cv.get_n_splits()} folds...")
+                scores = cross_validate(model, X, y, cv=cv, scoring=scorers, error_score="raise")
+                for key, scorer in scorers.items():
+                    values = scores[f"test_{key}"]
+                    if scorer.startswith("neg_"):
+                        values = -values
+                    entry["metrics"][key] = _finite(np.mean(values))
+                entry["metrics"]["fit_time"] = _finite(np.mean(scores["fit_time"]))
+                logger.info(f"{name}: {metric} = {entry['metrics'][metric]}")
+            except Exception as e:
+                logger.warning(f"{name} failed: {e}")
+                entry["status"] = "failed"
+                entry["error"] = str(e)
+            leaderboard.append(entry)
+            self.leaderboard = _rank(leaderboard, metric, lower_is_better)
+            if on_best is not None and self.leaderboard[0] is entry and entry["status"] == "completed":
+                on_best(entry)
+        
+        return metric, self.leaderboard
+    
+    def check_parameters(self, task_type: TaskType, names: List[str]):
+        """Fail on configured parameters none of the candidates accept, which are
+        most likely misspelt."""
+        parameters = self.config["parameters"] or {}
+        if not parameters:
+            return
+        accepted = set()
+        for name in names:
+            try:
+                accepted.update(self.make_model(task_type, name).get_params())
+            except ValueError:
+                pass  # Not installed; reported when it is cross-validated
+        unknown = sorted(set(parameters) - accepted)
+        if unknown:
+            raise ValueError(f"No candidate model takes parameters {', '.join(unknown)}")
+    
+    def prepare_series(self, data: pd.DataFrame, target_col: str) -> List[Dict[str, Any]]:
+        """Split the data into its series, each ordered by time with one value
+        per timestamp. Missing values are interpolated; other columns are
+        ignored, since their future values are unknown."""
+        timestamp_col, group_col = self.config["timestamp_column"], self.config["group_column"]
+        columns = [col for col in (timestamp_col, group_col, target_col) if col]
+        missing = [col for col in columns if col not in data.columns]
+        if missing:
+            raise ValueError(f"Columns not found: {', '.join(missing)}")
+        ignored = [col for col in data.columns if col not in columns]
+        if ignored:
+            logger.info(f"Ignoring columns {', '.join(map(str, ignored))}, which a forecast can't know ahead")
+        
+        data = data[columns].copy()
+        data[timestamp_col] = pd.to_datetime(data[timestamp_col], errors="coerce")
+        data[target_col] = pd.to_numeric(data[target_col], errors="coerce")
+        data = data.dropna(subset=[timestamp_col])
+        groups = data.groupby(group_col, sort=True) if group_col else [(None, data)]
+        
+        series = []
+        for key, frame in groups:
+            frame = frame.sort_values(timestamp_col).drop_duplicates(timestamp_col, keep="last")
+            values = frame[target_col].interpolate(limit_direction="both")
+            if values.isna().all():
+                logger.warning(f"Skipping series {key}, which has no values")
+                continue
+            series.append({
+                "key": key,
+                "code": len(series),
+                "timestamps": pd.DatetimeIndex(frame[timestamp_col]),
+                "values": values.to_numpy(dtype=float),
+            })
+        if not series:
+            raise ValueError(f"No values of {target_col} to forecast")
+        return series
+    
+    def backtest(self, series: List[Dict[str, Any]], forecaster, windows: int, horizon: int) -> Dict[str, Optional[float]]:
+        """Mean errors of forecasting each of the last `windows` windows of
+        `horizon` steps from the data before it, oldest first."""
+        scores = {metric: [] for metric in FORECASTING_METRICS}
+        for window in range(windows, 0, -1):
+            cutoffs = {s["code"]: len(s["values"]) - window * horizon for s in series}
+            timestamps = {s["code"]: s["timestamps"][cutoffs[s["code"]]:cutoffs[s["code"]] + horizon] for s in series}
+            predictions = forecaster(series, cutoffs, timestamps)
+            actual = np.concatenate([s["values"][cutoffs[s["code"]]:cutoffs[s["code"]] + horizon] for s in series])
+            predicted = np.concatenate([predictions[s["code"]] for s in series])
+            for metric, value in _forecast_errors(actual, predicted).items():
+                scores[metric].append(value)
+        return {
+            metric: _finite(np.mean(values)) if None not in values else None
+            for metric, values in scores.items()
+        }
+    
+    def make_forecaster(self, name: str, features: SeriesFeatures) -> tuple:
+        """A function forecasting series from their cutoffs with the named
+        baseline or model, and the model it fits, if any."""
+        if name in BASELINES:
+            return _baseline_forecaster(name, features.season_length), None
+        model = self.make_model(TaskType.FORECASTING, name)
+        
+        def forecast(series, cutoffs, timestamps):
+            X, y = features.frame(series, cutoffs)
+            model.fit(X, y)
+            return features.predict(model, series, cutoffs, timestamps)
+        return forecast, model
+    
+    def forecast(self, data: pd.DataFrame, target_col: str, max_trials: Optional[int] = None) -> tuple:
+        """Backtest the baselines and each configured model on the last
+        `cv_folds` windows of `horizon` steps, ranked by the configured metric.
+        
+        Each candidate that takes the lead forecasts `horizon` steps past the
+        end of the data from all of it, and is saved with its forecast right
+        away, so a search stopped by its budget leaves its best forecast behind.
+        
+        Returns the metric, the leaderboard and the best candidate's errors.
+        """
+        horizon, windows = self.config["horizon"], self.config["cv_folds"]
+        if not horizon:
+            raise ValueError("Forecasting needs a horizon")
+        metric = self.config["metric"] or DEFAULT_METRICS[TaskType.FORECASTING]
+        if metric not in FORECASTING_METRICS:
+            raise ValueError(f"Unknown forecasting metric '{metric}'; expected one of {', '.join(FORECASTING_METRICS)}")
+        self.task_type = TaskType.FORECASTING.value
+        self.metric = metric
+        
+        series = self.prepare_series(data, target_col)
+        longest = max(series, key=lambda s: len(s["values"]))
+        step = _frequency(longest["timestamps"])
+        shortest = min(len(s["values"]) for s in series)
+        season_length = self.config["season_length"] or _season_length(longest["timestamps"][-1], step)
+        features = SeriesFeatures(season_length)
+        # Each series needs history for its features and some rows to train on
+        # before the first backtest window
+        needed = features.min_history + windows * horizon + 2
+        if shortest < needed and not self.config["season_length"] and season_length > 1:
+            logger.warning(f"Series too short for a season of {season_length} steps; modelling none")
+            features = SeriesFeatures(1)
+            needed = features.min_history + windows * horizon + 2
+        if shortest < needed:
+            raise ValueError(
+                f"The shortest series has {shortest} steps; {windows} backtest windows of {horizon} steps "
+                f"need at least {needed}. Lower horizon, cv_folds or season_length."
+            )
+        logger.info(f"Forecasting {len(series)} series {horizon} steps ahead, with a season of {features.season_length}")
+        
+        names = self.configured_candidates(TaskType.FORECASTING)
+        if max_trials is not None and len(names) > max_trials:
+            logger.warning(f"Trying {max_trials} of {len(names)} models besides the baselines (max_trials)")
+            names = names[:max_trials]
+            self.budget_exhausted = "max_trials"
+        self.check_parameters(TaskType.FORECASTING, names)
+        
+        best = {}
+        leaderboard = []
+        for name in list(BASELINES) + names:
+            entry = {"model": name, "params": {}, "metrics": {}, "status": "completed"}
+            try:
+                forecaster, model = self.make_forecaster(name, features)
+                entry["params"] = _json_params(model) if model is not None else {"season_length": features.season_length}
+                logger.info(f"Backtesting {name} on {windows} windows...")
+                start = time.perf_counter()
+                entry["metrics"] = self.backtest(series, forecaster, windows, horizon)
+                entry["metrics"]["fit_time"] = _finite((time.perf_counter() - start) / windows)
+                logger.info(f"{name}: {metric} = {entry['metrics'][metric]}")
+            except Exception as e:
+                logger.warning(f"{name} failed: {e}")
+                entry["status"] = "failed"
+                entry["error"] = str(e)
+            leaderboard.append(entry)
+            self.leaderboard = _rank(leaderboard, metric, lower_is_better=True)
+            if self.leaderboard[0] is entry and entry["status"] == "completed":
+                best["metrics"] = {key: entry["metrics"][key] for key in FORECASTING_METRICS}
+                self.checkpoint_forecast(entry, forecaster, model, features, series, step, horizon)
+        
+        if not best:
+            errors = "; ".join(f"{e['model']}: {e.get('error')}" for e in leaderboard)
+            raise RuntimeError(f"No forecast could be made ({errors})")
+        return metric, self.leaderboard, best["metrics"]
+    
+    def checkpoint_forecast(self, entry, forecaster, model, features: SeriesFeatures, series, step, horizon: int):
+        """Forecast past the end of the data with a new best candidate and save
+        it, its forecast and the results so far."""
+        cutoffs = {s["code"]: len(s["values"]) for s in series}
+        future = {
+            s["code"]: pd.DatetimeIndex([s["timestamps"][-1] + step * k for k in range(1, horizon + 1)])
+            for s in series
+        }
+        predictions = forecaster(series, cutoffs, future)
+        self.save_forecast(series, future, predictions)
+        for name in FORECASTING_METRICS:
+            if entry["metrics"][name] is not None:
+                self.log_metric(name, entry["metrics"][name])
+        
+        if model is not None and hasattr(model, "feature_importances_"):
+            self.feature_importances = {
+                name: _finite(importance)
+                for name, importance in zip(features.names, model.feature_importances_)
+            }
+        else:
+            self.feature_importances = {}
+        self.best_model = entry["model"]
+        # What it takes to forecast again: the fitted model and how its
+        # features are built, or the baseline
+        self.save_model({
+            "model": model if model is not None else entry["model"],
+            "features": features.names,
+            "lags": features.lags,
+            "windows": features.windows,
+            "season_length": features.season_length,
+            "timestamp_column": self.config["timestamp_column"],
+            "group_column": self.config["group_column"],
+            "series": {s["code"]: s["key"] for s in series},
+        })
+        self.save_results()
+    
+    def save_forecast(self, series, timestamps: Dict[int, pd.DatetimeIndex], predictions: Dict[int, np.ndarray], filename: str = "forecast.csv"):
+        """Save the forecast of each series, one row per step."""
+        timestamp_col, group_col = self.config["timestamp_column"], self.config["group_column"]
+        frames = []
+        for s in series:
+            frame = pd.DataFrame({timestamp_col: timestamps[s["code"]], "forecast": predictions[s["code"]]})
+            if group_col:
+                frame.insert(0, group_col, s["key"])
+            frames.append(frame)
+        forecast_path = self.output_dir / filename
+        pd.concat(frames).to_csv(forecast_path, index=False)
+        logger.info(f"Forecast saved to {forecast_path}")
+        self.forecast_path = str(forecast_path)
+        return self.forecast_path
+    
+    def train_model(self, X: pd.DataFrame, y: pd.Series, task_type: TaskType, model_name: str = "random_forest", **kwargs):
+        """Train a candidate model, with its preprocessing, on the given data."""
+        logger.info(f"Training {task_type.value} model {model_name}...")
+        
+        model = self.make_pipeline(task_type, model_name, X, **kwargs)
         
         # Train the model
         model.fit(X, y)
         
-        # Store feature importances if available
-        if hasattr(model, 'feature_importances_'):
-            self.feature_importances = dict(zip(X.columns, model.feature_importances_))
+        # Store feature importances if available, by encoded feature
+        estimator = model.named_steps["model"]
+        if hasattr(estimator, 'feature_importances_'):
+            columns = model.named_steps["preprocess"].get_feature_names_out()
+            self.feature_importances = {
+                column: _finite(importance)
+                for column, importance in zip(columns, estimator.feature_importances_)
+            }
+        else:
+            self.feature_importances = {}
         
-        self.models[task_type.value] = model
+        self.models[model_name] = model
         return model
     
     def evaluate_model(self, model, X_test: pd.DataFrame, y_test: pd.Series, task_type: TaskType):
@@ -119,6 +726,8 @@ class AutoMLService:
                 "r2": r2_score(y_test, y_pred)
             }
         
+        metrics = {name: _finite(value) for name, value in metrics.items()}
+        
         # Log metrics
         for name, value in metrics.items():
             self.log_metric(name, value)
@@ -126,13 +735,14 @@ class AutoMLService:
         return metrics
     
     def save_model(self, model, filename: str = "model.pkl"):
-        """Save the trained model to disk."""
+        """Save the trained model to disk; pipelines predict from raw features."""
         import joblib
         
         model_path = self.output_dir / filename
         joblib.dump(model, model_path)
         logger.info(f"Model saved to {model_path}")
-        return str(model_path)
+        self.model_path = str(model_path)
+        return self.model_path
     
     def save_results(self, filename: str = "results.json"):
         """Save experiment results to a JSON file."""
@@ -141,7 +751,15 @@ class AutoMLService:
             "timestamp": datetime.utcnow().isoformat(),
             "metrics": self.metrics,
             "feature_importances": self.feature_importances,
-            "models": list(self.models.keys())
+            "models": list(self.models.keys()),
+            "leaderboard": self.leaderboard,
+            "task_type": self.task_type,
+            "metric": self.metric,
+            "config": self.config,
+            "best_model": self.best_model,
+            "model_path": self.model_path,
+            "forecast_path": self.forecast_path,
+            "budget_exhausted": self.budget_exhausted,
         }
         
         results_path = self.output_dir / filename
@@ -157,7 +775,8 @@ def run_automl(
     experiment_id: str,
     task_type: Optional[str] = None,
     test_size: float = 0.2,
-    **kwargs
+    max_trials: Optional[int] = None,
+    config: Optional[Dict[str, Any]] = None,
 ) -> Dict[str, Any]:
     """Run an AutoML pipeline on the given dataset.
     
@@ -165,9 +784,13 @@ def run_automl(
         data_path: Path to the input data file (CSV, Excel, etc.)
         target_col: Name of the target column
         experiment_id: Unique identifier for the experiment
-        task_type: Type of ML task ('classification', 'regression', or None for auto-detect)
-        test_size: Proportion of data to use for testing
-        **kwargs: Additional arguments to pass to the model
+        task_type: Type of ML task ('classification', 'regression', 'forecasting', or None
+            to detect classification or regression)
+        test_size: Proportion of data to use for testing; forecasts are tested by
+            backtesting instead
+        max_trials: Most candidates to try
+        config: Metric, folds, preprocessing, seed, model families and model
+            parameters of the search; see DEFAULT_CONFIG
         
     Returns:
         Dictionary containing experiment results
@@ -175,45 +798,92 @@ def run_automl(
     from sklearn.model_selection import train_test_split
     
     # Initialize the AutoML service
-    automl = AutoMLService(experiment_id)
+    automl = AutoMLService(experiment_id, config=config)
+    seed = automl.config["seed"]
     
     try:
         # Load data
         logger.info(f"Loading data from {data_path}")
-        data = pd.read_csv(data_path)  # Add support for other formats if needed
+        data = load_data(data_path, automl.config["input_format"])
+        
+        # Forecasts are split by time rather than at random
+        if task_type is not None and TaskType(task_type.lower()) == TaskType.FORECASTING:
+            metric, leaderboard, metrics = automl.forecast(data, target_col, max_trials)
+            results_path = automl.save_results()
+            return {
+                "status": "success",
+                "experiment_id": experiment_id,
+                "task_type": TaskType.FORECASTING.value,
+                "metric": metric,
+                "config": automl.config,
+                "best_model": automl.best_model,
+                "leaderboard": leaderboard,
+                "metrics": metrics,
+                "model_path": automl.model_path,
+                "forecast_path": automl.forecast_path,
+                "results_path": results_path,
+                "feature_importances": automl.feature_importances,
+                "budget_exhausted": automl.budget_exhausted,
+            }
         
         # Preprocess data
         X, y = automl.preprocess_data(data, target_col)
         
-        # Split data
-        X_train, X_test, y_train, y_test = train_test_split(
-            X, y, test_size=test_size, random_state=42
-        )
-        
         # Determine task type if not specified
         if task_type is None:
             task_type_enum = automl.detect_task_type(y)
         else:
             task_type_enum = TaskType(task_type.lower())
         
-        # Train model
-        model = automl.train_model(X_train, y_train, task_type_enum, **kwargs)
+        # Some libraries need classes numbered from 0
+        classes = None
+        if task_type_enum == TaskType.CLASSIFICATION:
+            from sklearn.preprocessing import LabelEncoder
+            encoder = LabelEncoder()
+            y = pd.Series(encoder.fit_transform(y), index=y.index, name=y.name)
+            classes = [str(c) for c in encoder.classes_]
         
-        # Evaluate model
-        metrics = automl.evaluate_model(model, X_test, y_test, task_type_enum)
+        # Split data
+        stratify = y if task_type_enum == TaskType.CLASSIFICATION and automl.config["stratify"] else None
+        X_train, X_test, y_train, y_test = train_test_split(
+            X, y, test_size=test_size, random_state=seed, stratify=stratify
+        )
         
-        # Save artifacts
-        model_path = automl.save_model()
+        # Refit each candidate that takes the lead and evaluate it on the
+        # held-out set, saving it right away so that a search stopped by its
+        # time or memory budget still leaves its best model behind
+        best = {}
+        
+        def checkpoint(entry):
+            model = automl.train_model(X_train, y_train, task_type_enum, entry["model"])
+            best["metrics"] = automl.evaluate_model(model, X_test, y_test, task_type_enum)
+            automl.best_model = entry["model"]
+            automl.save_model(model)
+            automl.save_results()
+        
+        # Rank the candidates by cross-validation on the training set
+        metric, leaderboard = automl.search(
+            X_train, y_train, task_type_enum, max_trials=max_trials, on_best=checkpoint,
+        )
+        if not best:
+            errors = "; ".join(f"{e['model']}: {e.get('error')}" for e in leaderboard)
+            raise RuntimeError(f"No candidate model could be trained ({errors or 'none given'})")
         results_path = automl.save_results()
         
         return {
             "status": "success",
             "experiment_id": experiment_id,
             "task_type": task_type_enum.value,
-            "metrics": metrics,
-            "model_path": model_path,
+            "metric": metric,
+            "config": automl.config,
+            "best_model": automl.best_model,
+            "classes": classes,
+            "leaderboard": leaderboard,
+            "metrics": best["metrics"],
+            "model_path": automl.model_path,
             "results_path": results_path,
-            "feature_importances": automl.feature_importances
+            "feature_importances": automl.feature_importances,
+            "budget_exhausted": automl.budget_exhausted,
         }
         
     except Exception as e:
@@ -236,6 +906,10 @@ if __name__ == "__main__":
                        help="Type of ML task (default: auto-detect)")
     parser.add_argument("--test-size", type=float, default=0.2, 
                        help="Proportion of data to use for testing")
+    parser.add_argument("--max-trials", type=int,
+                       help="Most candidates to try")
+    parser.add_argument("--config",
+                       help="JSON file configuring the search (default: DEFAULT_CONFIG)")
     
     args = parser.parse_args()
     
@@ -244,7 +918,9 @@ if __name__ == "__main__":
         target_col=args.target,
         experiment_id=args.experiment_id,
         task_type=args.task_type,
-        test_size=args.test_size
+        test_size=args.test_size,
+        max_trials=args.max_trials,
+        config=load_config(args.config),
     )
     
     print("\nAutoML Results:")
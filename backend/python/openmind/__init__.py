@@ -0,0 +1,5 @@
+"""Python helpers shipped with the OpenMind backend."""
+
+from . import track
+
+__all__ = ["track"]
@@ -0,0 +1,352 @@
+"""Report runs to the OpenMind experiment tracker.
+
+Example:
+    from openmind import track
+
+    with track.start_run("baseline", params={"model": "rf"}):
+        track.log_param("max_depth", 8)
+        for epoch in range(10):
+            track.log_metric("loss", train_one_epoch(), step=epoch)
+        track.log_artifact("model.pkl")
+
+The run is marked completed when the block exits and failed if it raises.
+Code run from a notebook is attributed to that notebook automatically. The
+interpreter, installed packages, script and seeds are recorded with the run so
+it can be rerun with the same setup.
+"""
+
+import hashlib
+import json
+import logging
+import mimetypes
+import os
+import platform
+import random
+import sys
+import urllib.error
+import urllib.parse
+import urllib.request
+from datetime import datetime, timezone
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Union
+
+logger = logging.getLogger(__name__)
+
+DEFAULT_API_URL = "http://127.0.0.1:3001/api"
+
+
+class TrackingError(Exception):
+    """Raised when the tracker rejects a request or can't be reached."""
+
+
+def _api_url() -> str:
+    return os.environ.get("OPENMIND_API_URL", DEFAULT_API_URL).rstrip("/")
+
+
+def _request(
+    method: str,
+    path: str,
+    payload: Optional[Dict[str, Any]] = None,
+    data: Any = None,
+    headers: Optional[Dict[str, str]] = None,
+) -> Any:
+    """Sends a request to the experiments API and returns the response's `data`."""
+    headers = dict(headers or {})
+    if payload is not None:
+        data = json.dumps(payload).encode("utf-8")
+        headers["Content-Type"] = "application/json"
+
+    request = urllib.request.Request(
+        f"{_api_url()}/experiments{path}", data=data, method=method, headers=headers
+    )
+    try:
+        with urllib.request.urlopen(request) as response:
+            body = response.read()
+    except urllib.error.HTTPError as e:
+        body = e.read()
+        try:
+            message = json.loads(body).get("error") or body.decode("utf-8", "replace")
+        except ValueError:
+            message = body.decode("utf-8", "replace")
+        raise TrackingError(f"{method} {path} failed ({e.code}): {message}") from e
+    except urllib.error.URLError as e:
+        raise TrackingError(
+            f"Could not reach the experiment tracker at {_api_url()}: {e.reason}"
+        ) from e
+
+    if not body:
+        return None
+    response = json.loads(body)
+    # Experiment endpoints wrap results as {"success", "data", "error"}
+    if isinstance(response, dict) and "success" in response:
+        return response.get("data")
+    return response
+
+
+def _timestamp() -> str:
+    return datetime.now(timezone.utc).replace(tzinfo=None).isoformat()
+
+
+def _seed(seeds: Dict[str, int]) -> Dict[str, int]:
+    """Seeds the random number generators named in `seeds`.
+
+    numpy is seeded if installed; torch and tensorflow only if already imported,
+    as importing them is slow.
+
+    Returns:
+        The seeds applied
+    """
+    applied = {}
+    for library, seed in seeds.items():
+        try:
+            if library == "random":
+                random.seed(seed)
+            elif library == "numpy":
+                import numpy
+
+                numpy.random.seed(seed)
+            elif library == "torch" and "torch" in sys.modules:
+                sys.modules["torch"].manual_seed(seed)
+            elif library == "tensorflow" and "tensorflow" in sys.modules:
+                sys.modules["tensorflow"].random.set_seed(seed)
+            else:
+                continue
+        except ImportError:
+            continue
+        applied[library] = seed
+    return applied
+
+
+def _packages() -> str:
+    """Installed distributions in `pip freeze` format."""
+    from importlib import metadata
+
+    packages = {
+        f"{dist.metadata['Name']}=={dist.version}"
+        for dist in metadata.distributions()
+        if dist.metadata["Name"]
+    }
+    return "\n".join(sorted(packages, key=str.lower))
+
+
+def _setup(seeds: Optional[Dict[str, int]]) -> Dict[str, Any]:
+    """What this process runs with, for reproducing the run."""
+    setup: Dict[str, Any] = {
+        "python_version": platform.python_version(),
+        "packages": _packages(),
+        "seeds": seeds or None,
+    }
+    script = getattr(sys.modules.get("__main__"), "__file__", None)
+    if script and os.path.isfile(script):
+        with open(script, "rb") as f:
+            setup["script_sha256"] = hashlib.sha256(f.read()).hexdigest()
+        setup["script_path"] = os.path.abspath(script)
+    return setup
+
+
+class Run:
+    """An experiment being tracked. Use as a context manager to end it automatically."""
+
+    def __init__(self, experiment: Dict[str, Any]):
+        self.id: str = experiment["id"]
+        self.name: str = experiment["name"]
+        self.experiment = experiment
+        self._ended = False
+
+    def __repr__(self) -> str:
+        return f"Run(id={self.id!r}, name={self.name!r})"
+
+    def __enter__(self) -> "Run":
+        return self
+
+    def __exit__(self, exc_type, exc, tb) -> bool:
+        if exc is None:
+            self.end("completed")
+        else:
+            self.end("failed", failure_reason=f"{exc_type.__name__}: {exc}")
+        # Never swallow the exception
+        return False
+
+    def log_param(self, key: str, value: Any) -> None:
+        self.log_params({key: value})
+
+    def log_params(self, params: Dict[str, Any]) -> None:
+        _request("POST", f"/{self.id}/params", {"params": params})
+
+    def log_metric(self, key: str, value: float, step: Optional[int] = None) -> None:
+        """Logs a metric value. Without a step, it follows the key's previous one."""
+        self.log_metrics({key: value}, step=step)
+
+    def log_metrics(self, metrics: Dict[str, float], step: Optional[int] = None) -> None:
+        timestamp = _timestamp()
+        points = [
+            {"key": key, "value": float(value), "step": step, "timestamp": timestamp}
+            for key, value in metrics.items()
+        ]
+        _request("POST", f"/{self.id}/metrics", {"metrics": points})
+
+    def log_artifact(self, local_path: Union[str, Path], artifact_path: Optional[str] = None) -> List[str]:
+        """Uploads a file, or every file under a directory, as artifacts of the run.
+
+        Args:
+            local_path: File or directory to upload
+            artifact_path: Path to store it under; the file or directory name by default
+
+        Returns:
+            The artifact paths written
+        """
+        local_path = Path(local_path)
+        prefix = (artifact_path or local_path.name).strip("/")
+        if local_path.is_dir():
+            files = [
+                (file, f"{prefix}/{file.relative_to(local_path).as_posix()}")
+                for file in sorted(local_path.rglob("*"))
+                if file.is_file()
+            ]
+        else:
+            files = [(local_path, prefix)]
+
+        uploaded = []
+        for file, path in files:
+            content_type = mimetypes.guess_type(file.name)[0] or "application/octet-stream"
+            with open(file, "rb") as f:
+                _request(
+                    "PUT",
+                    f"/{self.id}/artifacts/{urllib.parse.quote(path)}",
+                    data=f,
+                    headers={
+                        "Content-Type": content_type,
+                        "Content-Length": str(file.stat().st_size),
+                    },
+                )
+            uploaded.append(path)
+        return uploaded
+
+    def log(self, message: str, level: str = "info") -> None:
+        """Adds a line to the run's log."""
+        _request("POST", f"/{self.id}/logs", {"level": level, "message": message})
+
+    def set_status(self, status: str, failure_reason: Optional[str] = None) -> None:
+        """Moves the run to `status`; the tracker rejects moves its lifecycle doesn't allow."""
+        _request("PUT", f"/{self.id}", {"status": status, "failure_reason": failure_reason})
+
+    def end(self, status: str = "completed", failure_reason: Optional[str] = None) -> None:
+        """Sets the final status and stops tracking to this run.
+
+        Args:
+            status: "completed", "failed" or "cancelled"
+            failure_reason: Why the run failed, shown with it
+        """
+        if self._ended:
+            return
+        self._ended = True
+        try:
+            self.set_status(status, failure_reason)
+        finally:
+            if self in _active_runs:
+                _active_runs.remove(self)
+
+
+_active_runs: List[Run] = []
+
+
+def start_run(
+    name: Optional[str] = None,
+    params: Optional[Dict[str, Any]] = None,
+    dataset_id: Optional[str] = None,
+    notebook_id: Optional[str] = None,
+    seed: Optional[int] = None,
+) -> Run:
+    """Creates an experiment and makes it the active run.
+
+    When the backend reruns an experiment, the first run started reports to the
+    rerun instead, seeded like the original.
+
+    Args:
+        name: Experiment name; `run-<date>-<time>` by default
+        params: Initial parameters
+        dataset_id: Dataset the run trains on, recorded with its current version
+        notebook_id: Notebook to attribute the run to; set by the kernel when run from a notebook
+        seed: Seed for random, numpy, torch and tensorflow, recorded with the run
+
+    Returns:
+        The run, which also works as a context manager
+    """
+    notebook_id = notebook_id or os.environ.get("OPENMIND_NOTEBOOK_ID")
+    if name is None:
+        name = f"run-{datetime.now().strftime('%Y%m%d-%H%M%S')}"
+
+    seeds = None
+    if "OPENMIND_SEEDS" in os.environ:
+        seeds = _seed(json.loads(os.environ.pop("OPENMIND_SEEDS")))
+    elif seed is not None:
+        seeds = _seed({library: seed for library in ("random", "numpy", "torch", "tensorflow")})
+
+    run_id = os.environ.pop("OPENMIND_RUN_ID", None)
+    if run_id:
+        run = Run(_request("GET", f"/{run_id}"))
+        if params:
+            run.log_params(params)
+    else:
+        experiment = _request(
+            "POST",
+            "/",
+            {
+                "name": name,
+                "notebook_id": notebook_id,
+                "dataset_id": dataset_id,
+                "parameters": params,
+            },
+        )
+        run = Run(experiment)
+
+    try:
+        _request("PUT", f"/{run.id}/reproducibility", _setup(seeds))
+    except TrackingError as e:
+        logger.warning(f"Could not record the setup of run {run.id}: {e}")
+    run.set_status("running")
+    _active_runs.append(run)
+    logger.info(f"Started run {run.name} ({run.id})")
+    return run
+
+
+def active_run() -> Optional[Run]:
+    return _active_runs[-1] if _active_runs else None
+
+
+def _require_run() -> Run:
+    run = active_run()
+    if run is None:
+        raise TrackingError("No active run; call start_run() first")
+    return run
+
+
+def end_run(status: str = "completed", failure_reason: Optional[str] = None) -> None:
+    """Ends the active run, if any."""
+    run = active_run()
+    if run is not None:
+        run.end(status, failure_reason)
+
+
+def log_param(key: str, value: Any) -> None:
+    _require_run().log_param(key, value)
+
+
+def log_params(params: Dict[str, Any]) -> None:
+    _require_run().log_params(params)
+
+
+def log_metric(key: str, value: float, step: Optional[int] = None) -> None:
+    _require_run().log_metric(key, value, step=step)
+
+
+def log_metrics(metrics: Dict[str, float], step: Optional[int] = None) -> None:
+    _require_run().log_metrics(metrics, step=step)
+
+
+def log_artifact(local_path: Union[str, Path], artifact_path: Optional[str] = None) -> List[str]:
+    return _require_run().log_artifact(local_path, artifact_path)
+
+
+def log(message: str, level: str = "info") -> None:
+    _require_run().log(message, level=level)
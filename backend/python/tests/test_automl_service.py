@@ -0,0 +1,133 @@
+"""Tests for automl/service.py on small synthetic datasets and series.
+
+Run from `backend/python` with `python -m unittest discover -s tests`; they are
+skipped where numpy, pandas or scikit-learn aren't installed.
+"""
+
+import importlib.util
+import tempfile
+import unittest
+from pathlib import Path
+
+MISSING = [
+    name for name in ("numpy", "pandas", "sklearn", "joblib")
+    if importlib.util.find_spec(name) is None
+]
+
+if not MISSING:
+    import joblib
+    import numpy as np
+    import pandas as pd
+
+    spec = importlib.util.spec_from_file_location(
+        "service", Path(__file__).resolve().parent.parent / "automl" / "service.py"
+    )
+    service = importlib.util.module_from_spec(spec)
+    spec.loader.exec_module(service)
+
+
+@unittest.skipIf(MISSING, f"needs {', '.join(MISSING)}")
+class PreprocessingTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.automl = service.AutoMLService("exp-1", self.dir.name)
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def test_imputation_and_encoding_are_learnt_from_the_fitted_rows(self):
+        train, _ = self.automl.preprocess_data(pd.DataFrame({
+            "size": [1.0, 3.0, np.nan, 5.0],
+            "color": ["red", "red", "blue", None],
+            "price": [1.0, 2.0, 3.0, 4.0],
+        }), "price")
+        test = pd.DataFrame({"size": [np.nan, 100.0], "color": [np.nan, "green"]})
+
+        preprocessor = self.automl.make_preprocessor(train).fit(train)
+        self.assertEqual(list(preprocessor.get_feature_names_out()), ["size", "color_red"])
+        # The median and most frequent color of the training rows, whatever
+        # the rows transformed hold; unseen colors encode as the dropped one
+        np.testing.assert_array_equal(preprocessor.transform(test), [[3.0, 1.0], [100.0, 0.0]])
+
+    def test_saved_model_predicts_from_raw_features(self):
+        rng = np.random.default_rng(0)
+        X, y = self.automl.preprocess_data(pd.DataFrame({
+            "size": np.where(rng.random(40) < 0.2, np.nan, rng.random(40)),
+            "color": rng.choice(["red", "blue", None], 40),
+            "price": rng.random(40),
+        }), "price")
+
+        model = self.automl.train_model(X, y, service.TaskType.REGRESSION, "ridge")
+        saved = joblib.load(self.automl.save_model(model))
+        np.testing.assert_allclose(saved.predict(X.head(5)), model.predict(X.head(5)))
+
+
+@unittest.skipIf(MISSING, f"needs {', '.join(MISSING)}")
+class ForecastingTest(unittest.TestCase):
+    PATTERN = [10.0, 20.0, 15.0, 30.0, 25.0, 5.0, 12.0]
+
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.automl = service.AutoMLService("exp-1", self.dir.name, config={
+            "timestamp_column": "date", "horizon": 7, "cv_folds": 3, "model_families": ["linear"],
+        })
+        days = 8 * len(self.PATTERN)
+        self.data = pd.DataFrame({
+            "date": pd.date_range("2024-01-01", periods=days, freq="D"),
+            "sales": self.PATTERN * 8,
+        })
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def test_backtest_never_trains_past_the_cutoff(self):
+        series = self.automl.prepare_series(self.data, "sales")
+        features = service.SeriesFeatures(7)
+        forecaster, _ = self.automl.make_forecaster("ridge", features)
+        # A copy of the series that differs only past each window's cutoff
+        # must be forecast the same
+        forecasts = []
+
+        def recording(series, cutoffs, timestamps):
+            forecast = forecaster(series, cutoffs, timestamps)
+            for s in series:
+                self.assertEqual(timestamps[s["code"]][0], s["timestamps"][cutoffs[s["code"]]])
+                poisoned = dict(s, values=s["values"].copy())
+                poisoned["values"][cutoffs[s["code"]]:] = 1e6
+                forecasts.append((forecast[s["code"]], forecaster([poisoned], cutoffs, timestamps)[s["code"]]))
+            return forecast
+
+        self.automl.backtest(series, recording, windows=3, horizon=7)
+        self.assertEqual(len(forecasts), 3)
+        for forecast, poisoned in forecasts:
+            np.testing.assert_allclose(forecast, poisoned)
+
+    def test_training_rows_end_before_the_cutoff(self):
+        series = self.automl.prepare_series(self.data, "sales")
+        features = service.SeriesFeatures(7)
+        X, y = features.frame(series, {0: 30})
+        self.assertEqual(len(y), 30 - features.min_history)
+        np.testing.assert_array_equal(y, series[0]["values"][features.min_history:30])
+
+    def test_seasonal_naive_repeats_a_seasonal_pattern_exactly(self):
+        series = self.automl.prepare_series(self.data, "sales")
+        forecaster = service._baseline_forecaster("seasonal_naive", len(self.PATTERN))
+        errors = self.automl.backtest(series, forecaster, windows=3, horizon=7)
+        self.assertEqual(errors, {"mae": 0.0, "rmse": 0.0, "mape": 0.0})
+
+        future = {0: pd.date_range("2024-02-26", periods=10, freq="D")}
+        forecast = forecaster(series, {0: len(self.data)}, future)[0]
+        np.testing.assert_array_equal(forecast, (self.PATTERN * 2)[:10])
+
+    def test_forecast_picks_seasonal_naive_on_a_pure_season(self):
+        metric, leaderboard, errors = self.automl.forecast(self.data, "sales")
+        self.assertEqual(metric, "mae")
+        self.assertEqual(leaderboard[0]["model"], "seasonal_naive")
+        self.assertEqual(leaderboard[0]["params"], {"season_length": 7})
+        self.assertEqual(errors["mae"], 0.0)
+        forecast = pd.read_csv(self.automl.forecast_path)
+        np.testing.assert_array_equal(forecast["forecast"], self.PATTERN)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,148 @@
+"""Tests for openmind.track against a stand-in experiments API.
+
+Run from `backend/python` with `python -m unittest discover -s tests`.
+"""
+
+import json
+import os
+import random
+import tempfile
+import threading
+import unittest
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from pathlib import Path
+
+from openmind import track
+
+
+class FakeTracker(BaseHTTPRequestHandler):
+    """Records requests and answers like the experiments API."""
+
+    requests = []
+
+    def _handle(self):
+        length = int(self.headers.get("Content-Length") or 0)
+        body = self.rfile.read(length) if length else b""
+        if self.headers.get("Content-Type") == "application/json":
+            body = json.loads(body)
+        path = self.path.removeprefix("/api/experiments")
+        FakeTracker.requests.append((self.command, path, body))
+
+        if path == "/missing":
+            self._reply(404, {"success": False, "data": None, "error": "Experiment not found"})
+        elif self.command == "POST" and path == "/":
+            self._reply(200, {"success": True, "data": {"id": "exp-1", "name": body["name"]}})
+        elif self.command == "GET":
+            self._reply(200, {"success": True, "data": {"id": path[1:], "name": "rerun"}})
+        else:
+            self._reply(200, {"success": True, "data": None})
+
+    def _reply(self, status, payload):
+        body = json.dumps(payload).encode("utf-8")
+        self.send_response(status)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    do_GET = do_POST = do_PUT = _handle
+
+    def log_message(self, *args):
+        pass
+
+
+class TrackTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.server = HTTPServer(("127.0.0.1", 0), FakeTracker)
+        threading.Thread(target=cls.server.serve_forever, daemon=True).start()
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.server.shutdown()
+        cls.server.server_close()
+
+    def setUp(self):
+        FakeTracker.requests = []
+        self.env = {
+            key: os.environ.get(key)
+            for key in ("OPENMIND_API_URL", "OPENMIND_NOTEBOOK_ID", "OPENMIND_RUN_ID", "OPENMIND_SEEDS")
+        }
+        os.environ["OPENMIND_API_URL"] = f"http://127.0.0.1:{self.server.server_port}/api"
+        os.environ.pop("OPENMIND_RUN_ID", None)
+        os.environ.pop("OPENMIND_SEEDS", None)
+        os.environ["OPENMIND_NOTEBOOK_ID"] = "nb-1"
+
+    def tearDown(self):
+        while track.active_run() is not None:
+            track.active_run()._ended = True
+            track._active_runs.pop()
+        for key, value in self.env.items():
+            if value is None:
+                os.environ.pop(key, None)
+            else:
+                os.environ[key] = value
+
+    def sent(self, method, path):
+        return [body for m, p, body in FakeTracker.requests if (m, p) == (method, path)]
+
+    def test_run_is_created_logged_to_and_completed(self):
+        with track.start_run("baseline", params={"model": "rf"}, seed=7) as run:
+            self.assertIs(track.active_run(), run)
+            track.log_param("max_depth", 8)
+            track.log_metric("loss", 0.5, step=1)
+
+        created = self.sent("POST", "/")[0]
+        self.assertEqual(created["name"], "baseline")
+        self.assertEqual(created["notebook_id"], "nb-1")
+        self.assertEqual(created["parameters"], {"model": "rf"})
+        setup = self.sent("PUT", "/exp-1/reproducibility")[0]
+        self.assertEqual(setup["seeds"]["random"], 7)
+        self.assertIn("python_version", setup)
+        self.assertEqual(self.sent("POST", "/exp-1/params"), [{"params": {"max_depth": 8}}])
+        metric = self.sent("POST", "/exp-1/metrics")[0]["metrics"][0]
+        self.assertEqual((metric["key"], metric["value"], metric["step"]), ("loss", 0.5, 1))
+        statuses = [body["status"] for body in self.sent("PUT", "/exp-1")]
+        self.assertEqual(statuses, ["running", "completed"])
+        self.assertIsNone(track.active_run())
+
+    def test_failing_run_is_marked_failed_and_reraises(self):
+        with self.assertRaises(ValueError):
+            with track.start_run("broken"):
+                raise ValueError("bad input")
+
+        final = self.sent("PUT", "/exp-1")[-1]
+        self.assertEqual(final["status"], "failed")
+        self.assertEqual(final["failure_reason"], "ValueError: bad input")
+
+    def test_rerun_reports_to_the_existing_experiment(self):
+        os.environ["OPENMIND_RUN_ID"] = "exp-9"
+        os.environ["OPENMIND_SEEDS"] = json.dumps({"random": 3})
+        run = track.start_run("ignored")
+        self.assertEqual(run.id, "exp-9")
+        self.assertEqual(random.random(), random.Random(3).random())
+        self.assertEqual(self.sent("POST", "/"), [])
+        self.assertNotIn("OPENMIND_RUN_ID", os.environ)
+        run.end()
+
+    def test_directory_artifacts_keep_their_layout(self):
+        run = track.start_run("artifacts")
+        with tempfile.TemporaryDirectory() as dir:
+            (Path(dir) / "plots").mkdir()
+            (Path(dir) / "plots" / "roc.png").write_bytes(b"png")
+            (Path(dir) / "model.pkl").write_bytes(b"weights")
+            uploaded = run.log_artifact(dir, "outputs")
+
+        self.assertEqual(uploaded, ["outputs/model.pkl", "outputs/plots/roc.png"])
+        self.assertEqual(self.sent("PUT", "/exp-1/artifacts/outputs/model.pkl"), [b"weights"])
+        run.end()
+
+    def test_errors_carry_the_tracker_message(self):
+        with self.assertRaisesRegex(track.TrackingError, "404.*Experiment not found"):
+            track._request("GET", "/missing")
+        with self.assertRaisesRegex(track.TrackingError, "No active run"):
+            track.log_metric("loss", 1.0)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,208 @@
+use axum::{
+    body::Body,
+    extract::{Multipart, Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::StreamExt;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    error::{AppError, Result},
+    models::{Database, ExperimentArtifact},
+    tracking::{
+        artifacts::{self, RangeNotSatisfiable},
+        ArtifactStore,
+    },
+};
+
+async fn ensure_experiment(db: &Database, id: &str) -> Result<()> {
+    db.get_experiment(id)
+        .await?
+        .map(|_| ())
+        .ok_or_else(|| AppError::NotFound("Experiment not found".to_string()))
+}
+
+pub(super) fn artifact_path(path: &str) -> Result<String> {
+    artifacts::normalize_path(path).map_err(|e| AppError::BadRequest(e.to_string()))
+}
+
+pub async fn list_artifacts(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<ExperimentArtifact>>> {
+    ensure_experiment(&db, &id).await?;
+    Ok(Json(db.list_experiment_artifacts(&id).await?))
+}
+
+/// Stores each `file` part of a multipart upload under its file name.
+pub async fn upload_artifacts(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<Vec<ExperimentArtifact>>> {
+    ensure_experiment(&db, &id).await?;
+
+    let store = ArtifactStore::default();
+    let mut saved = Vec::new();
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let path = artifact_path(field.file_name().unwrap_or_default())?;
+        let mime_type = field
+            .content_type()
+            .filter(|mime| *mime != "application/octet-stream")
+            .map(ToString::to_string)
+            .unwrap_or_else(|| artifacts::guess_mime_type(&path).to_string());
+
+        let mut writer = store.writer().await?;
+        while let Some(chunk) = field
+            .chunk()
+            .await
+            .map_err(|e| AppError::BadRequest(e.to_string()))?
+        {
+            writer.write(&chunk).await?;
+        }
+        let blob = writer.finish().await?;
+
+        saved.push(
+            db.save_experiment_artifact(&id, &path, &blob.sha256, blob.size, &mime_type)
+                .await?,
+        );
+    }
+
+    if saved.is_empty() {
+        return Err(AppError::BadRequest("No file provided".to_string()));
+    }
+    Ok(Json(saved))
+}
+
+/// Stores the request body as the artifact at `path`.
+pub async fn put_artifact(
+    State(db): State<Arc<Database>>,
+    Path((id, path)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<ExperimentArtifact>> {
+    ensure_experiment(&db, &id).await?;
+    let path = artifact_path(&path)?;
+    Ok(Json(save_body(&db, &id, &path, &headers, body).await?))
+}
+
+/// Streams a request body into the store and records it as the artifact at `path`.
+pub(super) async fn save_body(
+    db: &Database,
+    id: &str,
+    path: &str,
+    headers: &HeaderMap,
+    body: Body,
+) -> Result<ExperimentArtifact> {
+    let mime_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|mime| *mime != "application/octet-stream")
+        .map(ToString::to_string)
+        .unwrap_or_else(|| artifacts::guess_mime_type(path).to_string());
+
+    let store = ArtifactStore::default();
+    let mut writer = store.writer().await?;
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| AppError::BadRequest(e.to_string()))?;
+        writer.write(&chunk).await?;
+    }
+    let blob = writer.finish().await?;
+
+    let artifact = db
+        .save_experiment_artifact(id, path, &blob.sha256, blob.size, &mime_type)
+        .await?;
+    Ok(artifact)
+}
+
+/// Streams an artifact, honouring single `Range` requests and `If-None-Match`.
+pub async fn download_artifact(
+    State(db): State<Arc<Database>>,
+    Path((id, path)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let path = artifact_path(&path)?;
+    let artifact = db
+        .get_experiment_artifact(&id, &path)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Artifact not found".to_string()))?;
+    serve_artifact(&artifact, &headers).await
+}
+
+pub(super) async fn serve_artifact(
+    artifact: &ExperimentArtifact,
+    headers: &HeaderMap,
+) -> Result<Response> {
+    // Content-addressed, so the hash is a strong validator
+    let etag = format!("\"{}\"", artifact.sha256);
+    if headers
+        .get(header::IF_NONE_MATCH)
+        .is_some_and(|value| value.as_bytes() == etag.as_bytes())
+    {
+        return Ok(StatusCode::NOT_MODIFIED.into_response());
+    }
+
+    let size = artifact.size as u64;
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok());
+    let range = match artifacts::parse_range(range, size) {
+        Ok(range) => range,
+        Err(RangeNotSatisfiable) => {
+            return Ok((
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{}", size))],
+            )
+                .into_response());
+        }
+    };
+
+    let mut file =
+        tokio::fs::File::open(ArtifactStore::default().blob_path(&artifact.sha256)).await?;
+    let path = &artifact.path;
+    let file_name = path.rsplit('/').next().unwrap_or(path).replace('"', "");
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, &artifact.mime_type)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, &etag)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file_name),
+        );
+
+    let body = match range {
+        Some((start, end)) => {
+            let length = end - start + 1;
+            file.seek(SeekFrom::Start(start)).await?;
+            response = response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_LENGTH, length)
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", start, end, size),
+                );
+            Body::from_stream(ReaderStream::new(file.take(length)))
+        }
+        None => {
+            response = response
+                .status(StatusCode::OK)
+                .header(header::CONTENT_LENGTH, size);
+            Body::from_stream(ReaderStream::new(file))
+        }
+    };
+
+    Ok(response.body(body).map_err(anyhow::Error::from)?)
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
@@ -43,8 +43,9 @@ pub async fn start_automl_experiment(
     
     match automl_service.start_automl(payload, None).await {
         Ok(response) => (StatusCode::ACCEPTED, Json(ApiResponse::success(response))),
+        // Invalid requests and missing datasets are the client's to fix
         Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            e.status_code(),
             Json(ApiResponse::error(&format!("Failed to start AutoML experiment: {}", e))),
         ),
     }
@@ -57,7 +58,11 @@ pub async fn get_experiment_status(
     let automl_service = AutoMLService::new(db);
     
     match automl_service.get_experiment_status(&experiment_id).await {
-        Ok(response) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Ok(Some(response)) => (StatusCode::OK, Json(ApiResponse::success(response))),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Experiment not found")),
+        ),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ApiResponse::error(&format!("Failed to get experiment status: {}", e))),
@@ -65,10 +70,37 @@ pub async fn get_experiment_status(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    /// Metric to rank by instead of the one the search used
+    pub metric: Option<String>,
+}
+
+pub async fn get_leaderboard(
+    State(db): State<Arc<Database>>,
+    Path(experiment_id): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+) -> impl IntoResponse {
+    let automl_service = AutoMLService::new(db);
+    
+    match automl_service.get_leaderboard(&experiment_id, query.metric.as_deref()).await {
+        Ok(Some(leaderboard)) => (StatusCode::OK, Json(ApiResponse::success(leaderboard))),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("Experiment not found")),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(&format!("Failed to get leaderboard: {}", e))),
+        ),
+    }
+}
+
 pub fn create_router() -> axum::Router<Arc<Database>> {
     use axum::routing::*;
 
     Router::new()
         .route("/", post(start_automl_experiment))
         .route("/:experiment_id", get(get_experiment_status))
+        .route("/:experiment_id/leaderboard", get(get_leaderboard))
 }
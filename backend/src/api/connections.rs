@@ -0,0 +1,110 @@
+use axum::{extract::Path, Extension, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::datasets::DatasetResponse;
+use crate::{
+    data::{connectors, profile_store, ConnectorConfig},
+    error::{AppError, Result},
+    models::{Connection, Database},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateConnectionRequest {
+    pub name: String,
+    pub config: ConnectorConfig,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConnectionResponse {
+    id: String,
+    name: String,
+    kind: String,
+    /// Credentials are never returned
+    config: ConnectorConfig,
+    created_at: chrono::NaiveDateTime,
+    updated_at: chrono::NaiveDateTime,
+}
+
+impl ConnectionResponse {
+    fn new(connection: Connection, config: &ConnectorConfig) -> Self {
+        Self {
+            id: connection.id,
+            name: connection.name,
+            kind: connection.kind,
+            config: config.redacted(),
+            created_at: connection.created_at,
+            updated_at: connection.updated_at,
+        }
+    }
+}
+
+/// Stores a connection with its credentials encrypted.
+pub async fn create_connection(
+    Extension(db): Extension<std::sync::Arc<Database>>,
+    Json(payload): Json<CreateConnectionRequest>,
+) -> Result<Json<ConnectionResponse>> {
+    let id = Uuid::new_v4().to_string();
+    let sealed = payload.config.seal(&id)?;
+    let connection = db
+        .create_connection(&id, &payload.name, payload.config.kind(), &sealed)
+        .await?;
+
+    Ok(Json(ConnectionResponse::new(connection, &payload.config)))
+}
+
+pub async fn list_connections(
+    Extension(db): Extension<std::sync::Arc<Database>>,
+) -> Result<Json<Vec<ConnectionResponse>>> {
+    let connections = db.list_connections().await?;
+
+    let mut responses = Vec::with_capacity(connections.len());
+    for connection in connections {
+        let config = ConnectorConfig::open(&connection)?;
+        responses.push(ConnectionResponse::new(connection, &config));
+    }
+    Ok(Json(responses))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateConnectionDatasetRequest {
+    pub name: String,
+}
+
+/// Snapshots the connection into a new dataset. The snapshot is what gets
+/// profiled and trained on; refresh the dataset to pick up source changes.
+pub async fn create_connection_dataset(
+    Path(id): Path<Uuid>,
+    Extension(db): Extension<std::sync::Arc<Database>>,
+    Json(payload): Json<CreateConnectionDatasetRequest>,
+) -> Result<Json<DatasetResponse>> {
+    let connection = db
+        .get_connection(&id.to_string())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Connection not found".to_string()))?;
+    let config = ConnectorConfig::open(&connection)?;
+
+    let (snapshot, size) = connectors::snapshot(&config).await?;
+    let dataset = db
+        .create_dataset_from_connection(
+            &payload.name,
+            &snapshot.to_string_lossy(),
+            size,
+            &connection.id,
+        )
+        .await?;
+    profile_store::spawn_profile_job(db.clone(), dataset.clone());
+
+    Ok(Json(dataset.into()))
+}
+
+pub fn create_router<S>() -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    use axum::routing::{get, post};
+
+    axum::Router::new()
+        .route("/", get(list_connections).post(create_connection))
+        .route("/:id/datasets", post(create_connection_dataset))
+}
@@ -1,12 +1,23 @@
 use axum::{
-    extract::{Multipart, Path, State},
+    extract::{Multipart, Path, Query},
     Extension, Json,
 };
-use serde::Serialize;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path as StdPath;
 use uuid::Uuid;
 
 use crate::{
+    data::{
+        connectors,
+        outliers::{self, OutlierMethod, OutlierThresholds},
+        pii::{self, PiiAction},
+        profile_store,
+        profiler::DataProfile,
+        reader::SourceInfo,
+        ConnectorConfig, DatasetReader, ProfilerConfig,
+    },
     error::{AppError, Result},
     models::{Database, Dataset},
 };
@@ -19,12 +30,34 @@ pub struct DatasetResponse {
     created_at: chrono::NaiveDateTime,
 }
 
+impl From<Dataset> for DatasetResponse {
+    fn from(dataset: Dataset) -> Self {
+        Self {
+            id: dataset.id,
+            name: dataset.name,
+            size: dataset.size,
+            created_at: dataset.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadDatasetResponse {
+    #[serde(flatten)]
+    dataset: DatasetResponse,
+    source: SourceInfo,
+    /// Tables or sheets of a multi-table upload; another one can be selected later
+    tables: Vec<String>,
+    table: Option<String>,
+}
+
 pub async fn upload_dataset(
     Extension(db): Extension<std::sync::Arc<Database>>,
     mut multipart: Multipart,
-) -> Result<Json<DatasetResponse>> {
+) -> Result<Json<UploadDatasetResponse>> {
     let mut name = None;
     let mut file_data = None;
+    let mut table = None;
 
     while let Some(field) = multipart.next_field().await? {
         let field_name = field.name().unwrap_or_default().to_string();
@@ -38,6 +71,8 @@ pub async fn upload_dataset(
             
             name = Some(file_name);
             file_data = Some(data);
+        } else if field_name == "table" {
+            table = Some(field.text().await?);
         }
     }
 
@@ -63,38 +98,77 @@ pub async fn upload_dataset(
     // Save the file
     tokio::fs::write(&file_path, &file_data).await?;
     
-    // Get file size
-    let metadata = tokio::fs::metadata(&file_path).await?;
-    let size = metadata.len() as i64;
+    // The format is detected from content, so a misnamed file still works
+    let reader = match DatasetReader::open(&file_path) {
+        Ok(reader) => reader,
+        Err(e) => {
+            tokio::fs::remove_file(&file_path).await.ok();
+            return Err(AppError::BadRequest(format!("Unsupported dataset file: {:#}", e)));
+        }
+    };
     
-    // Save dataset info to database
-    let id = file_id.to_string();
-    let now = chrono::Utc::now().naive_utc();
+    let tables = reader.tables().await?;
+    let (dataset, table) = if reader.info().format.has_tables() {
+        // Multi-table files are registered as one of their tables
+        let table = match table {
+            Some(table) if tables.contains(&table) => table,
+            Some(table) => {
+                return Err(AppError::BadRequest(format!("Table '{}' not found", table)));
+            }
+            None => tables
+                .first()
+                .cloned()
+                .ok_or_else(|| AppError::BadRequest("File contains no tables".to_string()))?,
+        };
+        let (extracted, size) = extract_table(&reader, &table).await?;
+        let dataset = db
+            .create_dataset_from_table(
+                &file_name,
+                &extracted.to_string_lossy(),
+                size,
+                &file_path.to_string_lossy(),
+                &table,
+            )
+            .await?;
+        (dataset, Some(table))
+    } else {
+        let size = tokio::fs::metadata(&file_path).await?.len() as i64;
+        let dataset = db
+            .create_dataset(&file_name, &file_path.to_string_lossy(), size)
+            .await?;
+        (dataset, None)
+    };
     
-    let dataset = sqlx::query_as!(
-        Dataset,
-        r#"
-        INSERT INTO datasets (id, name, file_path, size, created_at)
-        VALUES (?, ?, ?, ?, ?)
-        RETURNING *
-        "#,
-        id,
-        file_name,
-        file_path.to_string_lossy(),
-        size,
-        now
-    )
-    .fetch_one(&*db.pool)
-    .await?;
+    // Profile in the background so the result is cached by the time it's requested
+    profile_store::spawn_profile_job(db.clone(), dataset.clone());
     
-    Ok(Json(DatasetResponse {
-        id: dataset.id,
-        name: dataset.name,
-        size: dataset.size,
-        created_at: dataset.created_at,
+    Ok(Json(UploadDatasetResponse {
+        dataset: DatasetResponse {
+            id: dataset.id,
+            name: dataset.name,
+            size: dataset.size,
+            created_at: dataset.created_at,
+        },
+        source: reader.info().clone(),
+        tables,
+        table,
     }))
 }
 
+/// Writes one table of a multi-table file to Parquet so every consumer can read it as-is.
+async fn extract_table(reader: &DatasetReader, table: &str) -> Result<(std::path::PathBuf, i64)> {
+    let mut df = reader.read(Some(table)).await?;
+    
+    let file_path = StdPath::new("data/datasets").join(format!("{}.parquet", Uuid::new_v4()));
+    let file = std::fs::File::create(&file_path)?;
+    ParquetWriter::new(file)
+        .finish(&mut df)
+        .map_err(anyhow::Error::from)?;
+    
+    let size = tokio::fs::metadata(&file_path).await?.len() as i64;
+    Ok((file_path, size))
+}
+
 pub async fn list_datasets(
     Extension(db): Extension<std::sync::Arc<Database>>,
 ) -> Result<Json<Vec<DatasetResponse>>> {
@@ -118,33 +192,379 @@ pub struct DatasetPreview {
     rows: Vec<Vec<String>>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PreviewQuery {
+    rows: Option<usize>,
+}
+
 pub async fn preview_dataset(
     Path(id): Path<Uuid>,
+    Query(query): Query<PreviewQuery>,
     Extension(db): Extension<std::sync::Arc<Database>>,
 ) -> Result<Json<DatasetPreview>> {
-    let dataset = sqlx::query_as!(
-        Dataset,
-        r#"
-        SELECT * FROM datasets
-        WHERE id = ?
-        "#,
-        id.to_string()
-    )
-    .fetch_optional(&*db.pool)
-    .await?
-    .ok_or_else(|| AppError::NotFound("Dataset not found".to_string()))?;
-    
-    // In a real implementation, we would:
-    // 1. Read the file based on its type (CSV, Excel, etc.)
-    // 2. Parse the first few rows
-    // 3. Return the preview data
-    
-    // This is a simplified version that returns mock data
-    Ok(Json(DatasetPreview {
-        headers: vec!["Column 1".to_string(), "Column 2".to_string()],
-        rows: vec![
-            vec!["Value 1".to_string(), "Value 2".to_string()],
-            vec!["Value 3".to_string(), "Value 4".to_string()],
-        ],
+    let dataset = db
+        .get_dataset(&id.to_string())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Dataset not found".to_string()))?;
+    
+    let rows = query.rows.unwrap_or(20).min(1000);
+    let df = DatasetReader::open(&dataset.file_path)?
+        .read_head(None, rows)
+        .await?;
+    
+    let headers = df.get_column_names().iter().map(|s| s.to_string()).collect();
+    let rows = (0..df.height())
+        .map(|i| {
+            df.get_columns()
+                .iter()
+                .map(|series| match series.get(i) {
+                    Ok(AnyValue::Null) | Err(_) => String::new(),
+                    Ok(AnyValue::Utf8(s)) => s.to_string(),
+                    Ok(value) => value.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    
+    Ok(Json(DatasetPreview { headers, rows }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetTablesResponse {
+    tables: Vec<String>,
+    selected: Option<String>,
+}
+
+pub async fn list_dataset_tables(
+    Path(id): Path<Uuid>,
+    Extension(db): Extension<std::sync::Arc<Database>>,
+) -> Result<Json<DatasetTablesResponse>> {
+    let dataset = db
+        .get_dataset(&id.to_string())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Dataset not found".to_string()))?;
+    
+    let source = dataset.source_path.as_deref().unwrap_or(&dataset.file_path);
+    let tables = DatasetReader::open(source)?.tables().await?;
+    
+    Ok(Json(DatasetTablesResponse {
+        tables,
+        selected: dataset.source_table,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SelectTableRequest {
+    pub table: String,
+}
+
+/// Re-extracts the dataset from another table of the uploaded file.
+pub async fn select_dataset_table(
+    Path(id): Path<Uuid>,
+    Extension(db): Extension<std::sync::Arc<Database>>,
+    Json(payload): Json<SelectTableRequest>,
+) -> Result<Json<DatasetResponse>> {
+    let dataset = db
+        .get_dataset(&id.to_string())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Dataset not found".to_string()))?;
+    
+    let source = dataset.source_path.as_deref().ok_or_else(|| {
+        AppError::BadRequest("Dataset was not uploaded as a multi-table file".to_string())
+    })?;
+    let reader = DatasetReader::open(source)?;
+    if !reader.tables().await?.contains(&payload.table) {
+        return Err(AppError::BadRequest(format!("Table '{}' not found", payload.table)));
+    }
+    
+    let (extracted, size) = extract_table(&reader, &payload.table).await?;
+    let updated = db
+        .set_dataset_table(&dataset.id, &extracted.to_string_lossy(), size, &payload.table)
+        .await?;
+    // The previous extract is derived data and no longer referenced
+    tokio::fs::remove_file(&dataset.file_path).await.ok();
+    profile_store::spawn_profile_job(db.clone(), updated.clone());
+    
+    Ok(Json(DatasetResponse {
+        id: updated.id,
+        name: updated.name,
+        size: updated.size,
+        created_at: updated.created_at,
     }))
 }
+
+/// Takes a fresh snapshot of a connection-backed dataset as a new version.
+pub async fn refresh_dataset(
+    Path(id): Path<Uuid>,
+    Extension(db): Extension<std::sync::Arc<Database>>,
+) -> Result<Json<DatasetResponse>> {
+    let dataset = db
+        .get_dataset(&id.to_string())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Dataset not found".to_string()))?;
+    
+    let connection_id = dataset.connection_id.as_deref().ok_or_else(|| {
+        AppError::BadRequest("Dataset is not backed by a connection".to_string())
+    })?;
+    let connection = db
+        .get_connection(connection_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Connection not found".to_string()))?;
+    let config = ConnectorConfig::open(&connection)?;
+    
+    let (snapshot, size) = connectors::snapshot(&config).await?;
+    // Earlier snapshots are kept, for experiments that recorded their version
+    let updated = db
+        .update_dataset_snapshot(&dataset.id, &snapshot.to_string_lossy(), size)
+        .await?;
+    profile_store::spawn_profile_job(db.clone(), updated.clone());
+    
+    Ok(Json(updated.into()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetSnapshotResponse {
+    version: i64,
+    size: i64,
+    created_at: chrono::NaiveDateTime,
+}
+
+/// Lists the snapshots taken of a connection-backed dataset, newest first.
+pub async fn list_dataset_snapshots(
+    Path(id): Path<Uuid>,
+    Extension(db): Extension<std::sync::Arc<Database>>,
+) -> Result<Json<Vec<DatasetSnapshotResponse>>> {
+    let dataset = db
+        .get_dataset(&id.to_string())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Dataset not found".to_string()))?;
+    
+    let snapshots = db
+        .list_dataset_snapshots(&dataset.id)
+        .await?
+        .into_iter()
+        .map(|snapshot| DatasetSnapshotResponse {
+            version: snapshot.version,
+            size: snapshot.size,
+            created_at: snapshot.created_at,
+        })
+        .collect();
+    Ok(Json(snapshots))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RedactDatasetRequest {
+    /// Action per column; defaults to hashing every column flagged as personal data
+    pub actions: Option<HashMap<String, PiiAction>>,
+    /// Name of the cleaned dataset
+    pub name: Option<String>,
+    /// Salt mixed into hashed values so they can't be matched against other exports
+    pub salt: Option<String>,
+}
+
+pub async fn redact_dataset(
+    Path(id): Path<Uuid>,
+    Extension(db): Extension<std::sync::Arc<Database>>,
+    Json(payload): Json<RedactDatasetRequest>,
+) -> Result<Json<DatasetResponse>> {
+    let dataset = db
+        .get_dataset(&id.to_string())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Dataset not found".to_string()))?;
+    
+    let df = DatasetReader::open(&dataset.file_path)?.read(None).await?;
+    
+    let findings = pii::detect(&df, ProfilerConfig::default().pii_match_ratio)?;
+    let kinds = findings
+        .iter()
+        .map(|f| (f.column.clone(), f.kind))
+        .collect();
+    let actions = payload.actions.unwrap_or_else(|| {
+        findings
+            .iter()
+            .map(|f| (f.column.clone(), PiiAction::Hash))
+            .collect()
+    });
+    
+    if let Some(missing) = actions.keys().find(|c| df.column(c).is_err()) {
+        return Err(AppError::BadRequest(format!("Unknown column '{}'", missing)));
+    }
+    
+    let salt = payload.salt.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut cleaned = pii::redact(&df, &actions, &kinds, &salt)?;
+    
+    let name = payload
+        .name
+        .unwrap_or_else(|| derived_name(&dataset.name, "redacted"));
+    let dataset = save_derived_dataset(&db, &mut cleaned, &name).await?;
+    
+    Ok(Json(DatasetResponse {
+        id: dataset.id,
+        name: dataset.name,
+        size: dataset.size,
+        created_at: dataset.created_at,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportOutliersRequest {
+    /// Methods whose outliers are exported; all methods when omitted
+    #[serde(default)]
+    pub methods: Vec<OutlierMethod>,
+    pub thresholds: Option<OutlierThresholds>,
+    /// Name of the exported dataset
+    pub name: Option<String>,
+}
+
+/// Saves the rows flagged as outliers as a new dataset, keeping each row's
+/// position in the source dataset in a `source_row` column.
+pub async fn export_outliers(
+    Path(id): Path<Uuid>,
+    Extension(db): Extension<std::sync::Arc<Database>>,
+    Json(payload): Json<ExportOutliersRequest>,
+) -> Result<Json<DatasetResponse>> {
+    let dataset = db
+        .get_dataset(&id.to_string())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Dataset not found".to_string()))?;
+    
+    let df = DatasetReader::open(&dataset.file_path)?.read(None).await?;
+    
+    let config = ProfilerConfig {
+        outlier_thresholds: payload.thresholds.unwrap_or_default(),
+        ..ProfilerConfig::default()
+    };
+    let rows = outliers::outlier_rows(&df, &config, &payload.methods)?;
+    if rows.is_empty() {
+        return Err(AppError::BadRequest("No outliers found".to_string()));
+    }
+    
+    let indices: Vec<IdxSize> = rows.iter().map(|&row| row as IdxSize).collect();
+    let mut exported = df
+        .take(&IdxCa::from_vec("", indices.clone()))
+        .and_then(|mut rows| {
+            rows.insert_column(0, Series::new("source_row", indices))?;
+            Ok(rows)
+        })
+        .map_err(anyhow::Error::from)?;
+    
+    let name = payload
+        .name
+        .unwrap_or_else(|| derived_name(&dataset.name, "outliers"));
+    let dataset = save_derived_dataset(&db, &mut exported, &name).await?;
+    
+    Ok(Json(DatasetResponse {
+        id: dataset.id,
+        name: dataset.name,
+        size: dataset.size,
+        created_at: dataset.created_at,
+    }))
+}
+
+/// `customers.csv` -> `customers_<suffix>.csv`
+fn derived_name(name: &str, suffix: &str) -> String {
+    let stem = StdPath::new(name)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(name);
+    format!("{}_{}.csv", stem, suffix)
+}
+
+/// Writes a frame derived from another dataset as CSV next to the uploaded
+/// datasets, registers it and schedules its profile.
+async fn save_derived_dataset(
+    db: &std::sync::Arc<Database>,
+    df: &mut DataFrame,
+    name: &str,
+) -> Result<Dataset> {
+    let datasets_dir = StdPath::new("data/datasets");
+    tokio::fs::create_dir_all(datasets_dir).await?;
+    let file_path = datasets_dir.join(format!("{}.csv", Uuid::new_v4()));
+    
+    let mut file = std::fs::File::create(&file_path)?;
+    CsvWriter::new(&mut file)
+        .finish(df)
+        .map_err(anyhow::Error::from)?;
+    
+    let size = tokio::fs::metadata(&file_path).await?.len() as i64;
+    let dataset = db
+        .create_dataset(name, &file_path.to_string_lossy(), size)
+        .await?;
+    profile_store::spawn_profile_job(db.clone(), dataset.clone());
+    
+    Ok(dataset)
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetProfileResponse {
+    dataset_id: String,
+    version: i64,
+    status: String,
+    profile: Option<DataProfile>,
+    error: Option<String>,
+    duration_ms: Option<i64>,
+    computed_at: Option<chrono::NaiveDateTime>,
+}
+
+/// Returns the cached profile for the dataset's current version. If profiling
+/// hasn't started yet, or the cached profile is stale, it is scheduled and the
+/// response reports it as pending.
+pub async fn get_dataset_profile(
+    Path(id): Path<Uuid>,
+    Extension(db): Extension<std::sync::Arc<Database>>,
+) -> Result<Json<DatasetProfileResponse>> {
+    let dataset = db
+        .get_dataset(&id.to_string())
+        .await?
+        .ok_or_else(|| AppError::NotFound("Dataset not found".to_string()))?;
+    
+    let record = db.get_dataset_profile(&dataset.id, dataset.version).await?;
+    let profile = record
+        .as_ref()
+        .and_then(|record| record.profile.as_deref())
+        .and_then(|json| profile_store::parse_cached(&dataset, json));
+    let record = match record {
+        // A stale profile is computed again, like a missing one
+        Some(record) if record.profile.is_none() || profile.is_some() => record,
+        _ => {
+            profile_store::spawn_profile_job(db.clone(), dataset.clone());
+            return Ok(Json(DatasetProfileResponse {
+                dataset_id: dataset.id,
+                version: dataset.version,
+                status: "pending".to_string(),
+                profile: None,
+                error: None,
+                duration_ms: None,
+                computed_at: None,
+            }));
+        }
+    };
+    
+    Ok(Json(DatasetProfileResponse {
+        dataset_id: record.dataset_id,
+        version: record.version,
+        status: record.status,
+        profile,
+        error: record.error,
+        duration_ms: record.duration_ms,
+        computed_at: record.computed_at,
+    }))
+}
+
+pub fn create_router<S>() -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    use axum::routing::{get, post, put};
+
+    axum::Router::new()
+        .route("/", get(list_datasets).post(upload_dataset))
+        .route("/:id/preview", get(preview_dataset))
+        .route("/:id/tables", get(list_dataset_tables))
+        .route("/:id/table", put(select_dataset_table))
+        .route("/:id/refresh", post(refresh_dataset))
+        .route("/:id/snapshots", get(list_dataset_snapshots))
+        .route("/:id/profile", get(get_dataset_profile))
+        .route("/:id/redact", post(redact_dataset))
+        .route("/:id/outliers/export", post(export_outliers))
+}
@@ -1,13 +1,28 @@
-use crate::models::Database;
+use crate::{
+    models::{Database, ExperimentLog, ExperimentMetricSummary, Reproducibility},
+    tracking::{
+        compare::MAX_COMPARED_EXPERIMENTS,
+        events::{self, ExperimentEvent},
+        logs::{self, LogCursor, LogQuery, DEFAULT_LOG_LIMIT},
+        reproducibility,
+        metrics::{MetricPoint, MetricSeries},
+        search::{escape_like, parse_timestamp, Attribute, Comparator, Field, Literal},
+        status, Comparison, ExperimentSearch, ExperimentStatus, OrderBy, Page, SearchError,
+    },
+};
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Json,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::Arc;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
 #[derive(Debug, Serialize)]
@@ -43,14 +58,145 @@ pub struct CreateExperimentRequest {
     pub parameters: Option<Value>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ListExperimentsQuery {
+    pub status: Option<String>,
+    pub notebook_id: Option<String>,
+    pub dataset_id: Option<String>,
+    /// Case-insensitive substring of the name
+    pub name: Option<String>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    /// e.g. `metrics.accuracy > 0.9 AND params.model = 'rf'`
+    pub filter: Option<String>,
+    /// e.g. `metrics.accuracy DESC`; newest first by default
+    pub order_by: Option<String>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+impl ListExperimentsQuery {
+    fn into_search(self) -> Result<ExperimentSearch, SearchError> {
+        let mut search = ExperimentSearch::new(self.page, self.page_size);
+        let attributes = [
+            (Attribute::Status, Comparator::Eq, self.status),
+            (Attribute::NotebookId, Comparator::Eq, self.notebook_id),
+            (Attribute::DatasetId, Comparator::Eq, self.dataset_id),
+            (
+                Attribute::Name,
+                Comparator::ILike,
+                self.name.map(|name| format!("%{}%", escape_like(&name))),
+            ),
+            (
+                Attribute::CreatedAt,
+                Comparator::Ge,
+                self.created_after.as_deref().map(parse_timestamp).transpose()?,
+            ),
+            (
+                Attribute::CreatedAt,
+                Comparator::Lt,
+                self.created_before.as_deref().map(parse_timestamp).transpose()?,
+            ),
+        ];
+        for (attribute, comparator, value) in attributes {
+            if let Some(value) = value {
+                search = search.condition(
+                    Field::Attribute(attribute),
+                    comparator,
+                    Literal::Text(value),
+                )?;
+            }
+        }
+        if let Some(filter) = self.filter.as_deref() {
+            search = search.filter(filter)?;
+        }
+        search.order_by = self.order_by.as_deref().map(OrderBy::parse).transpose()?;
+        Ok(search)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateExperimentMetricsRequest {
     pub metrics: Value,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct LogExperimentParamsRequest {
+    pub params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogExperimentMetricsRequest {
+    pub metrics: Vec<MetricPoint>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExperimentMetricsQuery {
+    /// Comma-separated metric keys; all metrics by default
+    pub keys: Option<String>,
+    /// Downsample each series to at most this many points
+    pub max_points: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentMetricsResponse {
+    pub summaries: Vec<ExperimentMetricSummary>,
+    pub series: Vec<MetricSeries>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompareExperimentsQuery {
+    /// Comma-separated experiment ids, in display order
+    pub ids: String,
+    /// Comma-separated metric keys to include series for; all metrics by default
+    pub keys: Option<String>,
+    pub max_points: Option<usize>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateExperimentStatusRequest {
     pub status: String,
+    /// Recorded when `status` is `failed`
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExperimentLogsQuery {
+    /// Lines newer than this log id, oldest first
+    pub after: Option<i64>,
+    /// Lines older than this log id, newest first
+    pub before: Option<i64>,
+    /// Comma-separated levels to include, e.g. `warning,error`
+    pub level: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentLogsResponse {
+    pub logs: Vec<ExperimentLog>,
+    /// Pass as `after` (or `before`, when paging back) to get the next page
+    pub next_cursor: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamExperimentLogsQuery {
+    /// Replay lines newer than this log id first; `Last-Event-ID` takes precedence
+    pub after: Option<i64>,
+    pub level: Option<String>,
+}
+
+/// Setup reported by the tracking client; replaces what the backend captured.
+#[derive(Debug, Deserialize)]
+pub struct ReportReproducibilityRequest {
+    pub environment_id: Option<String>,
+    pub python_version: Option<String>,
+    pub packages: Option<String>,
+    pub git_commit: Option<String>,
+    pub git_dirty: Option<bool>,
+    pub script_path: Option<String>,
+    pub script_sha256: Option<String>,
+    /// Object of library to seed, e.g. `{"numpy": 42}`
+    pub seeds: Option<Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -80,6 +226,37 @@ pub async fn create_experiment(
     }
 }
 
+pub async fn list_experiments(
+    State(db): State<Arc<Database>>,
+    Query(query): Query<ListExperimentsQuery>,
+) -> impl IntoResponse {
+    let search = match query.into_search() {
+        Ok(search) => search,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(&e.to_string())),
+            )
+        }
+    };
+
+    match db.search_experiments(&search).await {
+        Ok((experiments, total)) => (
+            StatusCode::OK,
+            Json(ApiResponse::success(Page {
+                items: experiments,
+                total,
+                page: search.page,
+                page_size: search.page_size,
+            })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(&format!("Failed to list experiments: {}", e))),
+        ),
+    }
+}
+
 pub async fn get_experiment(
     State(db): State<Arc<Database>>,
     Path(id): Path<String>,
@@ -117,22 +294,214 @@ pub async fn update_experiment_metrics(
     }
 }
 
-pub async fn update_experiment_status(
+/// Adds parameters to the experiment, overwriting ones with the same key.
+pub async fn log_experiment_params(
     State(db): State<Arc<Database>>,
     Path(id): Path<String>,
-    Json(payload): Json<UpdateExperimentStatusRequest>,
+    Json(payload): Json<LogExperimentParamsRequest>,
 ) -> impl IntoResponse {
-    match db.update_experiment_status(&id, &payload.status).await {
+    if !payload.params.is_object() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error("params must be an object")),
+        );
+    }
+
+    match db.get_experiment(&id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error("Experiment not found")),
+            )
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(&format!("Failed to get experiment: {}", e))),
+            )
+        }
+    }
+
+    match db.log_experiment_params(&id, &payload.params).await {
         Ok(_) => (
             StatusCode::OK,
-            Json(ApiResponse::success("Status updated successfully")),
+            Json(ApiResponse::success("Params logged successfully")),
         ),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(&format!("Failed to log params: {}", e))),
+        ),
+    }
+}
+
+pub async fn log_experiment_metrics(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<String>,
+    Json(payload): Json<LogExperimentMetricsRequest>,
+) -> impl IntoResponse {
+    if let Some(point) = payload
+        .metrics
+        .iter()
+        .find(|point| point.key.is_empty() || !point.value.is_finite())
+    {
+        return (
+            StatusCode::BAD_REQUEST,
             Json(ApiResponse::error(&format!(
-                "Failed to update status: {}",
-                e
+                "Invalid value {} for metric '{}'",
+                point.value, point.key
+            ))),
+        );
+    }
+
+    match db.get_experiment(&id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error("Experiment not found")),
+            )
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(&format!("Failed to get experiment: {}", e))),
+            )
+        }
+    }
+
+    match db.log_experiment_metrics(&id, &payload.metrics).await {
+        Ok(_) => (
+            StatusCode::CREATED,
+            Json(ApiResponse::success("Metrics logged successfully")),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(&format!("Failed to log metrics: {}", e))),
+        ),
+    }
+}
+
+/// Latest/best values of the experiment's metrics and their series for plotting.
+pub async fn get_experiment_metrics(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<String>,
+    Query(query): Query<ExperimentMetricsQuery>,
+) -> impl IntoResponse {
+    match load_experiment_metrics(&db, &id, &query).await {
+        Ok(metrics) => (StatusCode::OK, Json(ApiResponse::success(metrics))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(&format!("Failed to get metrics: {}", e))),
+        ),
+    }
+}
+
+/// Parameters, metrics, series and run details of several experiments aligned
+/// for side-by-side display.
+pub async fn compare_experiments(
+    State(db): State<Arc<Database>>,
+    Query(query): Query<CompareExperimentsQuery>,
+) -> impl IntoResponse {
+    let ids: Vec<&str> = query
+        .ids
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .collect();
+    if ids.len() < 2 || ids.len() > MAX_COMPARED_EXPERIMENTS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!(
+                "Compare between 2 and {} experiments",
+                MAX_COMPARED_EXPERIMENTS
             ))),
+        );
+    }
+
+    let mut experiments = Vec::with_capacity(ids.len());
+    let mut summaries = Vec::with_capacity(ids.len());
+    let mut series = Vec::with_capacity(ids.len());
+    for id in ids {
+        let experiment = match db.get_experiment(id).await {
+            Ok(Some(experiment)) => experiment,
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::error(&format!("Experiment {} not found", id))),
+                )
+            }
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(&format!("Failed to get experiment: {}", e))),
+                )
+            }
+        };
+        let metrics_query = ExperimentMetricsQuery {
+            keys: query.keys.clone(),
+            max_points: query.max_points,
+        };
+        match load_experiment_metrics(&db, id, &metrics_query).await {
+            Ok(metrics) => {
+                summaries.push(metrics.summaries);
+                series.push(metrics.series);
+            }
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(&format!("Failed to get metrics: {}", e))),
+                )
+            }
+        }
+        experiments.push(experiment);
+    }
+
+    let comparison = Comparison::new(&experiments, &summaries, series);
+    (StatusCode::OK, Json(ApiResponse::success(comparison)))
+}
+
+async fn load_experiment_metrics(
+    db: &Database,
+    id: &str,
+    query: &ExperimentMetricsQuery,
+) -> Result<ExperimentMetricsResponse, sqlx::Error> {
+    let summaries = db.get_metric_summaries(id).await?;
+    let keys: Vec<String> = match query.keys.as_deref() {
+        Some(keys) => keys
+            .split(',')
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .collect(),
+        None => summaries.iter().map(|summary| summary.key.clone()).collect(),
+    };
+
+    let mut series = Vec::with_capacity(keys.len());
+    for key in keys {
+        let points = db.get_metric_series(id, &key).await?;
+        series.push(MetricSeries::new(key, points, query.max_points));
+    }
+
+    Ok(ExperimentMetricsResponse { summaries, series })
+}
+
+pub async fn update_experiment_status(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateExperimentStatusRequest>,
+) -> impl IntoResponse {
+    let result = match payload.status.parse::<ExperimentStatus>() {
+        Ok(status) => {
+            status::transition(&db, &id, status, payload.failure_reason.as_deref()).await
+        }
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(experiment) => (StatusCode::OK, Json(ApiResponse::success(experiment))),
+        Err(e) => (
+            e.status_code(),
+            Json(ApiResponse::error(&format!("Failed to update status: {}", e))),
         ),
     }
 }
@@ -160,9 +529,33 @@ pub async fn add_experiment_log(
 pub async fn get_experiment_logs(
     State(db): State<Arc<Database>>,
     Path(id): Path<String>,
+    Query(query): Query<ExperimentLogsQuery>,
 ) -> impl IntoResponse {
-    match db.get_experiment_logs(&id, None).await {
-        Ok(logs) => (StatusCode::OK, Json(ApiResponse::success(logs))),
+    let cursor = match (query.after, query.before) {
+        (Some(_), Some(_)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("Pass either after or before, not both")),
+            )
+        }
+        (Some(after), None) => LogCursor::After(after),
+        (None, Some(before)) => LogCursor::Before(before),
+        (None, None) => LogCursor::Latest,
+    };
+    let query = LogQuery::new(
+        cursor,
+        logs::parse_levels(query.level.as_deref()),
+        query.limit.unwrap_or(DEFAULT_LOG_LIMIT),
+    );
+
+    match db.get_experiment_logs(&id, &query).await {
+        Ok(logs) => {
+            let next_cursor = query.next_cursor(&logs);
+            (
+                StatusCode::OK,
+                Json(ApiResponse::success(ExperimentLogsResponse { logs, next_cursor })),
+            )
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ApiResponse::error(&format!(
@@ -173,19 +566,191 @@ pub async fn get_experiment_logs(
     }
 }
 
+/// Server-Sent Events of the experiment's log lines (`log`), metric points
+/// (`metric`) and status changes (`status`). Closes after the experiment finishes.
+pub async fn stream_experiment_logs(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<String>,
+    Query(query): Query<StreamExperimentLogsQuery>,
+    headers: HeaderMap,
+) -> Response {
+    match db.get_experiment(&id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("Experiment not found")),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(&format!(
+                    "Failed to get experiment: {}",
+                    e
+                ))),
+            )
+                .into_response()
+        }
+    }
+
+    // Browsers reconnect with the id of the last event they got
+    let after = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok()?.parse().ok())
+        .or(query.after)
+        .unwrap_or(0);
+    let query = LogQuery::after(after, &logs::parse_levels(query.level.as_deref()));
+
+    let (tx, rx) = mpsc::channel(64);
+    tokio::spawn(async move {
+        if let Err(e) = events::tail(&db, &id, query, tx).await {
+            tracing::error!("Failed to stream logs of experiment {}: {}", id, e);
+        }
+    });
+
+    let stream = futures_util::stream::unfold(rx, |mut rx| async move {
+        let event = rx.recv().await?;
+        let mut sse = Event::default().event(event.name());
+        if let ExperimentEvent::Log(log) = &event {
+            sse = sse.id(log.id.to_string());
+        }
+        Some((sse.json_data(&event), rx))
+    });
+    Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+pub async fn get_reproducibility(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match db.get_reproducibility(&id).await {
+        Ok(Some(record)) => (StatusCode::OK, Json(ApiResponse::success(record))),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::error("No setup was captured for this experiment")),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(&format!(
+                "Failed to get reproducibility info: {}",
+                e
+            ))),
+        ),
+    }
+}
+
+pub async fn report_reproducibility(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<String>,
+    Json(payload): Json<ReportReproducibilityRequest>,
+) -> impl IntoResponse {
+    let seeds = match payload.seeds {
+        None | Some(Value::Null) => None,
+        Some(seeds @ Value::Object(_)) => Some(seeds.to_string()),
+        Some(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error("seeds must be an object")),
+            )
+        }
+    };
+    match db.get_experiment(&id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::error("Experiment not found")),
+            )
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(&format!("Failed to get experiment: {}", e))),
+            )
+        }
+    }
+
+    let record = Reproducibility {
+        experiment_id: id,
+        environment_id: payload.environment_id,
+        python_version: payload.python_version,
+        packages: payload.packages,
+        git_commit: payload.git_commit,
+        git_dirty: payload.git_dirty,
+        script_path: payload.script_path,
+        script_sha256: payload.script_sha256,
+        seeds,
+        ..Default::default()
+    };
+    match db.save_reproducibility(&record, true).await {
+        Ok(record) => (StatusCode::OK, Json(ApiResponse::success(record))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::error(&format!(
+                "Failed to save reproducibility info: {}",
+                e
+            ))),
+        ),
+    }
+}
+
+/// Reruns an experiment with the setup captured when it started.
+pub async fn rerun_experiment(
+    State(db): State<Arc<Database>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match reproducibility::rerun(&db, &id).await {
+        Ok(rerun) => (StatusCode::CREATED, Json(ApiResponse::success(rerun))),
+        Err(e) => (
+            e.status_code(),
+            Json(ApiResponse::error(&format!("Failed to rerun experiment: {}", e))),
+        ),
+    }
+}
+
 pub fn create_router() -> axum::Router<Arc<Database>> {
-    use axum::routing::*;
+    use super::artifacts;
+    use axum::{extract::DefaultBodyLimit, routing::*};
 
     Router::new()
-        .route("/", post(create_experiment))
+        .route("/", get(list_experiments).post(create_experiment))
+        .route("/compare", get(compare_experiments))
         .route(
             "/:id",
             get(get_experiment)
                 .patch(update_experiment_metrics)
                 .put(update_experiment_status),
         )
+        .route("/:id/params", post(log_experiment_params))
+        .route(
+            "/:id/reproducibility",
+            get(get_reproducibility).put(report_reproducibility),
+        )
+        .route("/:id/rerun", post(rerun_experiment))
+        .route(
+            "/:id/metrics",
+            get(get_experiment_metrics).post(log_experiment_metrics),
+        )
+        .route(
+            "/:id/artifacts",
+            get(artifacts::list_artifacts)
+                .post(artifacts::upload_artifacts)
+                // Models easily exceed the default request size limit
+                .layer(DefaultBodyLimit::disable()),
+        )
+        .route(
+            "/:id/artifacts/*path",
+            get(artifacts::download_artifact)
+                .put(artifacts::put_artifact)
+                .layer(DefaultBodyLimit::disable()),
+        )
         .route(
             "/:id/logs",
             get(get_experiment_logs).post(add_experiment_log),
         )
+        .route("/:id/logs/stream", get(stream_experiment_logs))
 }
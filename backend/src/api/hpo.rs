@@ -0,0 +1,50 @@
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::jobs::SubmittedJob;
+use crate::{
+    error::{AppError, Result},
+    hpo::{
+        study::{self, StudyReport},
+        StudyConfig,
+    },
+    models::Database,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateStudyRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub config: StudyConfig,
+}
+
+/// Queues a study as a new experiment; its trials become child experiments.
+pub async fn create_study(
+    Extension(db): Extension<Arc<Database>>,
+    Json(payload): Json<CreateStudyRequest>,
+) -> Result<(StatusCode, Json<SubmittedJob>)> {
+    let (job, experiment) = study::create(&db, &payload.name, payload.config).await?;
+    Ok((StatusCode::ACCEPTED, Json(SubmittedJob { job, experiment })))
+}
+
+pub async fn get_study(
+    Path(id): Path<String>,
+    Extension(db): Extension<Arc<Database>>,
+) -> Result<Json<StudyReport>> {
+    let report = study::report(&db, &id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Study {} not found", id)))?;
+    Ok(Json(report))
+}
+
+pub fn create_router<S>() -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    use axum::routing::{get, post};
+
+    axum::Router::new()
+        .route("/", post(create_study))
+        .route("/:id", get(get_study))
+}
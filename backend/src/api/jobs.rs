@@ -0,0 +1,159 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::{
+    error::{AppError, Result},
+    jobs::{self, JobKind, JobStatus, NotebookJob, TrainingJob},
+    models::{Database, Experiment, Job},
+};
+
+const DEFAULT_LIST_LIMIT: i64 = 100;
+const MAX_LIST_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct ListJobsQuery {
+    pub status: Option<String>,
+    pub experiment_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitTrainingRequest {
+    pub name: String,
+    pub script_path: String,
+    pub dataset_id: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub parameters: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitNotebookRequest {
+    pub notebook_id: String,
+    pub name: String,
+    pub parameters: Option<Value>,
+}
+
+/// A queued job and the experiment it runs.
+#[derive(Debug, Serialize)]
+pub struct SubmittedJob {
+    pub job: Job,
+    pub experiment: Option<Experiment>,
+}
+
+pub async fn list_jobs(
+    Extension(db): Extension<Arc<Database>>,
+    Query(query): Query<ListJobsQuery>,
+) -> Result<Json<Vec<Job>>> {
+    let status = query
+        .status
+        .as_deref()
+        .map(str::parse::<JobStatus>)
+        .transpose()?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+    let jobs = db
+        .list_jobs(status, query.experiment_id.as_deref(), limit)
+        .await?;
+    Ok(Json(jobs))
+}
+
+pub async fn get_job(
+    Path(id): Path<String>,
+    Extension(db): Extension<Arc<Database>>,
+) -> Result<Json<Job>> {
+    let job = db
+        .get_job(&id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Job {} not found", id)))?;
+    Ok(Json(job))
+}
+
+pub async fn cancel_job(
+    Path(id): Path<String>,
+    Extension(db): Extension<Arc<Database>>,
+) -> Result<Json<Job>> {
+    Ok(Json(jobs::cancel(&db, &id).await?))
+}
+
+/// Queues a training script, relative to the workspace, as a new experiment.
+pub async fn submit_training(
+    Extension(db): Extension<Arc<Database>>,
+    Json(payload): Json<SubmitTrainingRequest>,
+) -> Result<(StatusCode, Json<SubmittedJob>)> {
+    let script_path = jobs::resolve_script(&jobs::workspace(), &payload.script_path)?;
+    if let Some(dataset_id) = &payload.dataset_id {
+        db.get_dataset(dataset_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Dataset {} not found", dataset_id)))?;
+    }
+
+    let experiment = db
+        .create_experiment(
+            &payload.name,
+            None,
+            payload.dataset_id.as_deref(),
+            payload.parameters,
+        )
+        .await?;
+    let job = TrainingJob {
+        script_path,
+        dataset_id: payload.dataset_id,
+        args: payload.args,
+        seeds: None,
+    };
+    let (job, experiment) =
+        jobs::enqueue(&db, JobKind::Training, &job, Some(&experiment.id)).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(SubmittedJob { job, experiment })))
+}
+
+/// Queues the notebook's code cells, as they are now, as a new experiment.
+pub async fn submit_notebook(
+    Extension(db): Extension<Arc<Database>>,
+    Json(payload): Json<SubmitNotebookRequest>,
+) -> Result<(StatusCode, Json<SubmittedJob>)> {
+    let cells = db.get_notebook_cells(&payload.notebook_id).await?;
+    if cells.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Notebook {} has no cells to run",
+            payload.notebook_id
+        )));
+    }
+
+    let experiment = db
+        .create_experiment(
+            &payload.name,
+            Some(&payload.notebook_id),
+            None,
+            payload.parameters,
+        )
+        .await?;
+    let job = NotebookJob::from_cells(&payload.notebook_id, &cells, None);
+    let (job, experiment) =
+        jobs::enqueue(&db, JobKind::Notebook, &job, Some(&experiment.id)).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(SubmittedJob { job, experiment })))
+}
+
+pub fn create_router<S>() -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    use axum::routing::{get, post};
+
+    axum::Router::new()
+        .route("/", get(list_jobs))
+        .route("/training", post(submit_training))
+        .route("/notebook", post(submit_notebook))
+        .route("/:id", get(get_job))
+        .route("/:id/cancel", post(cancel_job))
+}
@@ -0,0 +1,916 @@
+//! The subset of the MLflow tracking REST API that `mlflow` clients use to log
+//! runs, so `MLFLOW_TRACKING_URI` can point at this backend. Runs are stored as
+//! experiments; artifacts go through the `mlflow-artifacts` proxy into our store.
+
+use axum::{
+    body::Body,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use super::artifacts;
+use crate::{
+    error::AppError,
+    models::{Database, Experiment, MlflowExperiment},
+    tracking::{
+        metrics::MetricPoint,
+        mlflow::{
+            self, int64, ExperimentInfo, KeyValue, Metric, Run, RunInfo, RunRef, RUN_NAME_TAG,
+            USER_TAG,
+        },
+        search::{self, Attribute, Field, MAX_PAGE_SIZE},
+        reproducibility, status, ExperimentSearch, OrderBy, SearchError,
+    },
+};
+
+/// Error in MLflow's format, which clients turn into `MlflowException`s.
+#[derive(Debug)]
+pub struct MlflowError {
+    status: StatusCode,
+    error_code: &'static str,
+    message: String,
+}
+
+impl MlflowError {
+    fn not_found(message: String) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            error_code: "RESOURCE_DOES_NOT_EXIST",
+            message,
+        }
+    }
+
+    fn invalid(message: String) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            error_code: "INVALID_PARAMETER_VALUE",
+            message,
+        }
+    }
+
+    fn already_exists(message: String) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            error_code: "RESOURCE_ALREADY_EXISTS",
+            message,
+        }
+    }
+
+    fn invalid_state(message: String) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            error_code: "INVALID_STATE",
+            message,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorResponse<'a> {
+    error_code: &'a str,
+    message: &'a str,
+}
+
+impl IntoResponse for MlflowError {
+    fn into_response(self) -> Response {
+        let body = ErrorResponse {
+            error_code: self.error_code,
+            message: &self.message,
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<sqlx::Error> for MlflowError {
+    fn from(error: sqlx::Error) -> Self {
+        AppError::from(error).into()
+    }
+}
+
+impl From<SearchError> for MlflowError {
+    fn from(error: SearchError) -> Self {
+        Self::invalid(error.to_string())
+    }
+}
+
+impl From<AppError> for MlflowError {
+    fn from(error: AppError) -> Self {
+        match error {
+            AppError::NotFound(message) => Self::not_found(message),
+            AppError::BadRequest(message) => Self::invalid(message),
+            AppError::Conflict(message) => Self {
+                status: StatusCode::CONFLICT,
+                ..Self::invalid_state(message)
+            },
+            other => Self {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                error_code: "INTERNAL_ERROR",
+                message: other.to_string(),
+            },
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, MlflowError>;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateExperimentRequest {
+    pub name: String,
+    pub artifact_location: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<KeyValue>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExperimentIdRequest {
+    pub experiment_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExperimentNameQuery {
+    pub experiment_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchExperimentsRequest {
+    #[serde(default, deserialize_with = "int64")]
+    pub max_results: Option<i64>,
+    pub page_token: Option<String>,
+    /// Only conditions on `name` are supported
+    pub filter: Option<String>,
+    /// `ACTIVE_ONLY` (default), `DELETED_ONLY` or `ALL`
+    pub view_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateExperimentRequest {
+    pub experiment_id: String,
+    pub new_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetExperimentTagRequest {
+    pub experiment_id: String,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRunRequest {
+    pub experiment_id: Option<String>,
+    pub user_id: Option<String>,
+    #[serde(default, deserialize_with = "int64")]
+    pub start_time: Option<i64>,
+    #[serde(default)]
+    pub tags: Vec<KeyValue>,
+    pub run_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunIdQuery {
+    #[serde(flatten)]
+    pub run: RunRef,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRunRequest {
+    #[serde(flatten)]
+    pub run: RunRef,
+    pub status: Option<String>,
+    pub run_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogMetricRequest {
+    #[serde(flatten)]
+    pub run: RunRef,
+    #[serde(flatten)]
+    pub metric: Metric,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunKeyValueRequest {
+    #[serde(flatten)]
+    pub run: RunRef,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogBatchRequest {
+    pub run_id: String,
+    #[serde(default)]
+    pub metrics: Vec<Metric>,
+    #[serde(default)]
+    pub params: Vec<KeyValue>,
+    #[serde(default)]
+    pub tags: Vec<KeyValue>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchRunsRequest {
+    #[serde(default)]
+    pub experiment_ids: Vec<String>,
+    pub filter: Option<String>,
+    #[serde(default, deserialize_with = "int64")]
+    pub max_results: Option<i64>,
+    /// At most one clause, e.g. `metrics.rmse ASC`
+    #[serde(default)]
+    pub order_by: Vec<String>,
+    pub page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetricHistoryQuery {
+    #[serde(flatten)]
+    pub run: RunRef,
+    pub metric_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListArtifactsQuery {
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchExperimentsResponse {
+    pub experiments: Vec<ExperimentInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchRunsResponse {
+    pub runs: Vec<Run>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+}
+
+fn parse_experiment_id(id: &str) -> Result<i64> {
+    id.parse()
+        .map_err(|_| MlflowError::invalid(format!("Invalid experiment id '{}'", id)))
+}
+
+fn parse_page_token(token: Option<&str>) -> Result<i64> {
+    mlflow::page_from_token(token)
+        .ok_or_else(|| MlflowError::invalid("Invalid page token".to_string()))
+}
+
+async fn find_experiment(db: &Database, id: i64) -> Result<MlflowExperiment> {
+    db.get_mlflow_experiment(id)
+        .await?
+        .ok_or_else(|| MlflowError::not_found(format!("No experiment with id '{}'", id)))
+}
+
+/// The run a request names, which must exist.
+async fn requested_run(db: &Database, run: &RunRef) -> Result<Experiment> {
+    let run_id = run.id().ok_or_else(|| {
+        MlflowError::invalid("Missing value for required parameter 'run_id'".to_string())
+    })?;
+    find_run(db, run_id).await
+}
+
+async fn find_run(db: &Database, run_id: &str) -> Result<Experiment> {
+    db.get_experiment(run_id)
+        .await?
+        .ok_or_else(|| MlflowError::not_found(format!("Run '{}' not found", run_id)))
+}
+
+async fn experiment_info(db: &Database, experiment: MlflowExperiment) -> Result<ExperimentInfo> {
+    let tags = db.get_mlflow_experiment_tags(experiment.id).await?;
+    Ok(ExperimentInfo::new(experiment, tags))
+}
+
+async fn run_artifact_location(db: &Database, run: &Experiment) -> Result<String> {
+    let id = run.mlflow_experiment_id.unwrap_or_default();
+    Ok(match db.get_mlflow_experiment(id).await? {
+        Some(experiment) => mlflow::artifact_location(&experiment),
+        None => format!("mlflow-artifacts:/{}", id),
+    })
+}
+
+async fn load_run(db: &Database, run: &Experiment) -> Result<Run> {
+    let metrics = db
+        .get_latest_metrics(&run.id)
+        .await?
+        .into_iter()
+        .map(Metric::from)
+        .collect();
+    let tags = db.get_experiment_tags(&run.id).await?;
+    let artifact_location = run_artifact_location(db, run).await?;
+    Ok(Run::new(run, metrics, tags, &artifact_location))
+}
+
+/// Logs params, refusing to change the value of one already logged, as MLflow does.
+async fn log_params(db: &Database, run: &Experiment, params: &[KeyValue]) -> Result<()> {
+    let logged: Vec<(String, String)> = run
+        .parameters
+        .as_deref()
+        .and_then(|p| serde_json::from_str(p).ok())
+        .map(|p| search::param_values(&p))
+        .unwrap_or_default();
+    for param in params {
+        if let Some((_, value)) = logged
+            .iter()
+            .find(|(key, value)| *key == param.key && *value != param.value)
+        {
+            return Err(MlflowError::invalid(format!(
+                "Changing param values is not allowed. Param '{}' was already logged with value '{}'",
+                param.key, value
+            )));
+        }
+    }
+
+    let params: serde_json::Map<String, Value> = params
+        .iter()
+        .map(|param| (param.key.clone(), Value::String(param.value.clone())))
+        .collect();
+    db.log_experiment_params(&run.id, &Value::Object(params))
+        .await?;
+    Ok(())
+}
+
+async fn log_metrics(db: &Database, run: &Experiment, metrics: &[Metric]) -> Result<()> {
+    let mut points = Vec::with_capacity(metrics.len());
+    for metric in metrics {
+        if metric.key.is_empty() || !metric.value.is_finite() {
+            return Err(MlflowError::invalid(format!(
+                "Invalid value {} for metric '{}'",
+                metric.value, metric.key
+            )));
+        }
+        points.push(MetricPoint {
+            key: metric.key.clone(),
+            value: metric.value,
+            step: metric.step,
+            timestamp: metric.timestamp.and_then(mlflow::from_millis),
+        });
+    }
+    db.log_experiment_metrics(&run.id, &points).await?;
+    Ok(())
+}
+
+/// Sets run tags; `mlflow.runName` also renames the run.
+async fn set_tags(db: &Database, run: &Experiment, tags: &[KeyValue]) -> Result<()> {
+    if let Some(name) = tags.iter().rev().find(|tag| tag.key == RUN_NAME_TAG) {
+        db.rename_experiment(&run.id, &name.value).await?;
+    }
+    let tags: Vec<(String, String)> = tags
+        .iter()
+        .map(|tag| (tag.key.clone(), tag.value.clone()))
+        .collect();
+    db.set_experiment_tags(&run.id, &tags).await?;
+    Ok(())
+}
+
+pub async fn create_experiment(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<CreateExperimentRequest>,
+) -> Result<Json<Value>> {
+    if payload.name.trim().is_empty() {
+        return Err(MlflowError::invalid(
+            "Experiment name must not be empty".to_string(),
+        ));
+    }
+    if db
+        .get_mlflow_experiment_by_name(&payload.name)
+        .await?
+        .is_some()
+    {
+        return Err(MlflowError::already_exists(format!(
+            "Experiment '{}' already exists",
+            payload.name
+        )));
+    }
+
+    let tags: Vec<(String, String)> = payload
+        .tags
+        .into_iter()
+        .map(|tag| (tag.key, tag.value))
+        .collect();
+    let experiment = db
+        .create_mlflow_experiment(
+            &payload.name,
+            payload
+                .artifact_location
+                .as_deref()
+                .filter(|l| !l.is_empty()),
+            &tags,
+        )
+        .await?;
+    Ok(Json(json!({ "experiment_id": experiment.id.to_string() })))
+}
+
+pub async fn get_experiment(
+    State(db): State<Arc<Database>>,
+    Query(query): Query<ExperimentIdRequest>,
+) -> Result<Json<Value>> {
+    let experiment = find_experiment(&db, parse_experiment_id(&query.experiment_id)?).await?;
+    let experiment = experiment_info(&db, experiment).await?;
+    Ok(Json(json!({ "experiment": experiment })))
+}
+
+pub async fn get_experiment_by_name(
+    State(db): State<Arc<Database>>,
+    Query(query): Query<ExperimentNameQuery>,
+) -> Result<Json<Value>> {
+    let experiment = db
+        .get_mlflow_experiment_by_name(&query.experiment_name)
+        .await?
+        .ok_or_else(|| {
+            MlflowError::not_found(format!(
+                "Could not find experiment with name '{}'",
+                query.experiment_name
+            ))
+        })?;
+    let experiment = experiment_info(&db, experiment).await?;
+    Ok(Json(json!({ "experiment": experiment })))
+}
+
+pub async fn search_experiments(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<SearchExperimentsRequest>,
+) -> Result<Json<SearchExperimentsResponse>> {
+    let stages: &[&str] = match payload.view_type.as_deref().unwrap_or("ACTIVE_ONLY") {
+        "ACTIVE_ONLY" => &["active"],
+        "DELETED_ONLY" => &["deleted"],
+        "ALL" => &["active", "deleted"],
+        other => {
+            return Err(MlflowError::invalid(format!(
+                "Invalid view type '{}'",
+                other
+            )))
+        }
+    };
+    let conditions = search::parse_filter(payload.filter.as_deref().unwrap_or_default())?;
+    if conditions
+        .iter()
+        .any(|condition| condition.field != Field::Attribute(Attribute::Name))
+    {
+        return Err(MlflowError::invalid(
+            "Experiments can only be filtered by name".to_string(),
+        ));
+    }
+
+    let page = parse_page_token(payload.page_token.as_deref())?;
+    let page_size = payload
+        .max_results
+        .unwrap_or(MAX_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    // One more than a page tells whether there is a next one
+    let mut experiments = db
+        .search_mlflow_experiments(stages, &conditions, page_size + 1, (page - 1) * page_size)
+        .await?;
+    let next_page_token = (experiments.len() as i64 > page_size).then(|| (page + 1).to_string());
+    experiments.truncate(page_size as usize);
+
+    let mut infos = Vec::with_capacity(experiments.len());
+    for experiment in experiments {
+        infos.push(experiment_info(&db, experiment).await?);
+    }
+    Ok(Json(SearchExperimentsResponse {
+        experiments: infos,
+        next_page_token,
+    }))
+}
+
+pub async fn update_experiment(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<UpdateExperimentRequest>,
+) -> Result<Json<Value>> {
+    let experiment = find_experiment(&db, parse_experiment_id(&payload.experiment_id)?).await?;
+    if let Some(existing) = db.get_mlflow_experiment_by_name(&payload.new_name).await? {
+        if existing.id != experiment.id {
+            return Err(MlflowError::already_exists(format!(
+                "Experiment '{}' already exists",
+                payload.new_name
+            )));
+        }
+    }
+    db.update_mlflow_experiment(experiment.id, Some(&payload.new_name), None)
+        .await?;
+    Ok(Json(json!({})))
+}
+
+pub async fn delete_experiment(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<ExperimentIdRequest>,
+) -> Result<Json<Value>> {
+    let experiment = find_experiment(&db, parse_experiment_id(&payload.experiment_id)?).await?;
+    if experiment.id == 0 {
+        return Err(MlflowError::invalid_state(
+            "The default experiment cannot be deleted".to_string(),
+        ));
+    }
+    db.update_mlflow_experiment(experiment.id, None, Some("deleted"))
+        .await?;
+    Ok(Json(json!({})))
+}
+
+pub async fn restore_experiment(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<ExperimentIdRequest>,
+) -> Result<Json<Value>> {
+    let experiment = find_experiment(&db, parse_experiment_id(&payload.experiment_id)?).await?;
+    db.update_mlflow_experiment(experiment.id, None, Some("active"))
+        .await?;
+    Ok(Json(json!({})))
+}
+
+pub async fn set_experiment_tag(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<SetExperimentTagRequest>,
+) -> Result<Json<Value>> {
+    let experiment = find_experiment(&db, parse_experiment_id(&payload.experiment_id)?).await?;
+    db.set_mlflow_experiment_tag(experiment.id, &payload.key, &payload.value)
+        .await?;
+    Ok(Json(json!({})))
+}
+
+pub async fn create_run(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<CreateRunRequest>,
+) -> Result<Json<Value>> {
+    let experiment_id = parse_experiment_id(payload.experiment_id.as_deref().unwrap_or("0"))?;
+    let experiment = find_experiment(&db, experiment_id).await?;
+    if experiment.lifecycle_stage != "active" {
+        return Err(MlflowError::invalid_state(format!(
+            "Experiment '{}' is deleted",
+            experiment.name
+        )));
+    }
+
+    let started_at = payload
+        .start_time
+        .and_then(mlflow::from_millis)
+        .unwrap_or_else(|| chrono::Utc::now().naive_utc());
+    let mut tags: Vec<(String, String)> = payload
+        .tags
+        .into_iter()
+        .map(|tag| (tag.key, tag.value))
+        .collect();
+    let tagged_name = tags
+        .iter()
+        .find(|(key, _)| key == RUN_NAME_TAG)
+        .map(|(_, value)| value.clone());
+    let name = payload
+        .run_name
+        .filter(|name| !name.is_empty())
+        .or(tagged_name)
+        .unwrap_or_else(|| format!("run-{}", started_at.format("%Y%m%d-%H%M%S")));
+    tags.retain(|(key, _)| key != RUN_NAME_TAG);
+    tags.push((RUN_NAME_TAG.to_string(), name.clone()));
+    if let Some(user_id) = payload.user_id.filter(|user| !user.is_empty()) {
+        if !tags.iter().any(|(key, _)| key == USER_TAG) {
+            tags.push((USER_TAG.to_string(), user_id));
+        }
+    }
+
+    let run = db
+        .create_mlflow_run(experiment.id, &name, started_at, &tags)
+        .await?;
+    reproducibility::capture_in_background(&db, &run);
+    let run = load_run(&db, &run).await?;
+    Ok(Json(json!({ "run": run })))
+}
+
+pub async fn get_run(
+    State(db): State<Arc<Database>>,
+    Query(query): Query<RunIdQuery>,
+) -> Result<Json<Value>> {
+    let run = requested_run(&db, &query.run).await?;
+    let run = load_run(&db, &run).await?;
+    Ok(Json(json!({ "run": run })))
+}
+
+pub async fn update_run(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<UpdateRunRequest>,
+) -> Result<Json<Value>> {
+    let run = requested_run(&db, &payload.run).await?;
+    if let Some(status) = payload.status.as_deref() {
+        let status = mlflow::experiment_status(status)
+            .ok_or_else(|| MlflowError::invalid(format!("Invalid run status '{}'", status)))?;
+        status::transition(&db, &run.id, status, None).await?;
+    }
+    if let Some(name) = payload.run_name.filter(|name| !name.is_empty()) {
+        set_tags(
+            &db,
+            &run,
+            &[KeyValue {
+                key: RUN_NAME_TAG.to_string(),
+                value: name,
+            }],
+        )
+        .await?;
+    }
+
+    let run = find_run(&db, &run.id).await?;
+    let tags: Vec<KeyValue> = db
+        .get_experiment_tags(&run.id)
+        .await?
+        .into_iter()
+        .map(KeyValue::from)
+        .collect();
+    let artifact_location = run_artifact_location(&db, &run).await?;
+    Ok(Json(json!({
+        "run_info": RunInfo::new(&run, &tags, &artifact_location),
+    })))
+}
+
+pub async fn log_metric(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<LogMetricRequest>,
+) -> Result<Json<Value>> {
+    let run = requested_run(&db, &payload.run).await?;
+    log_metrics(&db, &run, &[payload.metric]).await?;
+    Ok(Json(json!({})))
+}
+
+pub async fn log_param(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<RunKeyValueRequest>,
+) -> Result<Json<Value>> {
+    let run = requested_run(&db, &payload.run).await?;
+    let param = KeyValue {
+        key: payload.key,
+        value: payload.value,
+    };
+    log_params(&db, &run, &[param]).await?;
+    Ok(Json(json!({})))
+}
+
+pub async fn set_tag(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<RunKeyValueRequest>,
+) -> Result<Json<Value>> {
+    let run = requested_run(&db, &payload.run).await?;
+    let tag = KeyValue {
+        key: payload.key,
+        value: payload.value,
+    };
+    set_tags(&db, &run, &[tag]).await?;
+    Ok(Json(json!({})))
+}
+
+pub async fn log_batch(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<LogBatchRequest>,
+) -> Result<Json<Value>> {
+    let run = requested_run(&db, &payload.run).await?;
+    log_params(&db, &run, &payload.params).await?;
+    log_metrics(&db, &run, &payload.metrics).await?;
+    set_tags(&db, &run, &payload.tags).await?;
+    Ok(Json(json!({})))
+}
+
+pub async fn search_runs(
+    State(db): State<Arc<Database>>,
+    Json(payload): Json<SearchRunsRequest>,
+) -> Result<Json<SearchRunsResponse>> {
+    if payload.order_by.len() > 1 {
+        return Err(MlflowError::invalid(
+            "Only one order_by clause is supported".to_string(),
+        ));
+    }
+
+    let mut search = ExperimentSearch::new(
+        Some(parse_page_token(payload.page_token.as_deref())?),
+        payload.max_results,
+    );
+    search.mlflow_experiment_ids = payload
+        .experiment_ids
+        .iter()
+        .map(|id| parse_experiment_id(id))
+        .collect::<Result<_>>()?;
+    search.conditions = mlflow::parse_run_filter(payload.filter.as_deref().unwrap_or_default())?;
+    search.order_by = payload
+        .order_by
+        .first()
+        .map(|order_by| OrderBy::parse(order_by))
+        .transpose()?;
+
+    let (experiments, total) = db.search_experiments(&search).await?;
+    let mut runs = Vec::with_capacity(experiments.len());
+    for experiment in &experiments {
+        runs.push(load_run(&db, experiment).await?);
+    }
+    let next_page_token =
+        (search.page * search.page_size < total).then(|| (search.page + 1).to_string());
+    Ok(Json(SearchRunsResponse {
+        runs,
+        next_page_token,
+    }))
+}
+
+pub async fn get_metric_history(
+    State(db): State<Arc<Database>>,
+    Query(query): Query<MetricHistoryQuery>,
+) -> Result<Json<Value>> {
+    let run = requested_run(&db, &query.run).await?;
+    let metrics: Vec<Metric> = db
+        .get_metric_series(&run.id, &query.metric_key)
+        .await?
+        .into_iter()
+        .map(|point| Metric {
+            key: query.metric_key.clone(),
+            value: point.value,
+            timestamp: Some(mlflow::millis(point.timestamp)),
+            step: Some(point.step),
+        })
+        .collect();
+    Ok(Json(json!({ "metrics": metrics })))
+}
+
+/// Run id and artifact path of a proxy path, with the run checked to exist.
+async fn proxy_target(db: &Database, path: &str) -> Result<(Experiment, String)> {
+    let (run_id, path) = mlflow::parse_proxy_path(path).ok_or_else(|| {
+        MlflowError::invalid(format!(
+            "Artifact path '{}' is not of the form <experiment>/<run>/artifacts/<path>",
+            path
+        ))
+    })?;
+    let run = find_run(db, run_id).await?;
+    Ok((run, path.to_string()))
+}
+
+pub async fn list_artifacts(
+    State(db): State<Arc<Database>>,
+    Query(query): Query<ListArtifactsQuery>,
+) -> Result<Json<Value>> {
+    let (run, dir) = proxy_target(&db, query.path.as_deref().unwrap_or_default()).await?;
+    let artifacts = db.list_experiment_artifacts(&run.id).await?;
+    Ok(Json(
+        json!({ "files": mlflow::list_directory(&artifacts, &dir) }),
+    ))
+}
+
+pub async fn upload_artifact(
+    State(db): State<Arc<Database>>,
+    Path(path): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<Value>> {
+    let (run, path) = proxy_target(&db, &path).await?;
+    let path = artifacts::artifact_path(&path)?;
+    artifacts::save_body(&db, &run.id, &path, &headers, body).await?;
+    Ok(Json(json!({})))
+}
+
+pub async fn download_artifact(
+    State(db): State<Arc<Database>>,
+    Path(path): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let (run, path) = proxy_target(&db, &path).await?;
+    let path = artifacts::artifact_path(&path)?;
+    let artifact = db
+        .get_experiment_artifact(&run.id, &path)
+        .await?
+        .ok_or_else(|| MlflowError::not_found(format!("Artifact '{}' not found", path)))?;
+    Ok(artifacts::serve_artifact(&artifact, &headers).await?)
+}
+
+/// Tracking endpoints, served under `/api/2.0/mlflow`.
+pub fn create_router() -> Router<Arc<Database>> {
+    Router::new()
+        .route("/experiments/create", post(create_experiment))
+        .route("/experiments/get", get(get_experiment))
+        .route("/experiments/get-by-name", get(get_experiment_by_name))
+        .route("/experiments/search", post(search_experiments))
+        .route("/experiments/update", post(update_experiment))
+        .route("/experiments/delete", post(delete_experiment))
+        .route("/experiments/restore", post(restore_experiment))
+        .route("/experiments/set-experiment-tag", post(set_experiment_tag))
+        .route("/runs/create", post(create_run))
+        .route("/runs/get", get(get_run))
+        .route("/runs/update", post(update_run))
+        .route("/runs/log-metric", post(log_metric))
+        .route("/runs/log-parameter", post(log_param))
+        .route("/runs/set-tag", post(set_tag))
+        .route("/runs/log-batch", post(log_batch))
+        .route("/runs/search", post(search_runs))
+        .route("/metrics/get-history", get(get_metric_history))
+}
+
+/// Artifact proxy for `mlflow-artifacts:` URIs, served under `/api/2.0/mlflow-artifacts`.
+pub fn create_artifacts_router() -> Router<Arc<Database>> {
+    Router::new()
+        .route("/artifacts", get(list_artifacts))
+        .route(
+            "/artifacts/*path",
+            get(download_artifact)
+                .put(upload_artifact)
+                .layer(DefaultBodyLimit::disable()),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn call(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn post(uri: &str, body: Value) -> Request<Body> {
+        Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stock_client_payloads() {
+        let db = Arc::new(Database::new().await.unwrap());
+        let run = db
+            .create_mlflow_run(0, "client-run", chrono::Utc::now().naive_utc(), &[])
+            .await
+            .unwrap();
+        let app = create_router().with_state(Arc::clone(&db));
+
+        // As `mlflow.log_metric` and friends send them, with both run keys
+        let (status, _) = call(
+            &app,
+            post(
+                "/runs/log-metric",
+                json!({"run_id": run.id, "run_uuid": run.id, "key": "loss",
+                       "value": 0.25, "timestamp": 1_700_000_000_000i64, "step": 2}),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = call(
+            &app,
+            post(
+                "/runs/log-parameter",
+                json!({"run_id": run.id, "run_uuid": run.id, "key": "lr", "value": "0.1"}),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = call(
+            &app,
+            post(
+                "/runs/set-tag",
+                json!({"run_id": run.id, "run_uuid": run.id, "key": "team", "value": "ml"}),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = call(
+            &app,
+            post(
+                "/runs/update",
+                json!({"run_id": run.id, "run_uuid": run.id, "status": "FINISHED",
+                       "end_time": 1_700_000_001_000i64}),
+            ),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["run_info"]["status"], "FINISHED");
+
+        let (status, body) = call(
+            &app,
+            Request::get(format!("/runs/get?run_uuid={0}&run_id={0}", run.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["run"]["data"]["params"][0]["value"], "0.1");
+        let (status, body) = call(
+            &app,
+            Request::get(format!(
+                "/metrics/get-history?run_id={0}&run_uuid={0}&metric_key=loss",
+                run.id
+            ))
+            .body(Body::empty())
+            .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["metrics"][0]["step"], 2);
+
+        let (status, body) = call(
+            &app,
+            post("/runs/log-metric", json!({"key": "loss", "value": 0.5})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error_code"], "INVALID_PARAMETER_VALUE");
+    }
+}
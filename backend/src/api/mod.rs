@@ -1,14 +1,20 @@
 pub mod ai;
+pub mod artifacts;
 pub mod automl;
 pub mod collaboration;
+pub mod connections;
 pub mod datasets;
 mod environment;
 pub mod experiments;
+pub mod hpo;
+pub mod jobs;
+pub mod mlflow;
 pub mod model_builder;
 pub mod notebooks;
 pub mod profiler;
 
 use axum::{
+    Extension,
     Router,
     routing::get,
     extract::{
@@ -67,6 +73,18 @@ pub fn create_api_router() -> Router {
         Arc::clone(&session_manager),
     ));
 
+    // Open the database once; handlers reach it through state or an extension
+    let db = Arc::new(
+        tokio::runtime::Runtime::new()
+            .expect("Failed to create tokio runtime")
+            .block_on(Database::new())
+            .expect("Failed to initialize database"),
+    );
+
+    // Run queued jobs against the same database, so their log and status
+    // events reach the API's subscribers
+    crate::jobs::worker::start(Arc::clone(&db), crate::jobs::workers());
+
     // Create a shared state that includes all services
     #[derive(Clone)]
     struct AppState {
@@ -78,12 +96,7 @@ pub fn create_api_router() -> Router {
     }
 
     let state = AppState {
-        db: Arc::new(
-            tokio::runtime::Runtime::new()
-                .expect("Failed to create tokio runtime")
-                .block_on(Database::new())
-                .expect("Failed to initialize database"),
-        ),
+        db: Arc::clone(&db),
         ai_copilot,
         model_builder,
         data_profiler,
@@ -96,11 +109,17 @@ pub fn create_api_router() -> Router {
         .nest("/model-builder", model_builder::create_router())
         .nest("/profiler", profiler::create_router())
         .nest("/environments", environment::create_router())
+        .nest("/connections", connections::create_router())
         .nest("/datasets", datasets::create_router())
         .nest("/experiments", experiments::create_router())
+        .nest("/2.0/mlflow", mlflow::create_router())
+        .nest("/2.0/mlflow-artifacts", mlflow::create_artifacts_router())
         .nest("/automl", automl::create_router())
+        .nest("/jobs", jobs::create_router())
+        .nest("/hpo", hpo::create_router())
         .nest("/notebooks", notebooks::create_router())
         .nest("/collaboration", collaboration::create_router())
+        .layer(Extension(db))
         .with_state(state);
     
     // Create WebSocket router for real-time collaboration
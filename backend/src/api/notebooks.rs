@@ -3,13 +3,18 @@ use axum::{
     Extension, Json,
 };
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::{
-    error::Result,
+    error::{AppError, Result},
     models::{Cell, Database, Notebook},
+    python::PythonExecutor,
 };
 
+/// How long a cell may run before it is stopped.
+const CELL_TIMEOUT: Duration = Duration::from_secs(300);
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateNotebookRequest {
     pub name: String,
@@ -116,15 +121,25 @@ pub async fn execute_cell(
     Path((notebook_id, cell_id)): Path<(Uuid, Uuid)>,
     Extension(db): Extension<std::sync::Arc<Database>>,
 ) -> Result<Json<Cell>> {
-    // In a real implementation, we would:
-    // 1. Get the cell content
-    // 2. Execute it using the Python executor
-    // 3. Save the output
-    // 4. Return the updated cell
-    
-    // This is a simplified version that just updates the cell with a mock output
+    let content = sqlx::query_scalar!(
+        "SELECT content FROM cells WHERE id = ? AND notebook_id = ?",
+        cell_id.to_string(),
+        notebook_id.to_string()
+    )
+    .fetch_optional(&*db.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Cell {} not found", cell_id)))?;
+
+    // Runs started with `openmind.track` are attributed to this notebook
+    let output = PythonExecutor::new()
+        .isolated()
+        .with_timeout(CELL_TIMEOUT)
+        .with_notebook(&notebook_id.to_string())
+        .execute_code(&content)
+        .await
+        .unwrap_or_else(|error| error);
     let now = chrono::Utc::now().naive_utc();
-    
+
     let cell = sqlx::query_as!(
         Cell,
         r#"
@@ -133,13 +148,15 @@ pub async fn execute_cell(
         WHERE id = ? AND notebook_id = ?
         RETURNING *
         "#,
-        Some("Output will be displayed here".to_string()),
+        output,
         now,
         cell_id.to_string(),
         notebook_id.to_string()
     )
-    .fetch_one(&*db.pool)
-    .await?;
+    .fetch_optional(&*db.pool)
+    .await?
+    // The cell was deleted while it ran
+    .ok_or_else(|| AppError::NotFound(format!("Cell {} not found", cell_id)))?;
 
     // Update notebook's updated_at
     sqlx::query!(
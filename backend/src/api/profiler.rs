@@ -1,9 +1,17 @@
-use crate::data::profiler::{DataProfiler, DataProfile};
+use crate::{
+    data::{
+        drift::{DriftReport, DriftThresholds},
+        outliers::OutlierThresholds,
+        profile_store,
+        profiler::{DataProfiler, DataProfile, ProfileMode, ProfilerConfig, MAX_HISTOGRAM_BINS},
+    },
+    models::Database,
+};
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    response::IntoResponse,
-    Json,
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -34,29 +42,230 @@ impl<T> ApiResponse<T> {
     }
 }
 
+/// Returned while the default profile of a dataset is computed in the background.
+#[derive(Debug, Serialize)]
+struct PendingProfile {
+    dataset_id: String,
+    version: i64,
+    status: &'static str,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ProfileDatasetRequest {
-    pub file_path: String,
+    pub dataset_id: String,
     pub sample_size: Option<usize>,
+    pub sample_seed: Option<u64>,
+    pub mode: Option<ProfileMode>,
+    /// At least 1; more than `MAX_HISTOGRAM_BINS` are clamped to it
+    pub histogram_bins: Option<usize>,
+    pub correlation_threshold: Option<f64>,
+    pub outlier_thresholds: Option<OutlierThresholds>,
+}
+
+impl ProfileDatasetRequest {
+    fn uses_defaults(&self) -> bool {
+        self.sample_size.is_none()
+            && self.sample_seed.is_none()
+            && self.mode.is_none()
+            && self.histogram_bins.is_none()
+            && self.correlation_threshold.is_none()
+            && self.outlier_thresholds.is_none()
+    }
+
+    fn histogram_bins(&self, default: usize) -> Result<usize, String> {
+        match self.histogram_bins {
+            Some(0) => Err("histogram_bins must be at least 1".to_string()),
+            Some(bins) => Ok(bins.min(MAX_HISTOGRAM_BINS)),
+            None => Ok(default),
+        }
+    }
 }
 
 pub async fn profile_dataset(
     State(profiler): State<Arc<DataProfiler>>,
+    Extension(db): Extension<Arc<Database>>,
     Json(payload): Json<ProfileDatasetRequest>,
-) -> impl IntoResponse {
-    // Check if file exists
-    if !std::path::Path::new(&payload.file_path).exists() {
-        return (
-            StatusCode::NOT_FOUND,
-            Json(ApiResponse::error("File not found".to_string())),
-        );
+) -> Response {
+    let dataset = match db.get_dataset(&payload.dataset_id).await {
+        Ok(Some(dataset)) => dataset,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<DataProfile>::error("Dataset not found".to_string())),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<DataProfile>::error(format!("Failed to load dataset: {}", e))),
+            )
+                .into_response()
+        }
+    };
+
+    // The default profile is cached per dataset version and computed in the
+    // background; poll until it is ready
+    if payload.uses_defaults() {
+        return match profile_store::profile_or_queue(&db, &dataset).await {
+            Ok(Some(profile)) => (StatusCode::OK, Json(ApiResponse::success(profile))).into_response(),
+            Ok(None) => (
+                StatusCode::ACCEPTED,
+                Json(ApiResponse::success(PendingProfile {
+                    dataset_id: dataset.id,
+                    version: dataset.version,
+                    status: "pending",
+                })),
+            )
+                .into_response(),
+            Err(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<DataProfile>::error(format!("Failed to profile dataset: {}", e))),
+            )
+                .into_response(),
+        };
+    }
+
+    let defaults = profiler.config();
+    let histogram_bins = match payload.histogram_bins(defaults.histogram_bins) {
+        Ok(bins) => bins,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<DataProfile>::error(message)),
+            )
+                .into_response()
+        }
+    };
+    let config = ProfilerConfig {
+        histogram_bins,
+        correlation_threshold: payload
+            .correlation_threshold
+            .unwrap_or(defaults.correlation_threshold),
+        mode: payload.mode.unwrap_or(defaults.mode),
+        sample_size: payload.sample_size.or(defaults.sample_size),
+        sample_seed: payload.sample_seed.unwrap_or(defaults.sample_seed),
+        outlier_thresholds: payload
+            .outlier_thresholds
+            .unwrap_or_else(|| defaults.outlier_thresholds.clone()),
+        ..defaults.clone()
+    };
+
+    match profiler.profile_file_with_config(&dataset.file_path, &config).await {
+        Ok(mut profile) => {
+            profile.file_path = dataset.name;
+            (StatusCode::OK, Json(ApiResponse::success(profile))).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<DataProfile>::error(format!("Failed to profile dataset: {}", e))),
+        )
+            .into_response(),
     }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DriftRequest {
+    /// Reference dataset, e.g. the training data
+    pub reference_dataset_id: Option<String>,
+    /// Compare against the reference dataset's cached profile instead of its rows
+    #[serde(default)]
+    pub use_stored_profile: bool,
+    /// An explicit reference profile, used when no reference dataset is given
+    pub reference_profile: Option<DataProfile>,
+    pub current_dataset_id: String,
+    pub thresholds: Option<DriftThresholds>,
+}
+
+pub async fn drift_report(
+    State(profiler): State<Arc<DataProfiler>>,
+    Extension(db): Extension<Arc<Database>>,
+    Json(payload): Json<DriftRequest>,
+) -> impl IntoResponse {
+    let current = match db.get_dataset(&payload.current_dataset_id).await {
+        Ok(Some(dataset)) => dataset,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<DriftReport>::error("Dataset not found".to_string())),
+            )
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::error(format!("Failed to load dataset: {}", e))),
+            )
+        }
+    };
+
+    let reference = match &payload.reference_dataset_id {
+        Some(id) => match db.get_dataset(id).await {
+            Ok(Some(dataset)) => Some(dataset),
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::error("Reference dataset not found".to_string())),
+                )
+            }
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::error(format!("Failed to load dataset: {}", e))),
+                )
+            }
+        },
+        None => None,
+    };
 
-    match profiler.profile_file(&payload.file_path).await {
-        Ok(profile) => (StatusCode::OK, Json(ApiResponse::success(profile))),
+    let thresholds = payload.thresholds.unwrap_or_default();
+    let result = match (reference, payload.reference_profile) {
+        (Some(reference), _) if payload.use_stored_profile => {
+            let stored = match profile_store::cached_profile(&db, &reference).await {
+                Ok(Some(profile)) => Ok(profile),
+                Ok(None) => profile_store::refresh_profile(&db, &profiler, &reference).await,
+                Err(e) => Err(e),
+            };
+            match stored {
+                Ok(profile) => profiler
+                    .compare_with_profile(&profile, &current.file_path, &thresholds)
+                    .await
+                    .map(|mut report| {
+                        report.reference = reference.name.clone();
+                        report
+                    }),
+                Err(e) => Err(e),
+            }
+        }
+        (Some(reference), _) => profiler
+            .compare_files(&reference.file_path, &current.file_path, &thresholds)
+            .await
+            .map(|mut report| {
+                report.reference = reference.name.clone();
+                report
+            }),
+        (None, Some(profile)) => {
+            profiler
+                .compare_with_profile(&profile, &current.file_path, &thresholds)
+                .await
+        }
+        (None, None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::error(
+                    "Either reference_dataset_id or reference_profile is required".to_string(),
+                )),
+            )
+        }
+    };
+
+    match result {
+        Ok(mut report) => {
+            report.current = current.name;
+            (StatusCode::OK, Json(ApiResponse::success(report)))
+        }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ApiResponse::error(format!("Failed to profile dataset: {}", e))),
+            Json(ApiResponse::error(format!("Failed to compute drift: {}", e))),
         ),
     }
 }
@@ -68,5 +277,31 @@ pub fn create_router() -> axum::Router<Arc<DataProfiler>> {
     
     axum::Router::new()
         .route("/profile", post(profile_dataset))
+        .route("/drift", post(drift_report))
         .with_state(profiler)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_bins_are_bounded() {
+        let request = |bins: Option<usize>| ProfileDatasetRequest {
+            dataset_id: "d".to_string(),
+            sample_size: None,
+            sample_seed: None,
+            mode: None,
+            histogram_bins: bins,
+            correlation_threshold: None,
+            outlier_thresholds: None,
+        };
+        assert_eq!(request(None).histogram_bins(20), Ok(20));
+        assert_eq!(request(Some(50)).histogram_bins(20), Ok(50));
+        assert_eq!(
+            request(Some(usize::MAX)).histogram_bins(20),
+            Ok(MAX_HISTOGRAM_BINS)
+        );
+        assert!(request(Some(0)).histogram_bins(20).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! Typed configuration of an AutoML search, checked before the search is
+//! queued and handed to `service.py` as `config.json`.
+
+use anyhow::{bail, ensure, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub const DEFAULT_CV_FOLDS: u32 = 5;
+pub const MAX_CV_FOLDS: u32 = 20;
+pub const DEFAULT_SEED: u64 = 42;
+pub const MAX_HORIZON: u32 = 10_000;
+
+/// Metrics candidates can be ranked by, for each task type.
+const CLASSIFICATION_METRICS: &[&str] = &["accuracy", "f1", "precision", "recall"];
+const REGRESSION_METRICS: &[&str] = &["r2", "mse", "rmse", "mae"];
+const FORECASTING_METRICS: &[&str] = &["mae", "rmse", "mape"];
+
+/// Metric candidates are ranked by when the request names none.
+pub fn default_metric(task_type: Option<&str>) -> &'static str {
+    match task_type {
+        Some("regression") => "r2",
+        Some("forecasting") => "mae",
+        _ => "accuracy",
+    }
+}
+
+/// Metrics known for `task_type`, or for any task type detected from the
+/// data; forecasting has to be asked for.
+fn metrics(task_type: Option<&str>) -> Result<Vec<&'static str>> {
+    Ok(match task_type {
+        Some("classification") => CLASSIFICATION_METRICS.to_vec(),
+        Some("regression") => REGRESSION_METRICS.to_vec(),
+        Some("forecasting") => FORECASTING_METRICS.to_vec(),
+        None => [CLASSIFICATION_METRICS, REGRESSION_METRICS].concat(),
+        Some(other) => bail!("Unsupported task type '{}'", other),
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClassWeight {
+    #[default]
+    None,
+    /// Weights classes inversely to their frequency, on models that support it
+    Balanced,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CategoricalEncoding {
+    /// A column per category, dropping the first
+    #[default]
+    OneHot,
+    /// Each category as an integer code, which keeps wide columns narrow
+    Ordinal,
+}
+
+/// How missing feature values are filled in. Categorical columns take their
+/// most frequent value, or `"missing"` with `Constant`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Imputation {
+    Mean,
+    #[default]
+    Median,
+    MostFrequent,
+    /// Zero for numbers
+    Constant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelFamily {
+    /// Logistic regression or ridge regression
+    #[serde(alias = "logistic_regression", alias = "ridge")]
+    Linear,
+    RandomForest,
+    ExtraTrees,
+    GradientBoosting,
+    Xgboost,
+    Lightgbm,
+}
+
+/// Format the dataset is handed to the script in; datasets in any other
+/// format are converted first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputFormat {
+    #[default]
+    Csv,
+    /// Keeps column types, such as dates and categories, that CSV loses
+    Parquet,
+    Json,
+}
+
+impl InputFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            InputFormat::Csv => ".csv",
+            InputFormat::Parquet => ".parquet",
+            InputFormat::Json => ".json",
+        }
+    }
+}
+
+/// How a search prepares the data and picks its model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoMLConfig {
+    /// Metric ranking the candidates; accuracy or r2 by default
+    pub metric: Option<String>,
+    /// Cross-validation folds scoring each candidate, or backtest windows of
+    /// a forecast
+    pub cv_folds: Option<u32>,
+    /// Keeps class proportions in every split of a classification
+    pub stratify: bool,
+    pub class_weight: ClassWeight,
+    pub categorical_encoding: CategoricalEncoding,
+    pub imputation: Imputation,
+    /// Seeds the splits and the models
+    pub seed: u64,
+    /// Models to try; all installed ones by default
+    #[serde(alias = "candidates")]
+    pub model_families: Option<Vec<ModelFamily>>,
+    pub input_format: InputFormat,
+    /// Keyword arguments for the models, e.g. `{"n_estimators": 500}`; each
+    /// candidate takes those it accepts
+    pub parameters: Option<Map<String, Value>>,
+    /// Column ordering the rows of a forecast
+    pub timestamp_column: Option<String>,
+    /// Steps a forecast predicts past the end of the data, and the length of
+    /// each backtest window
+    pub horizon: Option<u32>,
+    /// Column naming the series each row belongs to, when the dataset holds
+    /// several
+    pub group_column: Option<String>,
+    /// Steps in a season, e.g. 7 for daily data with a weekly cycle; inferred
+    /// from the timestamps by default
+    pub season_length: Option<u32>,
+}
+
+impl Default for AutoMLConfig {
+    fn default() -> Self {
+        Self {
+            metric: None,
+            cv_folds: None,
+            stratify: true,
+            class_weight: ClassWeight::default(),
+            categorical_encoding: CategoricalEncoding::default(),
+            imputation: Imputation::default(),
+            seed: DEFAULT_SEED,
+            model_families: None,
+            input_format: InputFormat::default(),
+            parameters: None,
+            timestamp_column: None,
+            horizon: None,
+            group_column: None,
+            season_length: None,
+        }
+    }
+}
+
+impl AutoMLConfig {
+    pub fn validate(&self, task_type: Option<&str>, target_column: &str) -> Result<()> {
+        let metrics = metrics(task_type)?;
+        if let Some(metric) = &self.metric {
+            ensure!(
+                metrics.contains(&metric.as_str()),
+                "Unknown metric '{}'; expected one of {}",
+                metric,
+                metrics.join(", ")
+            );
+        }
+        if let Some(cv_folds) = self.cv_folds {
+            ensure!(
+                (2..=MAX_CV_FOLDS).contains(&cv_folds),
+                "cv_folds must be between 2 and {}",
+                MAX_CV_FOLDS
+            );
+        }
+        if let Some(families) = &self.model_families {
+            ensure!(!families.is_empty(), "model_families is empty");
+        }
+
+        if task_type != Some("forecasting") {
+            ensure!(
+                self.timestamp_column.is_none()
+                    && self.horizon.is_none()
+                    && self.group_column.is_none()
+                    && self.season_length.is_none(),
+                "timestamp_column, horizon, group_column and season_length only apply to forecasting"
+            );
+            return Ok(());
+        }
+        let Some(timestamp_column) = &self.timestamp_column else {
+            bail!("Forecasting needs a timestamp_column");
+        };
+        ensure!(
+            self.horizon
+                .is_some_and(|horizon| (1..=MAX_HORIZON).contains(&horizon)),
+            "Forecasting needs a horizon between 1 and {}",
+            MAX_HORIZON
+        );
+        ensure!(
+            self.season_length != Some(0),
+            "season_length must be positive"
+        );
+        ensure!(
+            timestamp_column != target_column
+                && self.group_column.as_deref() != Some(target_column)
+                && self.group_column.as_ref() != Some(timestamp_column),
+            "The target, timestamp and group columns must differ"
+        );
+        Ok(())
+    }
+
+    /// Fills in the defaults that depend on the task type, so the experiment
+    /// records what the search actually used.
+    pub fn resolve(&mut self, task_type: Option<&str>) {
+        self.cv_folds.get_or_insert(DEFAULT_CV_FOLDS);
+        if task_type.is_some() {
+            self.metric
+                .get_or_insert_with(|| default_metric(task_type).to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_automl_config() {
+        let mut config: AutoMLConfig = serde_json::from_value(json!({
+            "candidates": ["ridge", "xgboost"],
+            "imputation": "most_frequent",
+            "parameters": {"n_estimators": 50}
+        }))
+        .unwrap();
+        assert_eq!(
+            config.model_families,
+            Some(vec![ModelFamily::Linear, ModelFamily::Xgboost])
+        );
+        assert_eq!(config.imputation, Imputation::MostFrequent);
+        assert!(config.stratify);
+        assert_eq!(config.seed, DEFAULT_SEED);
+        config.validate(Some("regression"), "price").unwrap();
+
+        config.resolve(Some("regression"));
+        assert_eq!(config.metric.as_deref(), Some("r2"));
+        assert_eq!(config.cv_folds, Some(DEFAULT_CV_FOLDS));
+        let echoed = serde_json::to_value(&config).unwrap();
+        assert_eq!(echoed["model_families"], json!(["linear", "xgboost"]));
+        assert_eq!(echoed["categorical_encoding"], "one_hot");
+
+        config.metric = Some("accuracy".to_string());
+        assert!(config.validate(Some("regression"), "price").is_err());
+        assert!(config.validate(None, "price").is_ok());
+        assert!(config.validate(Some("clustering"), "price").is_err());
+        config.cv_folds = Some(1);
+        assert!(config.validate(None, "price").is_err());
+        assert!(serde_json::from_value::<AutoMLConfig>(json!({"imputation": "knn"})).is_err());
+    }
+
+    #[test]
+    fn test_forecasting_config() {
+        let mut config: AutoMLConfig = serde_json::from_value(json!({
+            "timestamp_column": "date",
+            "horizon": 14,
+            "group_column": "store"
+        }))
+        .unwrap();
+        config.validate(Some("forecasting"), "sales").unwrap();
+        config.resolve(Some("forecasting"));
+        assert_eq!(config.metric.as_deref(), Some("mae"));
+
+        assert!(config.validate(Some("regression"), "sales").is_err());
+        assert!(config.validate(Some("forecasting"), "date").is_err());
+        config.metric = Some("r2".to_string());
+        assert!(config.validate(Some("forecasting"), "sales").is_err());
+        config.metric = None;
+        config.horizon = Some(0);
+        assert!(config.validate(Some("forecasting"), "sales").is_err());
+        config.horizon = Some(7);
+        config.timestamp_column = None;
+        assert!(config.validate(Some("forecasting"), "sales").is_err());
+    }
+}
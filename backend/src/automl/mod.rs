@@ -0,0 +1,608 @@
+pub mod config;
+
+use crate::{
+    data::{
+        reader::{Compression, DatasetFormat},
+        DatasetReader,
+    },
+    error::AppError,
+    jobs::{self, worker, JobError, JobKind, Limit, Limits},
+    models::{Database, FinishedRun, Job},
+    tracking::{artifacts, status, ExperimentStatus, Leaderboard},
+};
+use anyhow::{Context, Result};
+use polars::prelude::{CsvWriter, JsonFormat, JsonWriter, ParquetWriter, SerWriter};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tempfile::NamedTempFile;
+use tokio::process::Command;
+
+pub use config::AutoMLConfig;
+use config::InputFormat;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoMLRequest {
+    pub dataset_id: String,
+    pub target_column: String,
+    /// `classification`, `regression` or `forecasting`; classification or
+    /// regression is detected from the target by default
+    pub task_type: Option<String>,
+    /// Share of the rows held out for testing; forecasts are backtested instead
+    pub test_size: Option<f64>,
+    #[serde(flatten)]
+    pub config: AutoMLConfig,
+    /// Stops the search, keeping the best model so far, after this long
+    pub max_runtime_seconds: Option<u64>,
+    /// Most candidates to try
+    pub max_trials: Option<u32>,
+    /// Stops the search, keeping the best model so far, once it uses more
+    /// memory than this
+    pub max_memory_mb: Option<u64>,
+    /// Keys neither the request nor its configuration know, rejected rather
+    /// than silently ignored; `deny_unknown_fields` doesn't work with `flatten`
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub unknown_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl AutoMLRequest {
+    pub fn validate(&self) -> Result<()> {
+        if !self.unknown_fields.is_empty() {
+            let keys: Vec<&str> = self.unknown_fields.keys().map(String::as_str).collect();
+            anyhow::bail!("Unknown fields: {}", keys.join(", "));
+        }
+        if let Some(test_size) = self.test_size {
+            anyhow::ensure!(
+                test_size > 0.0 && test_size < 1.0,
+                "test_size must be between 0 and 1"
+            );
+        }
+        if self.max_runtime_seconds == Some(0)
+            || self.max_trials == Some(0)
+            || self.max_memory_mb == Some(0)
+        {
+            anyhow::bail!("Budgets must be positive");
+        }
+        self.config
+            .validate(self.task_type.as_deref(), &self.target_column)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoMLResponse {
+    pub experiment_id: String,
+    /// Job running the search; cancel it through `/api/jobs`
+    pub job_id: Option<String>,
+    pub status: String,
+    pub task_type: Option<String>,
+    /// Configuration the search runs with, defaults filled in
+    pub config: Option<AutoMLConfig>,
+    pub metrics: Option<serde_json::Value>,
+    pub model_path: Option<String>,
+    pub results_path: Option<String>,
+    pub feature_importances: Option<serde_json::Value>,
+    /// Request budget that stopped the search early
+    pub budget_exhausted: Option<String>,
+    pub error: Option<String>,
+}
+
+/// A bound on a search, named after its request field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Budget {
+    MaxRuntimeSeconds,
+    MaxTrials,
+    MaxMemoryMb,
+}
+
+impl Budget {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Budget::MaxRuntimeSeconds => "max_runtime_seconds",
+            Budget::MaxTrials => "max_trials",
+            Budget::MaxMemoryMb => "max_memory_mb",
+        }
+    }
+}
+
+impl From<Limit> for Budget {
+    fn from(limit: Limit) -> Self {
+        match limit {
+            Limit::Runtime => Budget::MaxRuntimeSeconds,
+            Limit::Memory => Budget::MaxMemoryMb,
+        }
+    }
+}
+
+/// Line `service.py` prints before the JSON summary of a search.
+const RESULTS_MARKER: &str = "AutoML Results:";
+
+/// The summary `service.py` prints when it is done.
+#[derive(Debug, Default, Deserialize)]
+struct ScriptOutput {
+    status: String,
+    task_type: Option<String>,
+    metric: Option<String>,
+    /// Candidates, best first
+    #[serde(default)]
+    leaderboard: Vec<Candidate>,
+    metrics: Option<serde_json::Value>,
+    model_path: Option<String>,
+    results_path: Option<String>,
+    feature_importances: Option<serde_json::Value>,
+    budget_exhausted: Option<Budget>,
+    error: Option<String>,
+}
+
+/// The `results.json` `service.py` writes next to the model.
+#[derive(Debug, Default, Deserialize)]
+struct ResultsFile {
+    /// Series of each metric
+    #[serde(default)]
+    metrics: BTreeMap<String, Vec<ResultsPoint>>,
+    feature_importances: Option<serde_json::Value>,
+    #[serde(default)]
+    leaderboard: Vec<Candidate>,
+    // Saved with each new best candidate, so they are there if the search is
+    // stopped before printing its summary
+    task_type: Option<String>,
+    metric: Option<String>,
+    model_path: Option<String>,
+}
+
+/// A model the search cross-validated.
+#[derive(Debug, Deserialize)]
+struct Candidate {
+    model: String,
+    #[serde(default)]
+    params: serde_json::Map<String, serde_json::Value>,
+    /// Mean cross-validated scores
+    #[serde(default)]
+    metrics: serde_json::Map<String, serde_json::Value>,
+    status: String,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResultsPoint {
+    value: f64,
+}
+
+pub struct AutoMLService {
+    db: Arc<Database>,
+    python_path: PathBuf,
+    automl_script_path: PathBuf,
+}
+
+impl AutoMLService {
+    pub fn new(db: Arc<Database>) -> Self {
+        // Get the path to the Python interpreter (use 'python3' by default)
+        let python_path = std::env::var("PYTHON_PATH").unwrap_or_else(|_| "python3".to_string());
+        
+        // Get the path to the AutoML script
+        let automl_script_path = Path::new("python/automl/service.py").to_path_buf();
+        
+        Self {
+            db,
+            python_path: PathBuf::from(python_path),
+            automl_script_path,
+        }
+    }
+
+    /// Creates the search's experiment and queues a job running it.
+    pub async fn start_automl(
+        &self,
+        mut request: AutoMLRequest,
+        notebook_id: Option<&str>,
+    ) -> crate::error::Result<AutoMLResponse> {
+        let dataset = self
+            .db
+            .get_dataset(&request.dataset_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Dataset {} not found", request.dataset_id))
+            })?;
+        request
+            .validate()
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+        request.config.resolve(request.task_type.as_deref());
+        
+        // The request is kept as the experiment's parameters, echoing the
+        // configuration with its defaults
+        let experiment = self
+            .db
+            .create_experiment(
+                &format!("AutoML: {} on {}", request.target_column, dataset.name),
+                notebook_id,
+                Some(&dataset.id),
+                Some(
+                    serde_json::to_value(&request).map_err(|e| AppError::Internal(e.into()))?,
+                ),
+            )
+            .await?;
+        let (job, experiment) =
+            jobs::enqueue(&self.db, JobKind::Automl, &request, Some(&experiment.id)).await?;
+        let experiment = experiment.ok_or_else(|| {
+            AppError::Internal(anyhow::anyhow!("Queued AutoML job has no experiment"))
+        })?;
+        
+        // Return immediately; the worker reports progress on the experiment
+        Ok(AutoMLResponse {
+            experiment_id: experiment.id,
+            job_id: Some(job.id),
+            status: experiment.status,
+            task_type: request.task_type,
+            config: Some(request.config),
+            metrics: None,
+            model_path: None,
+            results_path: None,
+            feature_importances: None,
+            budget_exhausted: None,
+            error: None,
+        })
+    }
+    
+    /// Runs the AutoML script for a claimed job and records what it found on
+    /// the job's experiment.
+    pub async fn run(&self, job: &Job, request: AutoMLRequest) -> std::result::Result<(), JobError> {
+        let experiment_id = job
+            .experiment_id
+            .as_deref()
+            .ok_or_else(|| JobError::Invalid("AutoML job has no experiment".to_string()))?;
+        let dataset = self
+            .db
+            .get_dataset(&request.dataset_id)
+            .await?
+            .ok_or_else(|| JobError::Invalid(format!("Dataset {} not found", request.dataset_id)))?;
+        
+        // Datasets in another format than the script is configured to read
+        // are converted to a temporary file, kept until the process has
+        // finished with it
+        let (dataset_path, _converted) =
+            prepare_training_data(&dataset.file_path, request.config.input_format).await?;
+        let config_path = write_config(experiment_id, &request.config).await?;
+        
+        // Build the command to run the AutoML script
+        let mut cmd = Command::new(&self.python_path);
+        cmd.arg(&self.automl_script_path)
+            .arg("--data").arg(dataset_path)
+            .arg("--target").arg(&request.target_column)
+            .arg("--experiment-id").arg(experiment_id)
+            .arg("--test-size").arg(request.test_size.unwrap_or(0.2).to_string())
+            .arg("--config").arg(config_path);
+            
+        if let Some(task_type) = &request.task_type {
+            cmd.arg("--task-type").arg(task_type);
+        }
+        if let Some(max_trials) = request.max_trials {
+            cmd.arg("--max-trials").arg(max_trials.to_string());
+        }
+        
+        let limits = Limits {
+            runtime: request.max_runtime_seconds.map(Duration::from_secs),
+            memory_mb: request.max_memory_mb,
+        };
+        let (stdout, exceeded) = match worker::run_limited(&self.db, job, cmd, limits).await {
+            Ok(stdout) => (stdout, None),
+            // The search saves each new best model, so what it found still counts
+            Err(JobError::LimitExceeded(limit)) => (String::new(), Some(Budget::from(limit))),
+            Err(e) => return Err(e),
+        };
+        let reported = self
+            .record_results(experiment_id, &stdout)
+            .await
+            .map_err(|e| match exceeded {
+                Some(budget) => JobError::Invalid(format!("Exceeded {} before saving a model", budget.as_str())),
+                None => e,
+            })?;
+        
+        match exceeded.or(reported) {
+            Some(budget) => self.exhaust(experiment_id, budget).await,
+            None => Ok(()),
+        }
+    }
+    
+    /// Completes the search's experiment as stopped by `budget`, ahead of the
+    /// worker completing it as usual.
+    async fn exhaust(&self, experiment_id: &str, budget: Budget) -> std::result::Result<(), JobError> {
+        self.db.set_automl_budget_exhausted(experiment_id, budget.as_str()).await?;
+        status::transition(&self.db, experiment_id, ExperimentStatus::CompletedWithBudgetExhausted, None).await?;
+        self.db
+            .add_experiment_log(
+                experiment_id,
+                "warning",
+                &format!("Stopped by its {} budget; kept the best model found so far", budget.as_str()),
+            )
+            .await?;
+        Ok(())
+    }
+    
+    /// Stores the metrics, model path and feature importances of a finished
+    /// search from its printed summary, falling back to `results.json`, and
+    /// returns the budget the script reports stopping it.
+    async fn record_results(&self, experiment_id: &str, stdout: &str) -> std::result::Result<Option<Budget>, JobError> {
+        let output = parse_output(stdout).unwrap_or_default();
+        if output.status == "error" {
+            // The script reports failures of the pipeline itself; a rerun won't fix them
+            return Err(JobError::Invalid(
+                output.error.unwrap_or_else(|| "AutoML pipeline failed".to_string()),
+            ));
+        }
+        
+        let output_dir = artifacts::experiment_output_dir(experiment_id);
+        let results_path = output
+            .results_path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| output_dir.join("results.json"));
+        let results = read_results_file(&results_path).await;
+        if output.status.is_empty() && results.is_none() {
+            return Err(JobError::Failed("The AutoML script reported no results".to_string()));
+        }
+        let results = results.unwrap_or_default();
+        
+        let metrics = output.metrics.unwrap_or_else(|| latest_metrics(&results));
+        self.db.update_experiment_metrics(experiment_id, metrics).await?;
+        if let Some(model_path) = output.model_path.or(results.model_path) {
+            self.db.set_experiment_model_path(experiment_id, &model_path).await?;
+        }
+        let feature_importances = output.feature_importances.or(results.feature_importances);
+        self.db
+            .save_automl_result(
+                experiment_id,
+                output.task_type.or(results.task_type).as_deref(),
+                output.metric.or(results.metric).as_deref(),
+                Some(results_path.to_string_lossy().as_ref()),
+                feature_importances.as_ref(),
+            )
+            .await?;
+        let candidates = if output.leaderboard.is_empty() {
+            results.leaderboard
+        } else {
+            output.leaderboard
+        };
+        self.record_candidates(experiment_id, candidates).await?;
+        artifacts::import_experiment_outputs(&self.db, experiment_id).await?;
+        Ok(output.budget_exhausted)
+    }
+    
+    /// Records each candidate as a child run of the search, unless an earlier
+    /// attempt already did.
+    async fn record_candidates(&self, experiment_id: &str, candidates: Vec<Candidate>) -> std::result::Result<(), JobError> {
+        let Some(search) = self.db.get_experiment(experiment_id).await? else {
+            return Ok(());
+        };
+        if !self.db.list_child_experiments(experiment_id).await?.is_empty() {
+            return Ok(());
+        }
+        
+        // Inserted finished, in one transaction, so a failure leaves none to
+        // pass for a complete record on retry
+        let runs: Vec<FinishedRun> = candidates
+            .into_iter()
+            .map(|candidate| {
+                let mut parameters = candidate.params;
+                parameters.insert("model".to_string(), candidate.model.clone().into());
+                FinishedRun {
+                    name: candidate.model,
+                    parameters: Some(parameters.into()),
+                    metrics: candidate.metrics.into(),
+                    status: if candidate.status == "completed" {
+                        ExperimentStatus::Completed
+                    } else {
+                        ExperimentStatus::Failed
+                    },
+                    failure_reason: candidate.error,
+                }
+            })
+            .collect();
+        self.db.create_finished_child_experiments(&search, &runs).await?;
+        Ok(())
+    }
+    
+    /// Candidates of a search ranked by `metric`, by default the one the search
+    /// used; `None` if there is no such experiment.
+    pub async fn get_leaderboard(&self, experiment_id: &str, metric: Option<&str>) -> Result<Option<Leaderboard>> {
+        let Some(experiment) = self.db.get_experiment(experiment_id).await? else {
+            return Ok(None);
+        };
+        let result = self.db.get_automl_result(experiment_id).await?;
+        let request: Option<AutoMLRequest> = experiment
+            .parameters
+            .as_deref()
+            .and_then(|parameters| serde_json::from_str(parameters).ok());
+        let task_type = result
+            .as_ref()
+            .and_then(|result| result.task_type.clone())
+            .or_else(|| request.as_ref().and_then(|request| request.task_type.clone()));
+        let metric = metric
+            .map(str::to_string)
+            .or_else(|| result.and_then(|result| result.metric))
+            .or_else(|| request.and_then(|request| request.config.metric))
+            .unwrap_or_else(|| config::default_metric(task_type.as_deref()).to_string());
+        
+        let runs = self.db.list_child_experiments(experiment_id).await?;
+        Ok(Some(Leaderboard::rank(experiment_id, &metric, runs)))
+    }
+    
+    /// Status and results of a search; `None` if there is no such experiment.
+    pub async fn get_experiment_status(&self, experiment_id: &str) -> Result<Option<AutoMLResponse>> {
+        let Some(experiment) = self.db.get_experiment(experiment_id).await? else {
+            return Ok(None);
+        };
+        let result = self.db.get_automl_result(experiment_id).await?;
+        let job = self
+            .db
+            .list_jobs(None, Some(experiment_id), 1)
+            .await?
+            .into_iter()
+            .next();
+        let parse = |json: Option<String>| json.and_then(|json| serde_json::from_str(&json).ok());
+        let request: Option<AutoMLRequest> = parse(experiment.parameters);
+        let (request_task_type, config) = match request {
+            Some(request) => (request.task_type, Some(request.config)),
+            None => (None, None),
+        };
+        
+        Ok(Some(AutoMLResponse {
+            experiment_id: experiment.id,
+            job_id: job.as_ref().map(|job| job.id.clone()),
+            status: experiment.status,
+            task_type: result
+                .as_ref()
+                .and_then(|result| result.task_type.clone())
+                .or(request_task_type),
+            config,
+            metrics: parse(experiment.metrics),
+            model_path: experiment.model_path,
+            results_path: result.as_ref().and_then(|result| result.results_path.clone()),
+            feature_importances: parse(result.as_ref().and_then(|result| result.feature_importances.clone())),
+            budget_exhausted: result.and_then(|result| result.budget_exhausted),
+            // A retried job's last error explains why it is queued again
+            error: experiment
+                .failure_reason
+                .or_else(|| job.and_then(|job| job.error)),
+        }))
+    }
+}
+
+/// The summary printed after [`RESULTS_MARKER`], if the script got that far.
+fn parse_output(stdout: &str) -> Option<ScriptOutput> {
+    let (_, summary) = stdout.rsplit_once(RESULTS_MARKER)?;
+    serde_json::from_str(summary.trim()).ok()
+}
+
+async fn read_results_file(path: &Path) -> Option<ResultsFile> {
+    let content = tokio::fs::read_to_string(path).await.ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Last value of each metric series.
+fn latest_metrics(results: &ResultsFile) -> serde_json::Value {
+    results
+        .metrics
+        .iter()
+        .filter_map(|(name, series)| Some((name.clone(), serde_json::Value::from(series.last()?.value))))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// Returns a path the AutoML script can read in `format`, converting the
+/// dataset when it is in another format, compressed, encoded or delimited
+/// differently.
+async fn prepare_training_data(file_path: &str, format: InputFormat) -> Result<(PathBuf, Option<NamedTempFile>)> {
+    let reader = DatasetReader::open(file_path)?;
+    let info = reader.info();
+    let readable = match format {
+        InputFormat::Csv => info.is_plain_csv(),
+        InputFormat::Parquet => info.format == DatasetFormat::Parquet && info.compression == Compression::None,
+        // JSON datasets may be laid out by column; the script reads rows
+        InputFormat::Json => false,
+    };
+    if readable {
+        return Ok((PathBuf::from(file_path), None));
+    }
+    
+    let mut df = reader.read(None).await?;
+    let mut file = tempfile::Builder::new().suffix(format.extension()).tempfile()?;
+    match format {
+        InputFormat::Csv => CsvWriter::new(file.as_file_mut()).finish(&mut df),
+        InputFormat::Parquet => ParquetWriter::new(file.as_file_mut()).finish(&mut df).map(|_| ()),
+        InputFormat::Json => JsonWriter::new(file.as_file_mut())
+            .with_json_format(JsonFormat::Json)
+            .finish(&mut df),
+    }
+    .with_context(|| format!("Failed to convert dataset to {}", format.extension()))?;
+    Ok((file.path().to_path_buf(), Some(file)))
+}
+
+/// Writes the search's configuration where the script reads it, next to its
+/// outputs so it is kept as an artifact.
+async fn write_config(experiment_id: &str, config: &AutoMLConfig) -> Result<PathBuf> {
+    let output_dir = artifacts::experiment_output_dir(experiment_id);
+    tokio::fs::create_dir_all(&output_dir).await?;
+    let path = output_dir.join("config.json");
+    tokio::fs::write(&path, serde_json::to_vec_pretty(config)?).await?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    
+    #[tokio::test]
+    async fn test_start_automl() {
+        let db = Database::new().await.unwrap();
+        let automl_service = AutoMLService::new(Arc::new(db));
+        
+        let request = AutoMLRequest {
+            dataset_id: "test-dataset".to_string(),
+            target_column: "target".to_string(),
+            task_type: Some("classification".to_string()),
+            test_size: Some(0.2),
+            config: AutoMLConfig::default(),
+            max_runtime_seconds: None,
+            max_trials: None,
+            max_memory_mb: None,
+            unknown_fields: serde_json::Map::new(),
+        };
+        
+        // The dataset doesn't exist
+        let response = automl_service.start_automl(request, None).await;
+        assert!(matches!(response, Err(AppError::NotFound(_))));
+    }
+    
+    #[test]
+    fn test_request_validation() {
+        let request = |body: serde_json::Value| {
+            let mut request = serde_json::json!({"dataset_id": "d", "target_column": "price"});
+            request.as_object_mut().unwrap().extend(body.as_object().unwrap().clone());
+            serde_json::from_value::<AutoMLRequest>(request).unwrap()
+        };
+        
+        let valid = request(serde_json::json!({"test_size": 0.3, "candidates": ["ridge"], "max_trials": 5}));
+        valid.validate().unwrap();
+        assert!(serde_json::to_value(&valid).unwrap().get("unknown_fields").is_none());
+        
+        let misspelt = request(serde_json::json!({"tets_size": 0.3}));
+        assert_eq!(misspelt.validate().unwrap_err().to_string(), "Unknown fields: tets_size");
+        for test_size in [0.0, 1.0, 1.5, -0.2] {
+            assert!(request(serde_json::json!({"test_size": test_size})).validate().is_err());
+        }
+        assert!(request(serde_json::json!({"max_trials": 0})).validate().is_err());
+    }
+    
+    #[test]
+    fn test_parse_results() {
+        let stdout = "Loading data\n\nAutoML Results:\n{\n  \"status\": \"success\",\n  \
+            \"task_type\": \"classification\",\n  \"metrics\": {\"accuracy\": 0.9},\n  \
+            \"model_path\": \"data/experiments/e/model.pkl\",\n  \
+            \"budget_exhausted\": \"max_trials\"\n}\n";
+        let output = parse_output(stdout).unwrap();
+        assert_eq!(output.status, "success");
+        assert_eq!(output.metrics, Some(serde_json::json!({"accuracy": 0.9})));
+        assert_eq!(output.model_path.as_deref(), Some("data/experiments/e/model.pkl"));
+        assert!(output.leaderboard.is_empty());
+        assert_eq!(output.budget_exhausted, Some(Budget::MaxTrials));
+        assert!(parse_output("Traceback (most recent call last):").is_none());
+        
+        let results: ResultsFile = serde_json::from_str(
+            r#"{"metrics": {"f1": [{"step": 0, "value": 0.5}, {"step": 1, "value": 0.7}], "mse": []},
+                "leaderboard": [
+                    {"model": "ridge", "params": {"alpha": 1.0}, "metrics": {"r2": 0.8}, "status": "completed"},
+                    {"model": "xgboost", "status": "failed", "error": "xgboost is not installed"}
+                ],
+                "model_path": "data/experiments/e/model.pkl"}"#,
+        )
+        .unwrap();
+        assert_eq!(latest_metrics(&results), serde_json::json!({"f1": 0.7}));
+        assert_eq!(results.leaderboard[0].params["alpha"], 1.0);
+        assert_eq!(results.leaderboard[1].error.as_deref(), Some("xgboost is not installed"));
+        assert_eq!(results.model_path.as_deref(), Some("data/experiments/e/model.pkl"));
+        assert_eq!(Budget::from(Limit::Runtime).as_str(), "max_runtime_seconds");
+    }
+}
@@ -0,0 +1,32 @@
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A file served over HTTP(S), in any format the dataset reader understands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpSource {
+    pub url: String,
+    /// Sent with the request, e.g. `Authorization`; values are treated as secrets
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+pub async fn fetch(source: &HttpSource) -> Result<DataFrame> {
+    let mut request = reqwest::Client::new().get(&source.url);
+    for (name, value) in &source.headers {
+        request = request.header(name, value);
+    }
+
+    let bytes = request
+        .send()
+        .await
+        .with_context(|| format!("Request to {} failed", source.url))?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    super::read_bytes(&bytes)
+        .await
+        .with_context(|| format!("Failed to read {}", source.url))
+}
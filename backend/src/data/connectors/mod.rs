@@ -0,0 +1,146 @@
+pub mod http;
+pub mod s3;
+pub mod sql;
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use super::reader::DatasetReader;
+use super::secrets::SecretBox;
+use crate::models::Connection;
+
+/// Shown in place of credentials when a connection is returned by the API.
+const REDACTED: &str = "***";
+
+/// Where a connection-backed dataset is read from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConnectorConfig {
+    Sql(sql::SqlSource),
+    S3(s3::S3Source),
+    Http(http::HttpSource),
+}
+
+impl ConnectorConfig {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConnectorConfig::Sql(_) => "sql",
+            ConnectorConfig::S3(_) => "s3",
+            ConnectorConfig::Http(_) => "http",
+        }
+    }
+
+    /// Reads the whole source into memory.
+    pub async fn fetch(&self) -> Result<DataFrame> {
+        match self {
+            ConnectorConfig::Sql(source) => sql::fetch(source).await,
+            ConnectorConfig::S3(source) => s3::fetch(source).await,
+            ConnectorConfig::Http(source) => http::fetch(source).await,
+        }
+    }
+
+    /// A copy safe to return to clients, with passwords, keys and header values hidden.
+    pub fn redacted(&self) -> Self {
+        match self.clone() {
+            ConnectorConfig::Sql(mut source) => {
+                source.connection_string = redact_url_password(&source.connection_string);
+                ConnectorConfig::Sql(source)
+            }
+            ConnectorConfig::S3(mut source) => {
+                source.secret_access_key = REDACTED.to_string();
+                ConnectorConfig::S3(source)
+            }
+            ConnectorConfig::Http(mut source) => {
+                for value in source.headers.values_mut() {
+                    *value = REDACTED.to_string();
+                }
+                ConnectorConfig::Http(source)
+            }
+        }
+    }
+
+    /// Encrypts the config for storage on the connection with the given id.
+    pub fn seal(&self, connection_id: &str) -> Result<String> {
+        let json = serde_json::to_vec(self)?;
+        SecretBox::global()?.seal(&json, connection_id)
+    }
+
+    pub fn open(connection: &Connection) -> Result<Self> {
+        let json = SecretBox::global()?.open(&connection.config, &connection.id)?;
+        serde_json::from_slice(&json).context("Failed to parse connection config")
+    }
+}
+
+/// Fetches the source and writes it as a Parquet snapshot under `data/datasets`,
+/// returning the snapshot path and its size.
+pub async fn snapshot(config: &ConnectorConfig) -> Result<(PathBuf, i64)> {
+    let mut df = config.fetch().await?;
+
+    let datasets_dir = Path::new("data/datasets");
+    tokio::fs::create_dir_all(datasets_dir).await?;
+    let file_path = datasets_dir.join(format!("{}.parquet", Uuid::new_v4()));
+
+    let file = std::fs::File::create(&file_path)?;
+    ParquetWriter::new(file).finish(&mut df)?;
+
+    let size = tokio::fs::metadata(&file_path).await?.len() as i64;
+    Ok((file_path, size))
+}
+
+/// Reads a downloaded object whose format is only known from its content.
+async fn read_bytes(bytes: &[u8]) -> Result<DataFrame> {
+    let file = tempfile::NamedTempFile::new()?;
+    tokio::fs::write(file.path(), bytes).await?;
+    DatasetReader::open(file.path())?.read(None).await
+}
+
+/// Hides the password in the URL's user info and the values of query
+/// parameters holding credentials, such as `password` or `sslpassword`.
+fn redact_url_password(url: &str) -> String {
+    static PASSWORD: OnceLock<Regex> = OnceLock::new();
+    static QUERY_SECRET: OnceLock<Regex> = OnceLock::new();
+    let password = PASSWORD.get_or_init(|| Regex::new(r"(://[^:/@]*:)[^@]*@").unwrap());
+    let query_secret = QUERY_SECRET.get_or_init(|| {
+        Regex::new(r"(?i)([?&][^=&#]*(?:pass|secret|token|key)[^=&#]*=)[^&#]*").unwrap()
+    });
+    let url = password.replace(url, format!("${{1}}{}@", REDACTED));
+    query_secret
+        .replace_all(&url, format!("${{1}}{}", REDACTED))
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted_config() {
+        let config: ConnectorConfig = serde_json::from_value(serde_json::json!({
+            "type": "sql",
+            "connection_string": "postgres://analyst:s3cret@db:5432/warehouse",
+            "query": "SELECT * FROM orders",
+        }))
+        .unwrap();
+
+        match config.redacted() {
+            ConnectorConfig::Sql(source) => assert_eq!(
+                source.connection_string,
+                "postgres://analyst:***@db:5432/warehouse"
+            ),
+            other => panic!("expected sql config, got {:?}", other),
+        }
+        assert_eq!(
+            redact_url_password("sqlite://data/app.db"),
+            "sqlite://data/app.db"
+        );
+        assert_eq!(
+            redact_url_password("postgres://db/w?user=a&password=x&sslmode=require&sslpassword=y"),
+            "postgres://db/w?user=a&password=***&sslmode=require&sslpassword=***"
+        );
+    }
+}
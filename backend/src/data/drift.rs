@@ -0,0 +1,582 @@
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::profiler::{self, DataProfile, ProfilerConfig};
+use super::stats::{self, Distribution, Histogram};
+
+/// Per-request drift thresholds; a column is flagged when any statistic crosses its threshold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DriftThresholds {
+    /// Population Stability Index above which a numeric column has drifted
+    pub psi: f64,
+    /// Two-sample Kolmogorov–Smirnov statistic above which a numeric column has drifted
+    pub ks_statistic: f64,
+    /// Jensen–Shannon divergence (base 2) above which a categorical column has drifted
+    pub jensen_shannon: f64,
+    /// Chi-squared p-value below which a categorical column has drifted
+    pub chi_squared_p_value: f64,
+    /// Absolute change in the fraction of nulls that counts as drift
+    pub null_rate_change: f64,
+    /// Share of current rows in categories never seen in the reference that counts as drift
+    pub new_category_rate: f64,
+    /// Share of compared columns that must drift for the dataset verdict to be `drift`
+    pub dataset_drift_share: f64,
+    /// Number of reference quantile bins used for PSI
+    pub psi_bins: usize,
+}
+
+impl Default for DriftThresholds {
+    fn default() -> Self {
+        Self {
+            psi: 0.2,
+            ks_statistic: 0.1,
+            jensen_shannon: 0.1,
+            chi_squared_p_value: 0.01,
+            null_rate_change: 0.05,
+            new_category_rate: 0.01,
+            dataset_drift_share: 0.3,
+            psi_bins: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftVerdict {
+    NoDrift,
+    Warning,
+    Drift,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftColumnKind {
+    Numeric,
+    Categorical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnDrift {
+    pub column: String,
+    pub kind: DriftColumnKind,
+    pub psi: Option<f64>,
+    pub ks_statistic: Option<f64>,
+    pub chi_squared: Option<f64>,
+    pub chi_squared_p_value: Option<f64>,
+    pub jensen_shannon: Option<f64>,
+    pub reference_null_rate: f64,
+    pub current_null_rate: f64,
+    pub new_categories: Vec<String>,
+    pub new_category_rate: Option<f64>,
+    pub drifted: bool,
+    pub reasons: Vec<String>,
+    /// Set when the reference side came from a stored profile, so statistics
+    /// were computed from binned or truncated summaries rather than raw rows.
+    pub approximate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftReport {
+    pub reference: String,
+    pub current: String,
+    pub reference_rows: usize,
+    pub current_rows: usize,
+    pub columns: Vec<ColumnDrift>,
+    /// Columns present in the reference but missing from the current data
+    pub missing_columns: Vec<String>,
+    /// Columns present in the current data but not in the reference
+    pub new_columns: Vec<String>,
+    pub drifted_columns: usize,
+    pub drift_share: f64,
+    pub verdict: DriftVerdict,
+    pub thresholds: DriftThresholds,
+}
+
+/// What the current data is compared against.
+pub enum DriftReference<'a> {
+    Frame { name: String, df: &'a DataFrame },
+    Profile(&'a DataProfile),
+}
+
+enum ColumnData {
+    Numeric {
+        values: Option<Vec<f64>>,
+        histogram: Option<Histogram>,
+    },
+    Categorical {
+        counts: Vec<(String, usize)>,
+        /// False when the counts only cover the most frequent values
+        complete: bool,
+    },
+}
+
+struct ColumnSummary {
+    name: String,
+    rows: usize,
+    null_count: usize,
+    data: ColumnData,
+}
+
+impl ColumnSummary {
+    fn null_rate(&self) -> f64 {
+        if self.rows == 0 {
+            0.0
+        } else {
+            self.null_count as f64 / self.rows as f64
+        }
+    }
+}
+
+pub fn compare(
+    reference: DriftReference<'_>,
+    current_name: &str,
+    current: &DataFrame,
+    profiler_config: &ProfilerConfig,
+    thresholds: &DriftThresholds,
+) -> Result<DriftReport> {
+    let (reference_name, reference_rows, reference_columns) = match reference {
+        DriftReference::Frame { name, df } => {
+            (name, df.height(), summarize_frame(df, profiler_config)?)
+        },
+        DriftReference::Profile(profile) => (
+            profile.file_path.clone(),
+            profile.num_rows,
+            summarize_profile(profile),
+        ),
+    };
+    let current_columns = summarize_frame(current, profiler_config)?;
+
+    let current_names: HashSet<&str> = current_columns.iter().map(|c| c.name.as_str()).collect();
+    let reference_names: HashSet<&str> = reference_columns.iter().map(|c| c.name.as_str()).collect();
+
+    let missing_columns = reference_columns
+        .iter()
+        .filter(|c| !current_names.contains(c.name.as_str()))
+        .map(|c| c.name.clone())
+        .collect();
+    let new_columns = current_columns
+        .iter()
+        .filter(|c| !reference_names.contains(c.name.as_str()))
+        .map(|c| c.name.clone())
+        .collect();
+
+    let mut columns = Vec::new();
+    for reference in &reference_columns {
+        if let Some(current) = current_columns.iter().find(|c| c.name == reference.name) {
+            if let Some(drift) = compare_column(reference, current, thresholds) {
+                columns.push(drift);
+            }
+        }
+    }
+
+    let drifted_columns = columns.iter().filter(|c| c.drifted).count();
+    let drift_share = if columns.is_empty() {
+        0.0
+    } else {
+        drifted_columns as f64 / columns.len() as f64
+    };
+    let verdict = if drifted_columns == 0 {
+        DriftVerdict::NoDrift
+    } else if drift_share >= thresholds.dataset_drift_share {
+        DriftVerdict::Drift
+    } else {
+        DriftVerdict::Warning
+    };
+
+    Ok(DriftReport {
+        reference: reference_name,
+        current: current_name.to_string(),
+        reference_rows,
+        current_rows: current.height(),
+        columns,
+        missing_columns,
+        new_columns,
+        drifted_columns,
+        drift_share,
+        verdict,
+        thresholds: thresholds.clone(),
+    })
+}
+
+fn summarize_frame(df: &DataFrame, config: &ProfilerConfig) -> Result<Vec<ColumnSummary>> {
+    let mut summaries = Vec::new();
+    for series in df.get_columns() {
+        let data = if series.dtype().is_numeric() {
+            let values = profiler::numeric_values(series)?
+                .into_iter()
+                .flatten()
+                .filter(|v| v.is_finite())
+                .collect();
+            ColumnData::Numeric {
+                values: Some(values),
+                histogram: None,
+            }
+        } else if profiler::is_categorical(series, series.n_unique()?, config) {
+            let values = profiler::categorical_values(series)?;
+            ColumnData::Categorical {
+                counts: stats::value_counts(values.iter().map(|v| v.as_deref()), usize::MAX),
+                complete: true,
+            }
+        } else {
+            continue;
+        };
+
+        summaries.push(ColumnSummary {
+            name: series.name().to_string(),
+            rows: series.len(),
+            null_count: series.null_count(),
+            data,
+        });
+    }
+    Ok(summaries)
+}
+
+fn summarize_profile(profile: &DataProfile) -> Vec<ColumnSummary> {
+    profile
+        .columns
+        .iter()
+        .filter_map(|column| {
+            let data = match column.distribution.as_ref()? {
+                Distribution::Histogram(histogram) => ColumnData::Numeric {
+                    values: None,
+                    histogram: Some(histogram.clone()),
+                },
+                Distribution::ValueCounts { values } => {
+                    let covered: usize = values.iter().map(|(_, c)| c).sum();
+                    ColumnData::Categorical {
+                        counts: values.clone(),
+                        complete: covered + column.null_count >= profile.num_rows,
+                    }
+                },
+            };
+            Some(ColumnSummary {
+                name: column.name.clone(),
+                rows: profile.num_rows,
+                null_count: column.null_count,
+                data,
+            })
+        })
+        .collect()
+}
+
+fn compare_column(
+    reference: &ColumnSummary,
+    current: &ColumnSummary,
+    thresholds: &DriftThresholds,
+) -> Option<ColumnDrift> {
+    let mut drift = ColumnDrift {
+        column: reference.name.clone(),
+        kind: DriftColumnKind::Numeric,
+        psi: None,
+        ks_statistic: None,
+        chi_squared: None,
+        chi_squared_p_value: None,
+        jensen_shannon: None,
+        reference_null_rate: reference.null_rate(),
+        current_null_rate: current.null_rate(),
+        new_categories: Vec::new(),
+        new_category_rate: None,
+        drifted: false,
+        reasons: Vec::new(),
+        approximate: false,
+    };
+
+    match (&reference.data, &current.data) {
+        (
+            ColumnData::Numeric { values: ref_values, histogram: ref_histogram },
+            ColumnData::Numeric { values: Some(cur_values), .. },
+        ) => {
+            let edges = match (ref_values, ref_histogram) {
+                (Some(values), _) => quantile_edges(values, thresholds.psi_bins),
+                (None, Some(histogram)) => histogram.bin_edges.clone(),
+                (None, None) => return None,
+            };
+            let ref_counts = match (ref_values, ref_histogram) {
+                (Some(values), _) => bin_counts(values, &edges),
+                (None, Some(histogram)) => histogram.counts.clone(),
+                (None, None) => return None,
+            };
+            let cur_counts = bin_counts(cur_values, &edges);
+
+            drift.psi = psi(&ref_counts, &cur_counts);
+            drift.ks_statistic = match ref_values {
+                Some(values) => ks_statistic(values, cur_values),
+                None => {
+                    drift.approximate = true;
+                    binned_ks_statistic(&ref_counts, &cur_counts)
+                },
+            };
+
+            if let Some(psi) = drift.psi.filter(|v| *v > thresholds.psi) {
+                drift.reasons.push(format!("PSI {:.3} exceeds {:.3}", psi, thresholds.psi));
+            }
+            if let Some(ks) = drift.ks_statistic.filter(|v| *v > thresholds.ks_statistic) {
+                drift.reasons.push(format!(
+                    "KS statistic {:.3} exceeds {:.3}",
+                    ks, thresholds.ks_statistic
+                ));
+            }
+        },
+        (
+            ColumnData::Categorical { counts: ref_counts, complete },
+            ColumnData::Categorical { counts: cur_counts, .. },
+        ) => {
+            drift.kind = DriftColumnKind::Categorical;
+            drift.approximate = !complete;
+
+            let ref_total: usize = ref_counts.iter().map(|(_, c)| c).sum();
+            let cur_total: usize = cur_counts.iter().map(|(_, c)| c).sum();
+            if ref_total == 0 || cur_total == 0 {
+                return Some(drift);
+            }
+
+            let reference: HashMap<&str, usize> =
+                ref_counts.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+            drift.new_categories = cur_counts
+                .iter()
+                .filter(|(k, _)| !reference.contains_key(k.as_str()))
+                .map(|(k, _)| k.clone())
+                .collect();
+            let new_rows: usize = cur_counts
+                .iter()
+                .filter(|(k, _)| !reference.contains_key(k.as_str()))
+                .map(|(_, c)| c)
+                .sum();
+            let new_category_rate = new_rows as f64 / cur_total as f64;
+            drift.new_category_rate = Some(new_category_rate);
+
+            // Align both sides on the union of categories
+            let mut categories: Vec<&str> = reference.keys().copied().collect();
+            categories.extend(drift.new_categories.iter().map(String::as_str));
+            let current: HashMap<&str, usize> =
+                cur_counts.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+            let p: Vec<f64> = categories
+                .iter()
+                .map(|k| *reference.get(k).unwrap_or(&0) as f64 / ref_total as f64)
+                .collect();
+            let q: Vec<f64> = categories
+                .iter()
+                .map(|k| *current.get(k).unwrap_or(&0) as f64 / cur_total as f64)
+                .collect();
+
+            drift.jensen_shannon = Some(jensen_shannon(&p, &q));
+            let observed: Vec<f64> = categories
+                .iter()
+                .map(|k| *current.get(k).unwrap_or(&0) as f64)
+                .collect();
+            let (chi2, dof) = chi_squared(&p, &observed);
+            drift.chi_squared = Some(chi2);
+            drift.chi_squared_p_value = Some(stats::chi_squared_sf(chi2, dof));
+
+            if let Some(js) = drift.jensen_shannon.filter(|v| *v > thresholds.jensen_shannon) {
+                drift.reasons.push(format!(
+                    "Jensen-Shannon divergence {:.3} exceeds {:.3}",
+                    js, thresholds.jensen_shannon
+                ));
+            }
+            if let Some(p) = drift
+                .chi_squared_p_value
+                .filter(|v| *v < thresholds.chi_squared_p_value)
+            {
+                drift.reasons.push(format!(
+                    "Chi-squared p-value {:.4} below {:.4}",
+                    p, thresholds.chi_squared_p_value
+                ));
+            }
+            if new_category_rate > thresholds.new_category_rate {
+                drift.reasons.push(format!(
+                    "{:.1}% of rows fall in {} categories unseen in the reference",
+                    new_category_rate * 100.0,
+                    drift.new_categories.len()
+                ));
+            }
+        },
+        _ => return None,
+    }
+
+    let null_change = drift.current_null_rate - drift.reference_null_rate;
+    if null_change.abs() > thresholds.null_rate_change {
+        drift.reasons.push(format!(
+            "Null rate changed from {:.1}% to {:.1}%",
+            drift.reference_null_rate * 100.0,
+            drift.current_null_rate * 100.0
+        ));
+    }
+
+    drift.drifted = !drift.reasons.is_empty();
+    Some(drift)
+}
+
+/// Interior bin edges at reference quantiles, bracketed by -inf/+inf so every
+/// current value lands in a bin.
+fn quantile_edges(values: &[f64], bins: usize) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let mut edges = vec![f64::NEG_INFINITY];
+    if !sorted.is_empty() {
+        for i in 1..bins.max(1) {
+            let idx = (i * sorted.len()) / bins;
+            let edge = sorted[idx.min(sorted.len() - 1)];
+            if edge > *edges.last().unwrap() {
+                edges.push(edge);
+            }
+        }
+    }
+    edges.push(f64::INFINITY);
+    edges
+}
+
+/// Counts values per bin; values outside the edges go to the first or last bin.
+fn bin_counts(values: &[f64], edges: &[f64]) -> Vec<usize> {
+    let bins = edges.len().saturating_sub(1).max(1);
+    let mut counts = vec![0; bins];
+    for &v in values {
+        // Bins are closed on the left: [edge_i, edge_{i+1})
+        let idx = edges.partition_point(|&e| e <= v).saturating_sub(1);
+        counts[idx.min(bins - 1)] += 1;
+    }
+    counts
+}
+
+fn proportions(counts: &[usize]) -> Vec<f64> {
+    let total: usize = counts.iter().sum();
+    counts
+        .iter()
+        .map(|&c| if total == 0 { 0.0 } else { c as f64 / total as f64 })
+        .collect()
+}
+
+fn psi(reference: &[usize], current: &[usize]) -> Option<f64> {
+    const EPSILON: f64 = 1e-4;
+    if reference.iter().sum::<usize>() == 0 || current.iter().sum::<usize>() == 0 {
+        return None;
+    }
+
+    let p = proportions(reference);
+    let q = proportions(current);
+    Some(
+        p.iter()
+            .zip(&q)
+            .map(|(&r, &c)| {
+                let (r, c) = (r.max(EPSILON), c.max(EPSILON));
+                (c - r) * (c / r).ln()
+            })
+            .sum(),
+    )
+}
+
+fn ks_statistic(reference: &[f64], current: &[f64]) -> Option<f64> {
+    if reference.is_empty() || current.is_empty() {
+        return None;
+    }
+
+    let mut a = reference.to_vec();
+    let mut b = current.to_vec();
+    a.sort_by(f64::total_cmp);
+    b.sort_by(f64::total_cmp);
+
+    let (mut i, mut j, mut d) = (0, 0, 0.0f64);
+    while i < a.len() && j < b.len() {
+        let x = a[i].min(b[j]);
+        while i < a.len() && a[i] <= x {
+            i += 1;
+        }
+        while j < b.len() && b[j] <= x {
+            j += 1;
+        }
+        d = d.max((i as f64 / a.len() as f64 - j as f64 / b.len() as f64).abs());
+    }
+    Some(d)
+}
+
+/// KS statistic evaluated only at shared bin edges.
+fn binned_ks_statistic(reference: &[usize], current: &[usize]) -> Option<f64> {
+    if reference.iter().sum::<usize>() == 0 || current.iter().sum::<usize>() == 0 {
+        return None;
+    }
+
+    let (mut cdf_r, mut cdf_c, mut d) = (0.0, 0.0, 0.0f64);
+    for (r, c) in proportions(reference).iter().zip(proportions(current)) {
+        cdf_r += r;
+        cdf_c += c;
+        d = d.max((cdf_r - cdf_c).abs());
+    }
+    Some(d)
+}
+
+fn jensen_shannon(p: &[f64], q: &[f64]) -> f64 {
+    let kl = |a: &[f64], m: &[f64]| -> f64 {
+        a.iter()
+            .zip(m)
+            .filter(|(&x, &y)| x > 0.0 && y > 0.0)
+            .map(|(&x, &y)| x * (x / y).log2())
+            .sum()
+    };
+    let m: Vec<f64> = p.iter().zip(q).map(|(a, b)| (a + b) / 2.0).collect();
+    (0.5 * kl(p, &m) + 0.5 * kl(q, &m)).clamp(0.0, 1.0)
+}
+
+/// Goodness of fit of `observed` counts against reference proportions `expected`.
+fn chi_squared(expected: &[f64], observed: &[f64]) -> (f64, f64) {
+    const EPSILON: f64 = 1e-4;
+    let total: f64 = observed.iter().sum();
+    let chi2 = expected
+        .iter()
+        .zip(observed)
+        .map(|(&p, &o)| {
+            let e = p.max(EPSILON) * total;
+            (o - e).powi(2) / e
+        })
+        .sum();
+    (chi2, (expected.len() as f64 - 1.0).max(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drift_between_frames() {
+        let reference = df! [
+            "amount" => (0..500).map(|i| i as f64).collect::<Vec<_>>(),
+            "country" => (0..500).map(|i| if i % 2 == 0 { "FR" } else { "DE" }).collect::<Vec<_>>(),
+        ].unwrap();
+        let shifted = df! [
+            "amount" => (0..500).map(|i| i as f64 + 250.0).collect::<Vec<_>>(),
+            "country" => (0..500).map(|i| if i % 5 == 0 { "US" } else { "FR" }).collect::<Vec<_>>(),
+        ].unwrap();
+
+        let config = ProfilerConfig::default();
+        let thresholds = DriftThresholds::default();
+
+        let same = compare(
+            DriftReference::Frame { name: "train".to_string(), df: &reference },
+            "train",
+            &reference,
+            &config,
+            &thresholds,
+        ).unwrap();
+        assert_eq!(same.verdict, DriftVerdict::NoDrift);
+
+        let report = compare(
+            DriftReference::Frame { name: "train".to_string(), df: &reference },
+            "batch",
+            &shifted,
+            &config,
+            &thresholds,
+        ).unwrap();
+        assert_eq!(report.verdict, DriftVerdict::Drift);
+
+        let amount = report.columns.iter().find(|c| c.column == "amount").unwrap();
+        assert!(amount.psi.unwrap() > thresholds.psi);
+        assert!((amount.ks_statistic.unwrap() - 0.5).abs() < 1e-9);
+
+        let country = report.columns.iter().find(|c| c.column == "country").unwrap();
+        assert_eq!(country.new_categories, vec!["US".to_string()]);
+        assert!(country.jensen_shannon.unwrap() > thresholds.jensen_shannon);
+    }
+}
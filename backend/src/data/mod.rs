@@ -1,3 +1,16 @@
+pub mod connectors;
+pub mod drift;
+pub mod outliers;
+pub mod pii;
+pub mod profile_store;
 pub mod profiler;
+pub mod reader;
+pub mod secrets;
+pub mod stats;
+pub mod temporal;
+pub mod text;
 
-pub use profiler::DataProfiler;
+pub use connectors::ConnectorConfig;
+pub use drift::{DriftReport, DriftThresholds};
+pub use profiler::{DataProfiler, ProfileMode, ProfilerConfig};
+pub use reader::DatasetReader;
@@ -0,0 +1,495 @@
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+use super::profiler::{self, ProfilerConfig};
+use super::stats;
+
+/// Outlying row indices reported per rule; exports use the full set.
+const MAX_SAMPLE_ROWS: usize = 10;
+
+/// Numeric columns considered for the multivariate score, in column order.
+const MAX_MULTIVARIATE_COLUMNS: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutlierThresholds {
+    /// Values beyond `q1 - k * IQR` or `q3 + k * IQR` are outliers
+    pub iqr_multiplier: f64,
+    /// Values more than this many standard deviations from the mean are outliers
+    pub z_score: f64,
+    /// Categories holding less than this share of non-null values are rare
+    pub rare_category_ratio: f64,
+    /// Rows whose squared Mahalanobis distance has a chi-squared p-value below this are outliers
+    pub mahalanobis_p_value: f64,
+}
+
+impl Default for OutlierThresholds {
+    fn default() -> Self {
+        Self {
+            iqr_multiplier: 1.5,
+            z_score: 3.0,
+            rare_category_ratio: 0.01,
+            mahalanobis_p_value: 0.001,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum OutlierMethod {
+    Iqr,
+    ZScore,
+    RareCategory,
+    Mahalanobis,
+}
+
+/// The rule a set of outliers was selected with, including the resolved bounds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum OutlierRule {
+    Iqr {
+        lower_fence: f64,
+        upper_fence: f64,
+    },
+    ZScore {
+        mean: f64,
+        std_dev: f64,
+        threshold: f64,
+    },
+    RareCategory {
+        max_ratio: f64,
+        rare_values: Vec<(String, usize)>,
+    },
+    Mahalanobis {
+        /// Squared distance above which a row is an outlier
+        max_distance: f64,
+        p_value: f64,
+    },
+}
+
+impl OutlierRule {
+    pub fn method(&self) -> OutlierMethod {
+        match self {
+            OutlierRule::Iqr { .. } => OutlierMethod::Iqr,
+            OutlierRule::ZScore { .. } => OutlierMethod::ZScore,
+            OutlierRule::RareCategory { .. } => OutlierMethod::RareCategory,
+            OutlierRule::Mahalanobis { .. } => OutlierMethod::Mahalanobis,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlierSet {
+    /// A single column, or every column used by a multivariate rule
+    pub columns: Vec<String>,
+    #[serde(flatten)]
+    pub rule: OutlierRule,
+    pub count: usize,
+    /// Share of the rows the rule could be evaluated on
+    pub ratio: f64,
+    /// First outlying rows as 0-based indices into the dataset
+    pub sample_rows: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlierReport {
+    pub columns: Vec<OutlierSet>,
+    pub multivariate: Option<OutlierSet>,
+    pub thresholds: OutlierThresholds,
+}
+
+impl OutlierReport {
+    /// Maps row indices of a sampled frame back to rows of the full dataset.
+    pub fn remap_rows(&mut self, indices: &[IdxSize]) {
+        for set in self.columns.iter_mut().chain(self.multivariate.as_mut()) {
+            for row in &mut set.sample_rows {
+                *row = indices[*row] as usize;
+            }
+        }
+    }
+}
+
+struct Detected {
+    set: OutlierSet,
+    rows: Vec<usize>,
+}
+
+impl Detected {
+    fn new(columns: Vec<String>, rule: OutlierRule, rows: Vec<usize>, evaluated: usize) -> Self {
+        let set = OutlierSet {
+            columns,
+            rule,
+            count: rows.len(),
+            ratio: if evaluated == 0 {
+                0.0
+            } else {
+                rows.len() as f64 / evaluated as f64
+            },
+            sample_rows: rows.iter().take(MAX_SAMPLE_ROWS).copied().collect(),
+        };
+        Detected { set, rows }
+    }
+}
+
+/// Per-column and multivariate outlier analysis of a frame.
+pub fn detect(df: &DataFrame, config: &ProfilerConfig) -> Result<OutlierReport> {
+    let thresholds = &config.outlier_thresholds;
+    let mut columns = Vec::new();
+    for series in df.get_columns() {
+        columns.extend(column_outliers(series, config)?.into_iter().map(|d| d.set));
+    }
+
+    Ok(OutlierReport {
+        columns,
+        multivariate: mahalanobis_outliers(df, thresholds)?.map(|d| d.set),
+        thresholds: thresholds.clone(),
+    })
+}
+
+/// Every row flagged by any of `methods` (all methods when empty), sorted.
+pub fn outlier_rows(
+    df: &DataFrame,
+    config: &ProfilerConfig,
+    methods: &[OutlierMethod],
+) -> Result<Vec<usize>> {
+    let wanted = |method: OutlierMethod| methods.is_empty() || methods.contains(&method);
+
+    let mut detected = Vec::new();
+    for series in df.get_columns() {
+        detected.extend(column_outliers(series, config)?);
+    }
+    if wanted(OutlierMethod::Mahalanobis) {
+        detected.extend(mahalanobis_outliers(df, &config.outlier_thresholds)?);
+    }
+
+    let rows: BTreeSet<usize> = detected
+        .into_iter()
+        .filter(|d| wanted(d.set.rule.method()))
+        .flat_map(|d| d.rows)
+        .collect();
+    Ok(rows.into_iter().collect())
+}
+
+fn column_outliers(series: &Series, config: &ProfilerConfig) -> Result<Vec<Detected>> {
+    let thresholds = &config.outlier_thresholds;
+    let name = series.name().to_string();
+
+    if series.dtype().is_numeric() {
+        let values: Vec<(usize, f64)> = profiler::numeric_values(series)?
+            .into_iter()
+            .enumerate()
+            .filter_map(|(row, v)| v.filter(|v| v.is_finite()).map(|v| (row, v)))
+            .collect();
+        if values.len() < 4 {
+            return Ok(Vec::new());
+        }
+
+        let mut detected = Vec::new();
+        let mut sorted: Vec<f64> = values.iter().map(|(_, v)| *v).collect();
+        sorted.sort_by(f64::total_cmp);
+
+        let (q1, q3) = (quantile(&sorted, 0.25), quantile(&sorted, 0.75));
+        let iqr = q3 - q1;
+        if iqr > 0.0 {
+            let lower_fence = q1 - thresholds.iqr_multiplier * iqr;
+            let upper_fence = q3 + thresholds.iqr_multiplier * iqr;
+            let rows = values
+                .iter()
+                .filter(|(_, v)| *v < lower_fence || *v > upper_fence)
+                .map(|(row, _)| *row)
+                .collect();
+            detected.push(Detected::new(
+                vec![name.clone()],
+                OutlierRule::Iqr { lower_fence, upper_fence },
+                rows,
+                values.len(),
+            ));
+        }
+
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let std_dev = (sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+        if std_dev > 0.0 {
+            let rows = values
+                .iter()
+                .filter(|(_, v)| ((v - mean) / std_dev).abs() > thresholds.z_score)
+                .map(|(row, _)| *row)
+                .collect();
+            detected.push(Detected::new(
+                vec![name],
+                OutlierRule::ZScore {
+                    mean,
+                    std_dev,
+                    threshold: thresholds.z_score,
+                },
+                rows,
+                values.len(),
+            ));
+        }
+
+        return Ok(detected);
+    }
+
+    let unique_count = series.n_unique()?;
+    if series.dtype() == &DataType::Boolean
+        || !profiler::is_categorical(series, unique_count, config)
+    {
+        return Ok(Vec::new());
+    }
+
+    let values = profiler::categorical_values(series)?;
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for v in values.iter().flatten() {
+        *counts.entry(v.as_str()).or_insert(0) += 1;
+    }
+    let total: usize = counts.values().sum();
+    if total == 0 {
+        return Ok(Vec::new());
+    }
+
+    let is_rare = |count: usize| (count as f64 / total as f64) < thresholds.rare_category_ratio;
+    let mut rare_values: Vec<(String, usize)> = counts
+        .iter()
+        .filter(|(_, &count)| is_rare(count))
+        .map(|(value, &count)| (value.to_string(), count))
+        .collect();
+    rare_values.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+
+    let rows = values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.as_deref().is_some_and(|v| is_rare(counts[v])))
+        .map(|(row, _)| row)
+        .collect();
+
+    Ok(vec![Detected::new(
+        vec![name],
+        OutlierRule::RareCategory {
+            max_ratio: thresholds.rare_category_ratio,
+            rare_values,
+        },
+        rows,
+        total,
+    )])
+}
+
+/// Squared Mahalanobis distance of each complete row over the numeric columns,
+/// flagged against the chi-squared distribution with one degree of freedom per column.
+fn mahalanobis_outliers(df: &DataFrame, thresholds: &OutlierThresholds) -> Result<Option<Detected>> {
+    let mut names = Vec::new();
+    let mut columns = Vec::new();
+    for series in df.get_columns() {
+        if !series.dtype().is_numeric() || columns.len() == MAX_MULTIVARIATE_COLUMNS {
+            continue;
+        }
+        names.push(series.name().to_string());
+        columns.push(profiler::numeric_values(series)?);
+    }
+
+    // Rows with a missing value in any column are skipped
+    let rows: Vec<usize> = (0..df.height())
+        .filter(|&row| {
+            columns
+                .iter()
+                .all(|c| c[row].is_some_and(|v| v.is_finite()))
+        })
+        .collect();
+
+    // Standardize, dropping constant columns which carry no information
+    let mut kept_names = Vec::new();
+    let mut standardized: Vec<Vec<f64>> = Vec::new();
+    for (name, column) in names.into_iter().zip(&columns) {
+        let values: Vec<f64> = rows.iter().map(|&row| column[row].unwrap_or_default()).collect();
+        let n = values.len() as f64;
+        if n < 2.0 {
+            continue;
+        }
+        let mean = values.iter().sum::<f64>() / n;
+        let std_dev = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+        if std_dev > 0.0 {
+            kept_names.push(name);
+            standardized.push(values.iter().map(|v| (v - mean) / std_dev).collect());
+        }
+    }
+
+    let dims = standardized.len();
+    if dims < 2 || rows.len() <= dims + 1 {
+        return Ok(None);
+    }
+
+    let n = rows.len() as f64;
+    let mut covariance = vec![vec![0.0; dims]; dims];
+    for i in 0..dims {
+        for j in i..dims {
+            let c = standardized[i]
+                .iter()
+                .zip(&standardized[j])
+                .map(|(a, b)| a * b)
+                .sum::<f64>()
+                / (n - 1.0);
+            covariance[i][j] = c;
+            covariance[j][i] = c;
+        }
+    }
+
+    // Perfectly collinear columns make the matrix singular; a small ridge keeps it invertible
+    let inverse = match invert(&covariance) {
+        Some(inverse) => inverse,
+        None => {
+            for (i, row) in covariance.iter_mut().enumerate() {
+                row[i] += 1e-6;
+            }
+            match invert(&covariance) {
+                Some(inverse) => inverse,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    let max_distance = chi_squared_critical_value(thresholds.mahalanobis_p_value, dims as f64);
+    let outliers = (0..rows.len())
+        .filter(|&k| {
+            let x: Vec<f64> = standardized.iter().map(|c| c[k]).collect();
+            let distance: f64 = (0..dims)
+                .map(|i| x[i] * (0..dims).map(|j| inverse[i][j] * x[j]).sum::<f64>())
+                .sum();
+            distance > max_distance
+        })
+        .map(|k| rows[k])
+        .collect();
+
+    Ok(Some(Detected::new(
+        kept_names,
+        OutlierRule::Mahalanobis {
+            max_distance,
+            p_value: thresholds.mahalanobis_p_value,
+        },
+        outliers,
+        rows.len(),
+    )))
+}
+
+/// Linear interpolation between closest ranks of sorted values.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// Gauss–Jordan elimination with partial pivoting.
+fn invert(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix.to_vec();
+    let mut inverse: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let scale = a[col][col];
+        for j in 0..n {
+            a[col][j] /= scale;
+            inverse[col][j] /= scale;
+        }
+
+        for row in 0..n {
+            if row == col {
+                continue;
+            }
+            let factor = a[row][col];
+            if factor == 0.0 {
+                continue;
+            }
+            for j in 0..n {
+                a[row][j] -= factor * a[col][j];
+                inverse[row][j] -= factor * inverse[col][j];
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+/// Statistic whose chi-squared survival function equals `p_value`, found by bisection.
+fn chi_squared_critical_value(p_value: f64, dof: f64) -> f64 {
+    let mut high = dof.max(1.0);
+    while stats::chi_squared_sf(high, dof) > p_value && high < 1e6 {
+        high *= 2.0;
+    }
+
+    let mut low = 0.0;
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if stats::chi_squared_sf(mid, dof) > p_value {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    high
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outliers() {
+        let mut x: Vec<f64> = (0..50).map(|i| (i % 10) as f64).collect();
+        let mut y: Vec<f64> = (0..50).map(|i| (i % 10) as f64 * 2.0 + (i % 3) as f64).collect();
+        let mut city: Vec<&str> = (0..50).map(|i| if i % 2 == 0 { "Paris" } else { "Lyon" }).collect();
+        // Row 50 is extreme in both columns, row 51 only breaks the x/y relationship
+        x.extend([100.0, 9.0]);
+        y.extend([200.0, 0.0]);
+        city.extend(["Paris", "Nice"]);
+
+        let df = df! [
+            "x" => &x,
+            "y" => &y,
+            "city" => &city,
+        ].unwrap();
+
+        let config = ProfilerConfig {
+            outlier_thresholds: OutlierThresholds {
+                rare_category_ratio: 0.05,
+                ..OutlierThresholds::default()
+            },
+            ..ProfilerConfig::default()
+        };
+        let report = detect(&df, &config).unwrap();
+
+        let find = |column: &str, method: OutlierMethod| {
+            report
+                .columns
+                .iter()
+                .find(|s| s.columns[0] == column && s.rule.method() == method)
+                .unwrap()
+        };
+        assert_eq!(find("x", OutlierMethod::Iqr).sample_rows, vec![50]);
+        assert_eq!(find("x", OutlierMethod::ZScore).sample_rows, vec![50]);
+        assert_eq!(find("y", OutlierMethod::Iqr).sample_rows, vec![50]);
+        match &find("city", OutlierMethod::RareCategory).rule {
+            OutlierRule::RareCategory { rare_values, .. } => {
+                assert_eq!(rare_values, &vec![("Nice".to_string(), 1)]);
+            }
+            other => panic!("expected rare categories, got {:?}", other),
+        }
+
+        let multivariate = report.multivariate.unwrap();
+        assert_eq!(multivariate.sample_rows, vec![50, 51]);
+
+        let rows = outlier_rows(&df, &config, &[OutlierMethod::RareCategory]).unwrap();
+        assert_eq!(rows, vec![51]);
+        assert_eq!(quantile(&[1.0, 2.0, 3.0, 4.0], 0.5), 2.5);
+        assert!((chi_squared_critical_value(0.05, 1.0) - 3.841).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,373 @@
+use anyhow::Result;
+use polars::prelude::*;
+use regex::Regex;
+use ring::digest;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+/// Non-null values inspected per column, spread across it; detection is a
+/// ratio so a sample is enough.
+const MAX_SAMPLED_VALUES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    PhoneNumber,
+    CreditCard,
+    IpAddress,
+    Iban,
+    PersonName,
+}
+
+impl PiiKind {
+    pub fn description(&self) -> &'static str {
+        match self {
+            PiiKind::Email => "email addresses",
+            PiiKind::PhoneNumber => "phone numbers",
+            PiiKind::CreditCard => "credit card numbers",
+            PiiKind::IpAddress => "IP addresses",
+            PiiKind::Iban => "IBANs",
+            PiiKind::PersonName => "person names",
+        }
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            PiiKind::Email => email_regex().is_match(value),
+            PiiKind::PhoneNumber => is_phone_number(value),
+            PiiKind::CreditCard => is_credit_card(value),
+            PiiKind::IpAddress => value.parse::<IpAddr>().is_ok(),
+            PiiKind::Iban => is_iban(value),
+            PiiKind::PersonName => is_person_name(value),
+        }
+    }
+
+    // Checked in order; the first kind over the threshold wins so that e.g. a
+    // card number is not also reported as a phone number.
+    const ALL: [PiiKind; 6] = [
+        PiiKind::Email,
+        PiiKind::CreditCard,
+        PiiKind::Iban,
+        PiiKind::IpAddress,
+        PiiKind::PhoneNumber,
+        PiiKind::PersonName,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiFinding {
+    pub column: String,
+    pub kind: PiiKind,
+    /// Fraction of sampled non-null values that matched
+    pub match_ratio: f64,
+    pub sampled_values: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiAction {
+    /// Replace values with a salted SHA-256 digest, keeping joins possible
+    Hash,
+    /// Keep only a few trailing characters, e.g. `************4242`
+    Mask,
+    Drop,
+}
+
+/// Flags text columns whose sampled values mostly look like personal data.
+/// Numeric columns are skipped: IDs and timestamps have as many digits as
+/// phone numbers.
+pub fn detect(df: &DataFrame, min_match_ratio: f64) -> Result<Vec<PiiFinding>> {
+    let mut findings = Vec::new();
+
+    for series in df.get_columns() {
+        if !matches!(series.dtype(), DataType::Utf8) {
+            continue;
+        }
+
+        let values: Vec<&str> = series
+            .utf8()?
+            .into_iter()
+            .flatten()
+            .filter(|v| !v.trim().is_empty())
+            .collect();
+        let values = spread_sample(values, MAX_SAMPLED_VALUES);
+        if values.is_empty() {
+            continue;
+        }
+
+        let name_hint = has_name_hint(series.name());
+        for kind in PiiKind::ALL {
+            let matched = values.iter().filter(|v| kind.matches(v)).count();
+            let match_ratio = matched as f64 / values.len() as f64;
+
+            // Names are too ambiguous to flag on content alone
+            let threshold = if kind == PiiKind::PersonName && !name_hint {
+                min_match_ratio.max(0.8)
+            } else {
+                min_match_ratio
+            };
+
+            if match_ratio >= threshold {
+                findings.push(PiiFinding {
+                    column: series.name().to_string(),
+                    kind,
+                    match_ratio,
+                    sampled_values: values.len(),
+                });
+                break;
+            }
+        }
+    }
+
+    Ok(findings)
+}
+
+/// At most `max` of `values`, evenly spaced so that sorted or grouped columns
+/// are sampled throughout.
+fn spread_sample<T: Copy>(values: Vec<T>, max: usize) -> Vec<T> {
+    if values.len() <= max {
+        return values;
+    }
+    (0..max).map(|i| values[i * values.len() / max]).collect()
+}
+
+/// Applies an action per column, returning the cleaned frame.
+pub fn redact(
+    df: &DataFrame,
+    actions: &HashMap<String, PiiAction>,
+    kinds: &HashMap<String, PiiKind>,
+    salt: &str,
+) -> Result<DataFrame> {
+    let mut df = df.clone();
+
+    for (column, action) in actions {
+        if *action == PiiAction::Drop {
+            df = df.drop(column)?;
+            continue;
+        }
+
+        let series = df.column(column)?.cast(&DataType::Utf8)?;
+        let kind = kinds.get(column).copied();
+        let redacted: Utf8Chunked = series
+            .utf8()?
+            .into_iter()
+            .map(|v| {
+                v.map(|v| match action {
+                    PiiAction::Hash => hash_value(v, salt),
+                    PiiAction::Mask => mask_value(v, kind),
+                    PiiAction::Drop => unreachable!(),
+                })
+            })
+            .collect();
+        df.replace(column, redacted.into_series().with_name(column))?;
+    }
+
+    Ok(df)
+}
+
+fn hash_value(value: &str, salt: &str) -> String {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    ctx.update(salt.as_bytes());
+    ctx.update(value.as_bytes());
+    ctx.finish()
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn mask_value(value: &str, kind: Option<PiiKind>) -> String {
+    match kind {
+        Some(PiiKind::Email) => match value.split_once('@') {
+            Some((local, domain)) => {
+                let first: String = local.chars().take(1).collect();
+                format!("{}***@{}", first, domain)
+            }
+            None => "*".repeat(value.chars().count()),
+        },
+        Some(PiiKind::PersonName) => value
+            .split_whitespace()
+            .map(|w| format!("{}.", w.chars().next().unwrap_or('*')))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => {
+            // Keep the last four alphanumerics, as printed on receipts
+            let total = value.chars().filter(|c| c.is_alphanumeric()).count();
+            let mut seen = 0;
+            value
+                .chars()
+                .map(|c| {
+                    if !c.is_alphanumeric() {
+                        return c;
+                    }
+                    seen += 1;
+                    if seen + 4 > total {
+                        c
+                    } else {
+                        '*'
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+fn email_regex() -> &'static Regex {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    EMAIL.get_or_init(|| {
+        Regex::new(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}$").unwrap()
+    })
+}
+
+fn phone_regex() -> &'static Regex {
+    static PHONE: OnceLock<Regex> = OnceLock::new();
+    PHONE.get_or_init(|| Regex::new(r"^\+?[0-9(][0-9 ().-]{5,22}[0-9]$").unwrap())
+}
+
+fn is_phone_number(value: &str) -> bool {
+    let digits = value.chars().filter(char::is_ascii_digit).count();
+    phone_regex().is_match(value) && (7..=15).contains(&digits)
+}
+
+fn is_credit_card(value: &str) -> bool {
+    if !value.chars().all(|c| c.is_ascii_digit() || c == ' ' || c == '-') {
+        return false;
+    }
+    let digits: Vec<u32> = value.chars().filter_map(|c| c.to_digit(10)).collect();
+    // Card numbers start with 2-6, by card network; a run of one digit, e.g.
+    // zero padding, passes the Luhn check but is no card
+    (13..=19).contains(&digits.len())
+        && (2..=6).contains(&digits[0])
+        && digits.iter().any(|&d| d != digits[0])
+        && luhn_valid(&digits)
+}
+
+fn luhn_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+fn is_iban(value: &str) -> bool {
+    let compact: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = compact.as_bytes();
+    if !(15..=34).contains(&bytes.len())
+        || !bytes[..2].iter().all(u8::is_ascii_uppercase)
+        || !bytes[2..4].iter().all(u8::is_ascii_digit)
+        || !bytes.iter().all(u8::is_ascii_alphanumeric)
+    {
+        return false;
+    }
+
+    // ISO 13616: move the first four characters to the end, map letters to 10..35, mod 97 == 1
+    let rearranged = compact[4..].chars().chain(compact[..4].chars());
+    let mut remainder: u32 = 0;
+    for c in rearranged {
+        let n = match c.to_digit(36) {
+            Some(n) => n,
+            None => return false,
+        };
+        remainder = if n >= 10 {
+            (remainder * 100 + n) % 97
+        } else {
+            (remainder * 10 + n) % 97
+        };
+    }
+    remainder == 1
+}
+
+const COMMON_FIRST_NAMES: &[&str] = &[
+    "james", "john", "robert", "michael", "william", "david", "richard", "joseph", "thomas",
+    "charles", "mary", "patricia", "jennifer", "linda", "elizabeth", "barbara", "susan",
+    "jessica", "sarah", "karen", "anna", "maria", "peter", "paul", "daniel", "laura", "emma",
+    "olivia", "sophie", "lucas", "louis", "marie", "jean", "pierre", "hans", "anne", "ali",
+    "mohammed", "wei", "li", "yuki", "raj", "priya", "carlos", "juan", "ana", "luis", "jose",
+];
+
+fn is_person_name(value: &str) -> bool {
+    let words: Vec<&str> = value.split_whitespace().collect();
+    if !(2..=4).contains(&words.len()) {
+        return false;
+    }
+
+    let capitalized = words.iter().all(|w| {
+        let mut chars = w.chars();
+        chars.next().is_some_and(char::is_uppercase)
+            && chars.all(|c| c.is_alphabetic() || c == '-' || c == '\'' || c == '.')
+    });
+    capitalized && COMMON_FIRST_NAMES.contains(&words[0].to_lowercase().as_str())
+}
+
+fn has_name_hint(column: &str) -> bool {
+    let column = column.to_lowercase();
+    ["name", "customer", "contact", "owner", "person"]
+        .iter()
+        .any(|hint| column.contains(hint))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_and_redact() {
+        let df = df! [
+            "email" => &["ann@example.com", "bob@example.org", "carl@test.io"],
+            "card" => &["4242 4242 4242 4242", "5555555555554444", "4000056655665556"],
+            "iban" => &["GB82 WEST 1234 5698 7654 32", "DE89370400440532013000", "FR1420041010050500013M02606"],
+            "ip" => &["10.0.0.1", "192.168.1.20", "::1"],
+            "full_name" => &["Mary Smith", "John Doe", "Anna Karenina"],
+            "amount" => &["12", "15", "18"],
+            "order_id" => &[4155550123i64, 4155550124, 4155550125],
+        ].unwrap();
+
+        let findings = detect(&df, 0.6).unwrap();
+        let kind_of = |c: &str| findings.iter().find(|f| f.column == c).map(|f| f.kind);
+        assert_eq!(kind_of("email"), Some(PiiKind::Email));
+        assert_eq!(kind_of("card"), Some(PiiKind::CreditCard));
+        assert_eq!(kind_of("iban"), Some(PiiKind::Iban));
+        assert_eq!(kind_of("ip"), Some(PiiKind::IpAddress));
+        assert_eq!(kind_of("full_name"), Some(PiiKind::PersonName));
+        assert_eq!(kind_of("amount"), None);
+        assert_eq!(kind_of("order_id"), None);
+        assert_eq!(spread_sample((0..10).collect(), 3), vec![0, 3, 6]);
+        assert!(!is_credit_card("4242 4242 4242 4241"));
+        assert!(!is_credit_card("0000000000000000"));
+        assert!(!is_credit_card("0000-0000-0000-0000"));
+        assert!(!is_credit_card("5555555555555555"));
+        assert!(!is_credit_card("1234567812345670"));
+
+        let kinds = findings.iter().map(|f| (f.column.clone(), f.kind)).collect();
+        let actions = HashMap::from([
+            ("email".to_string(), PiiAction::Hash),
+            ("card".to_string(), PiiAction::Mask),
+            ("iban".to_string(), PiiAction::Drop),
+        ]);
+        let cleaned = redact(&df, &actions, &kinds, "salt").unwrap();
+
+        assert!(cleaned.column("iban").is_err());
+        let card = cleaned.column("card").unwrap().utf8().unwrap().get(0).unwrap().to_string();
+        assert_eq!(card, "**** **** **** 4242");
+        let email = cleaned.column("email").unwrap().utf8().unwrap().get(0).unwrap().to_string();
+        assert_eq!(email.len(), 64);
+    }
+}
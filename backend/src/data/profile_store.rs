@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use std::{sync::Arc, time::Instant};
+
+use crate::models::{Database, Dataset};
+
+use super::profiler::{DataProfile, DataProfiler};
+
+/// Profiles a dataset with the default configuration and caches the result
+/// under the dataset's current version.
+pub async fn refresh_profile(
+    db: &Database,
+    profiler: &DataProfiler,
+    dataset: &Dataset,
+) -> Result<DataProfile> {
+    db.set_dataset_profile_status(&dataset.id, dataset.version, "running")
+        .await?;
+
+    let started = Instant::now();
+    let result = profiler.profile_file(&dataset.file_path).await;
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    match result {
+        Ok(mut profile) => {
+            // Never expose the server-side location of the file
+            profile.file_path = dataset.name.clone();
+            let json = serde_json::to_value(&profile).context("Failed to serialize profile")?;
+            db.save_dataset_profile(&dataset.id, dataset.version, &json, duration_ms)
+                .await?;
+            Ok(profile)
+        }
+        Err(e) => {
+            db.fail_dataset_profile(&dataset.id, dataset.version, &e.to_string(), duration_ms)
+                .await?;
+            Err(e)
+        }
+    }
+}
+
+/// Returns the cached profile for the dataset's current version, if one was computed.
+pub async fn cached_profile(db: &Database, dataset: &Dataset) -> Result<Option<DataProfile>> {
+    let record = db.get_dataset_profile(&dataset.id, dataset.version).await?;
+    Ok(record
+        .and_then(|r| r.profile)
+        .and_then(|json| parse_cached(dataset, &json)))
+}
+
+/// A cached profile, or `None` if it was stored in a shape this version no
+/// longer reads; such a profile is stale and computed again.
+pub fn parse_cached(dataset: &Dataset, json: &str) -> Option<DataProfile> {
+    match serde_json::from_str(json) {
+        Ok(profile) => Some(profile),
+        Err(e) => {
+            tracing::warn!(
+                "Discarding stale profile of dataset {} version {}: {}",
+                dataset.id,
+                dataset.version,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// The cached profile of the dataset's current version, or `None` while it is
+/// being computed; a profile that was never computed, failed or is stale is
+/// queued again.
+pub async fn profile_or_queue(db: &Arc<Database>, dataset: &Dataset) -> Result<Option<DataProfile>> {
+    let record = db.get_dataset_profile(&dataset.id, dataset.version).await?;
+    if let Some(record) = record {
+        if let Some(profile) = record
+            .profile
+            .as_deref()
+            .and_then(|json| parse_cached(dataset, json))
+        {
+            return Ok(Some(profile));
+        }
+        if matches!(record.status.as_str(), "pending" | "running") {
+            return Ok(None);
+        }
+    }
+    spawn_profile_job(db.clone(), dataset.clone());
+    Ok(None)
+}
+
+/// Profiles the dataset in the background, e.g. right after upload, unless
+/// its current version is already queued or being profiled.
+pub fn spawn_profile_job(db: Arc<Database>, dataset: Dataset) {
+    tokio::spawn(async move {
+        match db.claim_dataset_profile(&dataset.id, dataset.version).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                tracing::error!("Failed to queue profile for dataset {}: {}", dataset.id, e);
+                return;
+            }
+        }
+
+        let profiler = DataProfiler::new();
+        if let Err(e) = refresh_profile(&db, &profiler, &dataset).await {
+            tracing::error!("Failed to profile dataset {}: {}", dataset.id, e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::profiler::{ColumnStats, ColumnStatsValues};
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stale_profile_is_queued_once() {
+        let db = Database::new().await.unwrap();
+        let dataset = db
+            .create_dataset("stale.csv", "data/datasets/stale.csv", 0)
+            .await
+            .unwrap();
+        // A profile cached in a format that no longer parses
+        let stale = serde_json::json!({
+            "file_path": "stale.csv",
+            "columns": [{"name": "city", "stats": {"top_values": [], "num_categories": 0}}]
+        });
+        db.save_dataset_profile(&dataset.id, dataset.version, &stale, 1)
+            .await
+            .unwrap();
+
+        assert!(cached_profile(&db, &dataset).await.unwrap().is_none());
+        assert!(db
+            .claim_dataset_profile(&dataset.id, dataset.version)
+            .await
+            .unwrap());
+        assert!(!db
+            .claim_dataset_profile(&dataset.id, dataset.version)
+            .await
+            .unwrap());
+    }
+
+    #[test]
+    fn test_cached_stats_keep_their_kind() {
+        let column = |stats| ColumnStats {
+            name: "city".to_string(),
+            dtype: "str".to_string(),
+            null_count: 0,
+            unique_count: Some(2),
+            stats,
+            distribution: None,
+        };
+        let round_trip = |column: ColumnStats| -> ColumnStats {
+            serde_json::from_str(&serde_json::to_string(&column).unwrap()).unwrap()
+        };
+
+        let cached = round_trip(column(ColumnStatsValues::Categorical {
+            top_values: vec![("Paris".to_string(), 3)],
+            num_categories: 2,
+        }));
+        assert!(matches!(
+            cached.stats,
+            ColumnStatsValues::Categorical { num_categories: 2, .. }
+        ));
+        let cached = round_trip(column(ColumnStatsValues::Boolean {
+            true_count: 1,
+            false_count: 4,
+        }));
+        assert!(matches!(
+            cached.stats,
+            ColumnStatsValues::Boolean { false_count: 4, .. }
+        ));
+        let cached = round_trip(column(ColumnStatsValues::Unsupported {}));
+        assert!(matches!(cached.stats, ColumnStatsValues::Unsupported {}));
+    }
+}
@@ -3,6 +3,67 @@ use polars::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+use super::drift::{self, DriftReference, DriftReport, DriftThresholds};
+use super::outliers::{self, OutlierReport, OutlierRule, OutlierThresholds};
+use super::pii::{self, PiiFinding};
+use super::reader::DatasetReader;
+use super::stats::{self, Correlations, Distribution, Histogram};
+use super::temporal::{self, TemporalSummary};
+use super::text::{self, TextSummary};
+
+/// Most histogram bins a profile request may ask for.
+pub const MAX_HISTOGRAM_BINS: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfilerConfig {
+    /// Number of equal-width bins used for numeric histograms
+    pub histogram_bins: usize,
+    /// Maximum number of bars kept in categorical value counts
+    pub max_value_counts: usize,
+    /// Text columns with at most this many distinct values are treated as categorical
+    pub max_categorical_cardinality: usize,
+    /// Absolute coefficient above which a column pair is reported as an issue
+    pub correlation_threshold: f64,
+    /// Share of sampled values that must look like personal data to flag a column
+    pub pii_match_ratio: f64,
+    pub mode: ProfileMode,
+    /// Files at least this large are scanned in streaming mode when `mode` is `Auto`
+    pub streaming_threshold_bytes: u64,
+    /// Rows to sample; `None` profiles every row unless streaming
+    pub sample_size: Option<usize>,
+    /// Sample size used by streaming mode when `sample_size` is not set
+    pub streaming_sample_size: usize,
+    /// Seed for the row sampler so repeated runs pick the same rows
+    pub sample_seed: u64,
+    pub outlier_thresholds: OutlierThresholds,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileMode {
+    Auto,
+    Full,
+    Streaming,
+}
+
+impl Default for ProfilerConfig {
+    fn default() -> Self {
+        Self {
+            histogram_bins: 20,
+            max_value_counts: 20,
+            max_categorical_cardinality: 50,
+            correlation_threshold: 0.9,
+            pii_match_ratio: 0.6,
+            mode: ProfileMode::Auto,
+            streaming_threshold_bytes: 256 * 1024 * 1024,
+            sample_size: None,
+            streaming_sample_size: 100_000,
+            sample_seed: 42,
+            outlier_thresholds: OutlierThresholds::default(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ColumnStats {
     pub name: String,
@@ -10,10 +71,11 @@ pub struct ColumnStats {
     pub null_count: usize,
     pub unique_count: Option<usize>,
     pub stats: ColumnStatsValues,
+    pub distribution: Option<Distribution>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(untagged)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ColumnStatsValues {
     Numeric {
         min: Option<f64>,
@@ -37,11 +99,14 @@ pub enum ColumnStatsValues {
     DateTime {
         min: Option<String>,
         max: Option<String>,
+        /// Only computed for dates and timestamps, not durations or times of day
+        summary: Option<TemporalSummary>,
     },
     Text {
         avg_length: f64,
         min_length: Option<u32>,
         max_length: Option<u32>,
+        summary: TextSummary,
     },
     Unsupported {},
 }
@@ -54,24 +119,257 @@ pub struct DataProfile {
     pub total_size_bytes: u64,
     pub columns: Vec<ColumnStats>,
     pub missing_values: bool,
+    pub correlations: Option<Correlations>,
+    pub outliers: Option<OutlierReport>,
+    /// Columns that likely contain personal data
+    pub pii: Vec<PiiFinding>,
     pub potential_issues: Vec<String>,
     pub suggested_actions: Vec<String>,
+    pub accuracy: StatAccuracy,
+    pub sample: Option<SampleInfo>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Accuracy {
+    Exact,
+    Approximate,
 }
 
-pub struct DataProfiler;
+/// Which parts of a profile were computed over every row and which were
+/// estimated from a sample or a sketch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatAccuracy {
+    pub row_count: Accuracy,
+    pub null_counts: Accuracy,
+    pub min_max: Accuracy,
+    pub unique_counts: Accuracy,
+    pub moments_and_quantiles: Accuracy,
+    pub distributions: Accuracy,
+    pub correlations: Accuracy,
+    pub issues: Accuracy,
+}
+
+impl StatAccuracy {
+    pub fn exact() -> Self {
+        Self {
+            row_count: Accuracy::Exact,
+            null_counts: Accuracy::Exact,
+            min_max: Accuracy::Exact,
+            unique_counts: Accuracy::Exact,
+            moments_and_quantiles: Accuracy::Exact,
+            distributions: Accuracy::Exact,
+            correlations: Accuracy::Exact,
+            issues: Accuracy::Exact,
+        }
+    }
+
+    pub fn sampled() -> Self {
+        Self {
+            row_count: Accuracy::Exact,
+            null_counts: Accuracy::Exact,
+            min_max: Accuracy::Exact,
+            unique_counts: Accuracy::Approximate,
+            moments_and_quantiles: Accuracy::Approximate,
+            distributions: Accuracy::Approximate,
+            correlations: Accuracy::Approximate,
+            issues: Accuracy::Approximate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SampleInfo {
+    pub size: usize,
+    pub population: usize,
+    pub seed: u64,
+}
+
+const ROW_COUNT_COLUMN: &str = "__openmind_rows";
+const ROW_INDEX_COLUMN: &str = "__openmind_row_index";
+
+/// Alias of an aggregate of the column at `index`; positional, since any
+/// separator could also appear in a column's name.
+fn aggregate_alias(index: usize, stat: &str) -> String {
+    format!("__openmind_{}_{}", index, stat)
+}
+
+struct ColumnAggregates {
+    name: String,
+    null_count: usize,
+    approx_unique_count: usize,
+    /// Dates and datetimes are scanned as milliseconds since the epoch
+    min: Option<AnyValue<'static>>,
+    max: Option<AnyValue<'static>>,
+}
+
+struct ScanAggregates {
+    num_rows: usize,
+    columns: Vec<ColumnAggregates>,
+}
+
+pub struct DataProfiler {
+    config: ProfilerConfig,
+}
 
 impl DataProfiler {
     pub fn new() -> Self {
-        DataProfiler
+        Self::with_config(ProfilerConfig::default())
+    }
+
+    pub fn with_config(config: ProfilerConfig) -> Self {
+        DataProfiler { config }
+    }
+
+    pub fn config(&self) -> &ProfilerConfig {
+        &self.config
     }
 
     pub async fn profile_file<P: AsRef<Path>>(&self, file_path: P) -> Result<DataProfile> {
+        self.profile_file_with_config(file_path, &self.config).await
+    }
+
+    pub async fn profile_file_with_config<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        config: &ProfilerConfig,
+    ) -> Result<DataProfile> {
         let file_path = file_path.as_ref();
         let file_size = std::fs::metadata(file_path)?.len();
 
-        // Read the file with Polars
-        let df = self.read_file(file_path).await?;
+        let streaming = match config.mode {
+            ProfileMode::Full => false,
+            ProfileMode::Streaming => true,
+            ProfileMode::Auto => {
+                file_size >= config.streaming_threshold_bytes && Self::supports_scan(file_path)
+            }
+        };
+
+        if !streaming && config.sample_size.is_none() {
+            // Small enough to profile everything exactly
+            let df = self.read_file(file_path).await?;
+            return self
+                .profile_frame(&df, file_path, file_size, config, StatAccuracy::exact(), None)
+                .await;
+        }
+
+        // Exact counts, nulls and min/max over the whole file, everything else on a sample
+        let (lf, eager) = if streaming {
+            (self.scan_file(file_path).await?, None)
+        } else {
+            let df = self.read_file(file_path).await?;
+            (df.clone().lazy(), Some(df))
+        };
+
+        let aggregates = self.scan_aggregates(lf.clone())?;
+        let sample_size = config
+            .sample_size
+            .unwrap_or(config.streaming_sample_size)
+            .min(aggregates.num_rows);
+        let indices = sample_indices(aggregates.num_rows, sample_size, config.sample_seed);
+
+        let sample = match eager {
+            Some(df) => df.take(&IdxCa::from_vec("", indices.clone()))?,
+            None => lf
+                .with_row_count(ROW_INDEX_COLUMN, None)
+                .filter(col(ROW_INDEX_COLUMN).is_in(lit(Series::new("", indices.as_slice()))))
+                .drop_columns([ROW_INDEX_COLUMN])
+                .with_streaming(true)
+                .collect()?,
+        };
+
+        let sample_info = SampleInfo {
+            size: sample.height(),
+            population: aggregates.num_rows,
+            seed: config.sample_seed,
+        };
+        let mut profile = self
+            .profile_frame(&sample, file_path, file_size, config, StatAccuracy::sampled(), Some(sample_info))
+            .await?;
+
+        // Replace sample-derived figures with the whole-file aggregates
+        profile.num_rows = aggregates.num_rows;
+        for column in &mut profile.columns {
+            if let Some(agg) = aggregates.columns.iter().find(|a| a.name == column.name) {
+                column.null_count = agg.null_count;
+                column.unique_count = Some(agg.approx_unique_count);
+                match &mut column.stats {
+                    ColumnStatsValues::Numeric { min, max, .. } => {
+                        *min = agg.min.as_ref().and_then(|v| v.extract::<f64>());
+                        *max = agg.max.as_ref().and_then(|v| v.extract::<f64>());
+                    },
+                    ColumnStatsValues::DateTime { min, max, .. } => {
+                        let format = |v: &AnyValue| match v {
+                            AnyValue::Int64(ms) => temporal::format_ms(*ms),
+                            v => v.to_string(),
+                        };
+                        *min = agg.min.as_ref().map(format);
+                        *max = agg.max.as_ref().map(format);
+                    },
+                    _ => {}
+                }
+            }
+        }
+        profile.missing_values = profile.columns.iter().any(|c| c.null_count > 0);
+        if let Some(outliers) = &mut profile.outliers {
+            outliers.remap_rows(&indices);
+        }
+
+        Ok(profile)
+    }
+    
+    /// Compares `current_path` against a reference dataset file.
+    pub async fn compare_files<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        reference_path: P,
+        current_path: Q,
+        thresholds: &DriftThresholds,
+    ) -> Result<DriftReport> {
+        let reference_path = reference_path.as_ref();
+        let current_path = current_path.as_ref();
+        let reference = self.read_file(reference_path).await?;
+        let current = self.read_file(current_path).await?;
+        
+        drift::compare(
+            DriftReference::Frame {
+                name: reference_path.to_string_lossy().to_string(),
+                df: &reference,
+            },
+            &current_path.to_string_lossy(),
+            &current,
+            &self.config,
+            thresholds,
+        )
+    }
+    
+    /// Compares `current_path` against a previously computed profile, e.g. of the training data.
+    pub async fn compare_with_profile<Q: AsRef<Path>>(
+        &self,
+        reference: &DataProfile,
+        current_path: Q,
+        thresholds: &DriftThresholds,
+    ) -> Result<DriftReport> {
+        let current_path = current_path.as_ref();
+        let current = self.read_file(current_path).await?;
         
+        drift::compare(
+            DriftReference::Profile(reference),
+            &current_path.to_string_lossy(),
+            &current,
+            &self.config,
+            thresholds,
+        )
+    }
+    
+    async fn profile_frame(
+        &self,
+        df: &DataFrame,
+        file_path: &Path,
+        file_size: u64,
+        config: &ProfilerConfig,
+        accuracy: StatAccuracy,
+        sample: Option<SampleInfo>,
+    ) -> Result<DataProfile> {
         // Get basic info
         let num_rows = df.height();
         let num_columns = df.width();
@@ -82,7 +380,7 @@ impl DataProfiler {
         
         for name in df.get_column_names() {
             let series = df.column(name)?;
-            let stats = self.profile_column(series)?;
+            let stats = self.profile_column(series, config)?;
             
             if stats.null_count > 0 {
                 has_missing_values = true;
@@ -91,11 +389,80 @@ impl DataProfiler {
             columns.push(stats);
         }
         
+        // Correlations between numeric and between categorical columns
+        let correlations = self.compute_correlations(df, &columns, config)?;
+        
         // Detect potential issues
-        let potential_issues = self.detect_issues(&df).await?;
+        let mut potential_issues = self.detect_issues(df).await?;
+        for pair in &correlations.top_pairs {
+            potential_issues.push(format!(
+                "Columns '{}' and '{}' are highly correlated ({} {:.2}); possible leakage or multicollinearity",
+                pair.column_a, pair.column_b, pair.method, pair.coefficient
+            ));
+        }
+        
+        for column in &columns {
+            match &column.stats {
+                ColumnStatsValues::Text { summary, .. } if summary.numbers_stored_as_strings() => {
+                    potential_issues.push(format!(
+                        "Column '{}' contains numbers stored as strings ({:.1}% of non-empty values parse as numbers)",
+                        column.name,
+                        summary.numeric_string_ratio * 100.0
+                    ));
+                },
+                ColumnStatsValues::DateTime { summary: Some(summary), .. } if summary.gap_count > 0 => {
+                    potential_issues.push(format!(
+                        "Column '{}' has {} gaps in time coverage (typical step {}s)",
+                        column.name,
+                        summary.gap_count,
+                        summary.typical_step_seconds.unwrap_or_default()
+                    ));
+                },
+                _ => {}
+            }
+        }
+        
+        let outliers = outliers::detect(df, config)?;
+        for set in outliers.columns.iter().chain(&outliers.multivariate) {
+            if set.count == 0 {
+                continue;
+            }
+            match &set.rule {
+                OutlierRule::Iqr { lower_fence, upper_fence } => {
+                    potential_issues.push(format!(
+                        "Column '{}' has {} outliers ({:.1}%) outside the IQR fences [{:.4}, {:.4}]",
+                        set.columns[0], set.count, set.ratio * 100.0, lower_fence, upper_fence
+                    ));
+                },
+                OutlierRule::RareCategory { rare_values, .. } => {
+                    potential_issues.push(format!(
+                        "Column '{}' has {} rare categories covering {} rows",
+                        set.columns[0], rare_values.len(), set.count
+                    ));
+                },
+                OutlierRule::Mahalanobis { .. } => {
+                    potential_issues.push(format!(
+                        "Found {} multivariate outliers ({:.1}%) across {} numeric columns",
+                        set.count, set.ratio * 100.0, set.columns.len()
+                    ));
+                },
+                // Z-scores flag the same extremes as the IQR fences on most data
+                OutlierRule::ZScore { .. } => {}
+            }
+        }
+        
+        let pii = pii::detect(df, config.pii_match_ratio)?;
+        for finding in &pii {
+            potential_issues.push(format!(
+                "Column '{}' likely contains personal data: {} ({:.1}% of sampled values)",
+                finding.column,
+                finding.kind.description(),
+                finding.match_ratio * 100.0
+            ));
+        }
         
         // Generate suggestions
-        let suggested_actions = self.generate_suggestions(&df, &potential_issues).await?;
+        let suggested_actions = self.generate_suggestions(df, &potential_issues).await?;
 
         Ok(DataProfile {
             file_path: file_path.to_string_lossy().to_string(),
@@ -104,37 +471,88 @@ impl DataProfiler {
             total_size_bytes: file_size,
             columns,
             missing_values: has_missing_values,
+            correlations: Some(correlations),
+            outliers: Some(outliers),
+            pii,
             potential_issues,
             suggested_actions,
+            accuracy,
+            sample,
         })
     }
     
-    async fn read_file<P: AsRef<Path>>(&self, file_path: P) -> Result<DataFrame> {
-        let file_path = file_path.as_ref();
-        let ext = file_path.extension()
-            .and_then(|s| s.to_str())
-            .unwrap_or("")
-            .to_lowercase();
-        
-        match ext.as_str() {
-            "csv" => CsvReader::from_path(file_path)?.finish(),
-            "parquet" | "pq" => {
-                let file = std::fs::File::open(file_path)?;
-                ParquetReader::new(file).finish()
-            },
-            "json" => JsonReader::new(std::fs::File::open(file_path)?).finish(),
-            "xlsx" | "xls" => {
-                let xls: Xlsx<_> = Xlsx::new(file_path)?;
-                xls.finish()
-            },
-            _ => Err(PolarsError::ComputeError(
-                format!("Unsupported file format: {}", ext).into()
-            )),
+    fn supports_scan(file_path: &Path) -> bool {
+        DatasetReader::open(file_path)
+            .map(|reader| reader.info().supports_scan())
+            .unwrap_or(false)
+    }
+    
+    async fn scan_file(&self, file_path: &Path) -> Result<LazyFrame> {
+        let reader = DatasetReader::open(file_path)?;
+        match reader.scan()? {
+            Some(lf) => Ok(lf),
+            // Formats without a lazy scanner are read eagerly
+            None => Ok(reader.read(None).await?.lazy()),
         }
-        .map_err(Into::into)
     }
     
-    fn profile_column(&self, series: &Series) -> Result<ColumnStats> {
+    /// Single streaming pass computing row count, per-column nulls, min/max and
+    /// HyperLogLog distinct counts.
+    fn scan_aggregates(&self, lf: LazyFrame) -> Result<ScanAggregates> {
+        let schema = lf.schema()?;
+        
+        let mut exprs = vec![count().alias(ROW_COUNT_COLUMN)];
+        for (i, (name, dtype)) in schema.iter().enumerate() {
+            exprs.push(col(name).null_count().alias(&aggregate_alias(i, "null_count")));
+            exprs.push(col(name).approx_n_unique().alias(&aggregate_alias(i, "approx_unique")));
+            // Formatted like the bounds of a fully scanned column
+            let bounded = match dtype {
+                DataType::Date | DataType::Datetime(_, _) => Some(
+                    col(name)
+                        .cast(DataType::Datetime(TimeUnit::Milliseconds, None))
+                        .cast(DataType::Int64),
+                ),
+                dtype if dtype.is_numeric() || dtype.is_temporal() => Some(col(name)),
+                _ => None,
+            };
+            if let Some(bounded) = bounded {
+                exprs.push(bounded.clone().min().alias(&aggregate_alias(i, "min")));
+                exprs.push(bounded.max().alias(&aggregate_alias(i, "max")));
+            }
+        }
+        
+        let result = lf.select(exprs).with_streaming(true).collect()?;
+        let get = |alias: &str| -> Option<AnyValue<'static>> {
+            result.column(alias).ok()?.get(0).ok()?.into_static().ok()
+        };
+        
+        let num_rows = get(ROW_COUNT_COLUMN)
+            .and_then(|v| v.extract::<usize>())
+            .context("Failed to count rows")?;
+        
+        let columns = schema
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| {
+                let key = |stat: &str| aggregate_alias(i, stat);
+                ColumnAggregates {
+                    name: name.to_string(),
+                    null_count: get(&key("null_count")).and_then(|v| v.extract::<usize>()).unwrap_or(0),
+                    approx_unique_count: get(&key("approx_unique")).and_then(|v| v.extract::<usize>()).unwrap_or(0),
+                    min: get(&key("min")).filter(|v| !v.is_null()),
+                    max: get(&key("max")).filter(|v| !v.is_null()),
+                }
+            })
+            .collect();
+        
+        Ok(ScanAggregates { num_rows, columns })
+    }
+    
+    pub(crate) async fn read_file<P: AsRef<Path>>(&self, file_path: P) -> Result<DataFrame> {
+        DatasetReader::open(file_path)?.read(None).await
+    }
+    
+    fn profile_column(&self, series: &Series, config: &ProfilerConfig) -> Result<ColumnStats> {
         let name = series.name().to_string();
         let dtype = series.dtype().to_string();
         let null_count = series.null_count();
@@ -167,29 +585,40 @@ impl DataProfiler {
             },
             DataType::Utf8 => {
                 let s = series.utf8()?;
-                let lengths = s.into_iter().map(|opt| opt.map(|s| s.len() as u32));
-                let lengths: Vec<u32> = lengths.filter_map(|x| x).collect();
-                
-                if lengths.is_empty() {
-                    ColumnStatsValues::Text {
-                        avg_length: 0.0,
-                        min_length: None,
-                        max_length: None,
-                    }
+                let lengths: Vec<u32> = s.into_iter().flatten().map(|v| v.chars().count() as u32).collect();
+                let avg_length = if lengths.is_empty() {
+                    0.0
                 } else {
-                    let avg_length = lengths.iter().sum::<u32>() as f64 / lengths.len() as f64;
-                    ColumnStatsValues::Text {
-                        avg_length,
-                        min_length: lengths.iter().min().copied(),
-                        max_length: lengths.iter().max().copied(),
-                    }
+                    lengths.iter().sum::<u32>() as f64 / lengths.len() as f64
+                };
+                
+                ColumnStatsValues::Text {
+                    avg_length,
+                    min_length: lengths.iter().min().copied(),
+                    max_length: lengths.iter().max().copied(),
+                    summary: text::summarize(s.into_iter().flatten()),
                 }
             },
-            DataType::Date | DataType::Datetime(_, _) | DataType::Duration(_) | DataType::Time => {
-                let s = series.utf8()?;
+            DataType::Date | DataType::Datetime(_, _) => {
+                let ms = series
+                    .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?
+                    .cast(&DataType::Int64)?;
+                let timestamps: Vec<i64> = ms.i64()?.into_iter().flatten().collect();
+                
+                ColumnStatsValues::DateTime {
+                    min: timestamps.iter().min().map(|&ts| temporal::format_ms(ts)),
+                    max: timestamps.iter().max().map(|&ts| temporal::format_ms(ts)),
+                    summary: Some(temporal::summarize(&timestamps)),
+                }
+            },
+            DataType::Duration(_) | DataType::Time => {
+                let sorted = series.drop_nulls().sort(false);
+                let value_at = |i: usize| sorted.get(i).ok().map(|v| v.to_string());
+                
                 ColumnStatsValues::DateTime {
-                    min: s.min().map(|s| s.to_string()),
-                    max: s.max().map(|s| s.to_string()),
+                    min: value_at(0),
+                    max: sorted.len().checked_sub(1).and_then(value_at),
+                    summary: None,
                 }
             },
             DataType::Categorical(_) => {
@@ -208,15 +637,56 @@ impl DataProfiler {
             _ => ColumnStatsValues::Unsupported {},
         };
         
+        let distribution = if series.dtype().is_numeric() {
+            let values: Vec<f64> = numeric_values(series)?.into_iter().flatten().collect();
+            Histogram::from_values(&values, config.histogram_bins).map(Distribution::Histogram)
+        } else if is_categorical(series, unique_count, config) {
+            let values = categorical_values(series)?;
+            Some(Distribution::ValueCounts {
+                values: stats::value_counts(
+                    values.iter().map(|v| v.as_deref()),
+                    config.max_value_counts,
+                ),
+            })
+        } else {
+            None
+        };
+        
         Ok(ColumnStats {
             name,
             dtype,
             null_count,
             unique_count: Some(unique_count),
             stats,
+            distribution,
         })
     }
     
+    fn compute_correlations(
+        &self,
+        df: &DataFrame,
+        columns: &[ColumnStats],
+        config: &ProfilerConfig,
+    ) -> Result<Correlations> {
+        let mut numeric = Vec::new();
+        let mut categorical = Vec::new();
+        
+        for stats in columns {
+            let series = df.column(&stats.name)?;
+            if series.dtype().is_numeric() {
+                numeric.push((stats.name.clone(), numeric_values(series)?));
+            } else if is_categorical(series, stats.unique_count.unwrap_or(0), config) {
+                categorical.push((stats.name.clone(), categorical_values(series)?));
+            }
+        }
+        
+        Ok(Correlations::compute(
+            &numeric,
+            &categorical,
+            config.correlation_threshold,
+        ))
+    }
+    
     async fn detect_issues(&self, df: &DataFrame) -> Result<Vec<String>> {
         let mut issues = Vec::new();
         
@@ -284,6 +754,18 @@ impl DataProfiler {
                 suggestions.push("Consider removing duplicate rows if they don't provide additional information.".to_string());
             } else if issue.contains("high cardinality") {
                 suggestions.push("Consider using target encoding, hashing, or other techniques for high cardinality categorical variables.".to_string());
+            } else if issue.contains("personal data") {
+                suggestions.push("Hash, mask or drop columns containing personal data before sharing the dataset.".to_string());
+            } else if issue.contains("highly correlated") {
+                suggestions.push("Check highly correlated columns for target leakage and consider dropping one of each redundant pair.".to_string());
+            } else if issue.contains("outliers") {
+                suggestions.push("Review outlier rows, which can be exported as a separate dataset, and consider capping, transforming or removing them.".to_string());
+            } else if issue.contains("rare categories") {
+                suggestions.push("Group rare categories into an 'other' bucket before encoding.".to_string());
+            } else if issue.contains("numbers stored as strings") {
+                suggestions.push("Cast columns holding numbers stored as strings to a numeric type, stripping thousands separators first.".to_string());
+            } else if issue.contains("gaps in time coverage") {
+                suggestions.push("Resample or impute missing periods in datetime columns before time-series modelling.".to_string());
             }
         }
         
@@ -329,6 +811,39 @@ impl DataProfiler {
     }
 }
 
+/// Sorted, reproducible sample of `amount` row indices out of `len`.
+fn sample_indices(len: usize, amount: usize, seed: u64) -> Vec<IdxSize> {
+    use rand::{rngs::StdRng, SeedableRng};
+    
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut indices: Vec<IdxSize> = rand::seq::index::sample(&mut rng, len, amount.min(len))
+        .into_iter()
+        .map(|i| i as IdxSize)
+        .collect();
+    indices.sort_unstable();
+    indices
+}
+
+pub(super) fn numeric_values(series: &Series) -> Result<Vec<Option<f64>>> {
+    let s = series.cast(&DataType::Float64)?;
+    Ok(s.f64()?.into_iter().collect())
+}
+
+pub(super) fn categorical_values(series: &Series) -> Result<Vec<Option<String>>> {
+    let s = series.cast(&DataType::Utf8)?;
+    Ok(s.utf8()?.into_iter().map(|v| v.map(str::to_string)).collect())
+}
+
+/// Booleans and categoricals always count as categorical; text only when its
+/// cardinality is low enough for value counts to be meaningful.
+pub(super) fn is_categorical(series: &Series, unique_count: usize, config: &ProfilerConfig) -> bool {
+    match series.dtype() {
+        DataType::Boolean | DataType::Categorical(_) => true,
+        DataType::Utf8 => unique_count <= config.max_categorical_cardinality,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -358,4 +873,169 @@ mod tests {
         assert!(!profile.potential_issues.is_empty());
         assert!(!profile.suggested_actions.is_empty());
     }
+    
+    #[tokio::test]
+    async fn test_histograms_and_correlations() {
+        let df = df! [
+            "x" => &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            "y" => &[2.0, 4.0, 6.0, 8.0, 10.0, 12.0],
+            "color" => &["red", "blue", "red", "blue", "red", "blue"],
+        ].unwrap();
+        
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("corr.csv");
+        
+        let mut file = std::fs::File::create(&file_path).unwrap();
+        CsvWriter::new(&mut file).finish(&mut df.clone()).unwrap();
+        
+        let profiler = DataProfiler::with_config(ProfilerConfig {
+            histogram_bins: 3,
+            ..ProfilerConfig::default()
+        });
+        let profile = profiler.profile_file(&file_path).await.unwrap();
+        
+        let x = profile.columns.iter().find(|c| c.name == "x").unwrap();
+        match &x.distribution {
+            Some(Distribution::Histogram(h)) => assert_eq!(h.counts.len(), 3),
+            other => panic!("expected histogram, got {:?}", other),
+        }
+        
+        let color = profile.columns.iter().find(|c| c.name == "color").unwrap();
+        assert!(matches!(color.distribution, Some(Distribution::ValueCounts { .. })));
+        
+        let correlations = profile.correlations.unwrap();
+        assert_eq!(correlations.top_pairs[0].column_a, "x");
+        assert_eq!(correlations.top_pairs[0].column_b, "y");
+        assert!(profile.potential_issues.iter().any(|i| i.contains("highly correlated")));
+    }
+    
+    #[tokio::test]
+    async fn test_streaming_profile_with_sample() {
+        let values: Vec<i64> = (0..1000).collect();
+        let nulls: Vec<Option<f64>> = (0..1000)
+            .map(|i| if i % 10 == 0 { None } else { Some(i as f64) })
+            .collect();
+        let df = df! [
+            "id" => &values,
+            "score" => &nulls,
+        ].unwrap();
+        
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("large.csv");
+        
+        let mut file = std::fs::File::create(&file_path).unwrap();
+        CsvWriter::new(&mut file).finish(&mut df.clone()).unwrap();
+        
+        let config = ProfilerConfig {
+            mode: ProfileMode::Streaming,
+            sample_size: Some(100),
+            ..ProfilerConfig::default()
+        };
+        let profiler = DataProfiler::new();
+        let first = profiler.profile_file_with_config(&file_path, &config).await.unwrap();
+        let second = profiler.profile_file_with_config(&file_path, &config).await.unwrap();
+        
+        // Counts, nulls and min/max are exact even though only 100 rows were profiled
+        assert_eq!(first.num_rows, 1000);
+        assert_eq!(first.sample.as_ref().unwrap().size, 100);
+        assert_eq!(first.accuracy.null_counts, Accuracy::Exact);
+        assert_eq!(first.accuracy.moments_and_quantiles, Accuracy::Approximate);
+        
+        let score = first.columns.iter().find(|c| c.name == "score").unwrap();
+        assert_eq!(score.null_count, 100);
+        match &score.stats {
+            ColumnStatsValues::Numeric { min, max, .. } => {
+                assert_eq!(*min, Some(1.0));
+                assert_eq!(*max, Some(999.0));
+            },
+            other => panic!("expected numeric stats, got {:?}", other),
+        }
+        
+        // The same seed yields the same sample
+        let mean = |p: &DataProfile| match &p.columns[0].stats {
+            ColumnStatsValues::Numeric { mean, .. } => *mean,
+            _ => None,
+        };
+        assert_eq!(mean(&first), mean(&second));
+    }
+    
+    #[tokio::test]
+    async fn test_datetime_and_text_columns() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("events.csv");
+        std::fs::write(
+            &file_path,
+            "day,amount,note\n\
+             2024-01-01,\"1,200\",first\n\
+             2024-01-02,\"3,400\",\n\
+             2024-01-03,\"5,600\",second note\n\
+             2024-01-06,\"7,800\",third\n",
+        )
+        .unwrap();
+        
+        let profile = DataProfiler::new().profile_file(&file_path).await.unwrap();
+        
+        let day = profile.columns.iter().find(|c| c.name == "day").unwrap();
+        match &day.stats {
+            ColumnStatsValues::DateTime { min, summary: Some(summary), .. } => {
+                assert_eq!(min.as_deref(), Some("2024-01-01 00:00:00"));
+                assert_eq!(summary.gap_count, 1);
+            },
+            other => panic!("expected datetime stats, got {:?}", other),
+        }
+        
+        assert!(profile.potential_issues.iter().any(|i| i.contains("'amount' contains numbers stored as strings")));
+        assert!(profile.potential_issues.iter().any(|i| i.contains("'day' has 1 gaps in time coverage")));
+        
+        // Tagged stats survive a round trip through the profile cache
+        let json = serde_json::to_string(&profile).unwrap();
+        let cached: DataProfile = serde_json::from_str(&json).unwrap();
+        let note = cached.columns.iter().find(|c| c.name == "note").unwrap();
+        assert!(matches!(note.stats, ColumnStatsValues::Text { .. }));
+    }
+    
+    #[tokio::test]
+    async fn test_sampled_bounds_match_a_full_scan() {
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("events.csv");
+        std::fs::write(
+            &file_path,
+            "day,a,a::min\n\
+             2024-01-01,5,100\n\
+             2024-01-02,1,200\n\
+             2024-01-03,9,300\n\
+             2024-01-06,3,400\n",
+        )
+        .unwrap();
+        
+        let profiler = DataProfiler::new();
+        let full = profiler.profile_file(&file_path).await.unwrap();
+        let sampled = profiler
+            .profile_file_with_config(
+                &file_path,
+                &ProfilerConfig {
+                    mode: ProfileMode::Streaming,
+                    sample_size: Some(2),
+                    ..ProfilerConfig::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(sampled.sample.is_some());
+        
+        let bounds = |profile: &DataProfile, name: &str| {
+            match &profile.columns.iter().find(|c| c.name == name).unwrap().stats {
+                ColumnStatsValues::DateTime { min, max, .. } => (min.clone(), max.clone()),
+                ColumnStatsValues::Numeric { min, max, .. } => {
+                    (min.map(|v| v.to_string()), max.map(|v| v.to_string()))
+                },
+                other => panic!("expected bounds, got {:?}", other),
+            }
+        };
+        for name in ["day", "a", "a::min"] {
+            assert_eq!(bounds(&sampled, name), bounds(&full, name), "{}", name);
+        }
+        assert_eq!(bounds(&sampled, "day").0.as_deref(), Some("2024-01-01 00:00:00"));
+        assert_eq!(bounds(&sampled, "a::min").0.as_deref(), Some("100"));
+    }
 }
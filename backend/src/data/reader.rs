@@ -0,0 +1,696 @@
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDateTime;
+use flate2::read::GzDecoder;
+use polars::io::mmap::MmapBytesReader;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqliteConnectOptions, Column, Connection, Row, SqliteConnection, TypeInfo, ValueRef};
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+
+/// Bytes inspected when sniffing format, encoding and delimiter.
+const SNIFF_BYTES: u64 = 64 * 1024;
+
+/// Lines used to pick a CSV delimiter.
+const SNIFF_LINES: usize = 20;
+
+const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetFormat {
+    Csv,
+    Parquet,
+    ArrowIpc,
+    Json,
+    Ndjson,
+    Excel,
+    Sqlite,
+}
+
+impl DatasetFormat {
+    /// Formats holding several tables (sheets) that the user picks from.
+    pub fn has_tables(&self) -> bool {
+        matches!(self, DatasetFormat::Excel | DatasetFormat::Sqlite)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    None,
+    Gzip,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+}
+
+/// What was detected about a dataset file from its content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceInfo {
+    pub format: DatasetFormat,
+    pub compression: Compression,
+    /// Only set for text formats
+    pub encoding: Option<TextEncoding>,
+    /// Only set for CSV
+    pub delimiter: Option<char>,
+}
+
+impl SourceInfo {
+    /// Whether polars can scan the file lazily as-is.
+    pub fn supports_scan(&self) -> bool {
+        self.compression == Compression::None
+            && matches!(self.encoding, None | Some(TextEncoding::Utf8))
+            && matches!(
+                self.format,
+                DatasetFormat::Csv | DatasetFormat::Parquet | DatasetFormat::ArrowIpc
+            )
+    }
+
+    /// Comma-separated, uncompressed UTF-8, which every consumer can read.
+    pub fn is_plain_csv(&self) -> bool {
+        self.format == DatasetFormat::Csv
+            && self.compression == Compression::None
+            && self.encoding == Some(TextEncoding::Utf8)
+            && self.delimiter == Some(',')
+    }
+}
+
+/// Reads dataset files of any supported format, detected from content rather
+/// than the file extension.
+pub struct DatasetReader {
+    path: PathBuf,
+    info: SourceInfo,
+}
+
+impl DatasetReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let info = sniff(&path).with_context(|| format!("Failed to detect format of {}", path.display()))?;
+        Ok(Self { path, info })
+    }
+
+    pub fn info(&self) -> &SourceInfo {
+        &self.info
+    }
+
+    /// Tables or sheets in the file; empty for single-table formats.
+    pub async fn tables(&self) -> Result<Vec<String>> {
+        match self.info.format {
+            DatasetFormat::Sqlite => self.sqlite_tables().await,
+            DatasetFormat::Excel => {
+                let workbook = self.open_workbook()?;
+                Ok(calamine::Reader::sheet_names(&workbook).to_vec())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Reads the whole dataset. `table` selects a table or sheet and defaults to the first one.
+    pub async fn read(&self, table: Option<&str>) -> Result<DataFrame> {
+        self.read_rows(table, None).await
+    }
+
+    /// Reads at most `rows` rows, without parsing the rest of the file where the format allows.
+    pub async fn read_head(&self, table: Option<&str>, rows: usize) -> Result<DataFrame> {
+        self.read_rows(table, Some(rows)).await
+    }
+
+    /// A lazy scan of the file when the format supports one.
+    pub fn scan(&self) -> Result<Option<LazyFrame>> {
+        if !self.info.supports_scan() {
+            return Ok(None);
+        }
+
+        let lf = match self.info.format {
+            DatasetFormat::Csv => LazyCsvReader::new(&self.path)
+                .with_separator(self.delimiter())
+                .with_try_parse_dates(true)
+                .finish()?,
+            DatasetFormat::Parquet => LazyFrame::scan_parquet(&self.path, ScanArgsParquet::default())?,
+            DatasetFormat::ArrowIpc => LazyFrame::scan_ipc(&self.path, ScanArgsIpc::default())?,
+            _ => return Ok(None),
+        };
+        Ok(Some(lf))
+    }
+
+    async fn read_rows(&self, table: Option<&str>, rows: Option<usize>) -> Result<DataFrame> {
+        if self.info.format.has_tables() {
+            let tables = self.tables().await?;
+            let table = match table {
+                Some(table) if tables.iter().any(|t| t == table) => table.to_string(),
+                Some(table) => bail!(
+                    "Table '{}' not found; available tables: {}",
+                    table,
+                    tables.join(", ")
+                ),
+                None => tables.first().cloned().context("File contains no tables")?,
+            };
+
+            let df = match self.info.format {
+                DatasetFormat::Sqlite => self.read_sqlite_table(&table, rows).await?,
+                _ => self.read_sheet(&table)?,
+            };
+            return Ok(match rows {
+                Some(rows) => df.head(Some(rows)),
+                None => df,
+            });
+        }
+
+        let df = if self.info.compression == Compression::None
+            && matches!(self.info.encoding, None | Some(TextEncoding::Utf8))
+        {
+            self.parse(File::open(&self.path)?, rows)?
+        } else {
+            self.parse(Cursor::new(self.decoded_bytes()?), rows)?
+        };
+        Ok(match rows {
+            Some(rows) => df.head(Some(rows)),
+            None => df,
+        })
+    }
+
+    fn parse<R: MmapBytesReader + 'static>(&self, input: R, rows: Option<usize>) -> Result<DataFrame> {
+        let df = match self.info.format {
+            DatasetFormat::Csv => CsvReader::new(input)
+                .has_header(true)
+                .with_separator(self.delimiter())
+                .with_try_parse_dates(true)
+                .with_n_rows(rows)
+                .finish()?,
+            DatasetFormat::Parquet => ParquetReader::new(input).finish()?,
+            DatasetFormat::ArrowIpc => IpcReader::new(input).finish()?,
+            DatasetFormat::Json => JsonReader::new(input).finish()?,
+            DatasetFormat::Ndjson => JsonReader::new(input)
+                .with_json_format(JsonFormat::JsonLines)
+                .finish()?,
+            DatasetFormat::Excel | DatasetFormat::Sqlite => unreachable!("tables are read separately"),
+        };
+        Ok(df)
+    }
+
+    fn delimiter(&self) -> u8 {
+        self.info.delimiter.map(|d| d as u8).unwrap_or(b',')
+    }
+
+    /// File content decompressed and transcoded to UTF-8 without a BOM.
+    fn decoded_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        match self.info.compression {
+            Compression::None => File::open(&self.path)?.read_to_end(&mut bytes)?,
+            Compression::Gzip => GzDecoder::new(File::open(&self.path)?).read_to_end(&mut bytes)?,
+        };
+
+        Ok(match self.info.encoding {
+            None | Some(TextEncoding::Utf8) => bytes,
+            Some(encoding) => decode(&bytes, encoding).into_bytes(),
+        })
+    }
+
+    fn read_sheet(&self, sheet: &str) -> Result<DataFrame> {
+        use calamine::{DataType as Value, Reader};
+
+        let mut workbook = self.open_workbook()?;
+        let range = workbook
+            .worksheet_range(sheet)
+            .with_context(|| format!("Sheet '{}' not found", sheet))??;
+
+        let mut rows = range.rows();
+        let headers: Vec<String> = rows
+            .next()
+            .map(|row| row.iter().map(|v| v.to_string()).collect())
+            .unwrap_or_default();
+        let cells = rows
+            .map(|row| {
+                row.iter()
+                    .map(|value| match value {
+                        Value::Empty | Value::Error(_) => Cell::Null,
+                        Value::Int(i) => Cell::Int(*i),
+                        Value::Float(f) => Cell::Float(*f),
+                        Value::Bool(b) => Cell::Bool(*b),
+                        Value::String(s) => Cell::Text(s.clone()),
+                        Value::DateTime(_) => value
+                            .as_datetime()
+                            .map(Cell::DateTime)
+                            .unwrap_or(Cell::Null),
+                        other => Cell::Text(other.to_string()),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        frame_from_cells(headers, cells)
+    }
+
+    /// Opens the workbook with the reader its content calls for; calamine's
+    /// own choice goes by the file extension, which uploads don't keep.
+    fn open_workbook(&self) -> Result<calamine::Sheets<BufReader<File>>> {
+        use calamine::{open_workbook, Sheets};
+
+        Ok(match workbook_kind(&self.path)? {
+            Some(WorkbookKind::Xlsx) => Sheets::Xlsx(open_workbook(&self.path)?),
+            Some(WorkbookKind::Xls) => Sheets::Xls(open_workbook(&self.path)?),
+            Some(WorkbookKind::Ods) => Sheets::Ods(open_workbook(&self.path)?),
+            None => bail!("{} is not a workbook", self.path.display()),
+        })
+    }
+
+    async fn sqlite_connection(&self) -> Result<SqliteConnection> {
+        let options = SqliteConnectOptions::new().filename(&self.path).read_only(true);
+        Ok(SqliteConnection::connect_with(&options).await?)
+    }
+
+    async fn sqlite_tables(&self) -> Result<Vec<String>> {
+        let mut conn = self.sqlite_connection().await?;
+        let tables = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+        )
+        .fetch_all(&mut conn)
+        .await?;
+        Ok(tables)
+    }
+
+    async fn read_sqlite_table(&self, table: &str, rows: Option<usize>) -> Result<DataFrame> {
+        let mut conn = self.sqlite_connection().await?;
+
+        let headers: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+            .bind(table)
+            .fetch_all(&mut conn)
+            .await?;
+
+        // The table name was checked against sqlite_master, quoting guards the rest
+        let mut sql = format!("SELECT * FROM \"{}\"", table.replace('"', "\"\""));
+        if let Some(rows) = rows {
+            sql.push_str(&format!(" LIMIT {}", rows));
+        }
+        let records = sqlx::query(&sql).fetch_all(&mut conn).await?;
+
+        let mut cells = Vec::with_capacity(records.len());
+        for record in &records {
+            let mut row = Vec::with_capacity(record.columns().len());
+            for column in record.columns() {
+                let i = column.ordinal();
+                let raw = record.try_get_raw(i)?;
+                // SQLite is dynamically typed, so the storage class is checked per value
+                let cell = if raw.is_null() {
+                    Cell::Null
+                } else {
+                    match raw.type_info().name() {
+                        "INTEGER" => Cell::Int(record.try_get(i)?),
+                        "REAL" => Cell::Float(record.try_get(i)?),
+                        "TEXT" => Cell::Text(record.try_get(i)?),
+                        // Blobs aren't tabular data
+                        _ => Cell::Null,
+                    }
+                };
+                row.push(cell);
+            }
+            cells.push(row);
+        }
+
+        frame_from_cells(headers, cells)
+    }
+}
+
+/// A value from a dynamically typed source (spreadsheet cell, SQLite value).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Cell {
+    Null,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+    DateTime(NaiveDateTime),
+}
+
+impl Cell {
+    fn to_text(&self) -> Option<String> {
+        match self {
+            Cell::Null => None,
+            Cell::Int(i) => Some(i.to_string()),
+            Cell::Float(f) => Some(f.to_string()),
+            Cell::Bool(b) => Some(b.to_string()),
+            Cell::Text(s) => Some(s.clone()),
+            Cell::DateTime(dt) => Some(dt.format("%Y-%m-%d %H:%M:%S").to_string()),
+        }
+    }
+}
+
+/// Builds a frame giving each column the narrowest type all its values fit.
+pub(crate) fn frame_from_cells(headers: Vec<String>, rows: Vec<Vec<Cell>>) -> Result<DataFrame> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0).max(headers.len());
+    let mut names: Vec<String> = Vec::with_capacity(width);
+    for i in 0..width {
+        let base = headers
+            .get(i)
+            .map(|h| h.trim().to_string())
+            .filter(|h| !h.is_empty())
+            .unwrap_or_else(|| format!("column_{}", i + 1));
+        // Duplicate headers are common in spreadsheets but not allowed in a frame
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while names.contains(&name) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        names.push(name);
+    }
+
+    let mut columns = Vec::with_capacity(width);
+    for (i, name) in names.iter().enumerate() {
+        let cells: Vec<&Cell> = rows.iter().map(|row| row.get(i).unwrap_or(&Cell::Null)).collect();
+        let present = || cells.iter().filter(|c| **c != &Cell::Null);
+
+        let series = if present().next().is_none() {
+            Series::full_null(name, cells.len(), &DataType::Utf8)
+        } else if present().all(|c| matches!(c, Cell::Int(_))) {
+            let values: Vec<Option<i64>> = cells
+                .iter()
+                .map(|c| match c {
+                    Cell::Int(i) => Some(*i),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        } else if present().all(|c| matches!(c, Cell::Int(_) | Cell::Float(_))) {
+            let values: Vec<Option<f64>> = cells
+                .iter()
+                .map(|c| match c {
+                    Cell::Int(i) => Some(*i as f64),
+                    Cell::Float(f) => Some(*f),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        } else if present().all(|c| matches!(c, Cell::Bool(_))) {
+            let values: Vec<Option<bool>> = cells
+                .iter()
+                .map(|c| match c {
+                    Cell::Bool(b) => Some(*b),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        } else if present().all(|c| matches!(c, Cell::DateTime(_))) {
+            let values: Vec<Option<i64>> = cells
+                .iter()
+                .map(|c| match c {
+                    Cell::DateTime(dt) => Some(dt.and_utc().timestamp_millis()),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values).cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?
+        } else {
+            let values: Vec<Option<String>> = cells.iter().map(|c| c.to_text()).collect();
+            Series::new(name, values)
+        };
+        columns.push(series);
+    }
+
+    Ok(DataFrame::new(columns)?)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WorkbookKind {
+    Xlsx,
+    Xls,
+    Ods,
+}
+
+/// The kind of spreadsheet in the file: an OLE compound file is xls, and a zip
+/// archive is xlsx or ods when it holds their workbook entries. `None` for
+/// anything else, including other zip archives.
+fn workbook_kind(path: &Path) -> Result<Option<WorkbookKind>> {
+    let mut magic = Vec::with_capacity(4);
+    File::open(path)?.take(4).read_to_end(&mut magic)?;
+    if magic == [0xd0, 0xcf, 0x11, 0xe0] {
+        return Ok(Some(WorkbookKind::Xls));
+    }
+    if magic != b"PK\x03\x04" {
+        return Ok(None);
+    }
+
+    let Ok(mut archive) = zip::ZipArchive::new(BufReader::new(File::open(path)?)) else {
+        return Ok(None);
+    };
+    if archive.by_name("xl/workbook.xml").is_ok() {
+        return Ok(Some(WorkbookKind::Xlsx));
+    }
+    let mut mimetype = String::new();
+    if let Ok(entry) = archive.by_name("mimetype") {
+        let _ = entry.take(128).read_to_string(&mut mimetype);
+    }
+    Ok((mimetype.trim() == "application/vnd.oasis.opendocument.spreadsheet").then_some(WorkbookKind::Ods))
+}
+
+fn sniff(path: &Path) -> Result<SourceInfo> {
+    let mut head = Vec::new();
+    File::open(path)?.take(SNIFF_BYTES).read_to_end(&mut head)?;
+
+    let compression = if head.starts_with(&[0x1f, 0x8b]) {
+        Compression::Gzip
+    } else {
+        Compression::None
+    };
+    if compression == Compression::Gzip {
+        head.clear();
+        // A truncated stream is fine here, only the beginning is needed
+        let _ = GzDecoder::new(File::open(path)?)
+            .take(SNIFF_BYTES)
+            .read_to_end(&mut head);
+    }
+
+    let binary = if head.starts_with(b"PAR1") {
+        Some(DatasetFormat::Parquet)
+    } else if head.starts_with(b"ARROW1") {
+        Some(DatasetFormat::ArrowIpc)
+    } else if head.starts_with(b"SQLite format 3\0") {
+        Some(DatasetFormat::Sqlite)
+    } else if head.starts_with(b"PK\x03\x04") || head.starts_with(&[0xd0, 0xcf, 0x11, 0xe0]) {
+        // Zip container (xlsx, ods) or OLE compound file (xls)
+        if compression == Compression::None && workbook_kind(path)?.is_none() {
+            bail!("Zip archives are only supported as Excel or OpenDocument workbooks");
+        }
+        Some(DatasetFormat::Excel)
+    } else {
+        None
+    };
+
+    if let Some(format) = binary {
+        if format.has_tables() && compression != Compression::None {
+            bail!("Compressed Excel and SQLite files are not supported; upload them uncompressed");
+        }
+        return Ok(SourceInfo {
+            format,
+            compression,
+            encoding: None,
+            delimiter: None,
+        });
+    }
+
+    let encoding = detect_encoding(&head);
+    let text = decode(&head, encoding);
+    let trimmed = text.trim_start();
+    if trimmed.is_empty() {
+        bail!("File is empty");
+    }
+
+    let (format, delimiter) = if trimmed.starts_with('[') {
+        (DatasetFormat::Json, None)
+    } else if trimmed.starts_with('{') {
+        let lines: Vec<&str> = trimmed.lines().filter(|l| !l.trim().is_empty()).take(SNIFF_LINES).collect();
+        // One object per line is NDJSON; a single pretty-printed object is JSON
+        if lines.len() > 1 && lines.iter().all(|l| l.trim_start().starts_with('{')) {
+            (DatasetFormat::Ndjson, None)
+        } else {
+            (DatasetFormat::Json, None)
+        }
+    } else {
+        (DatasetFormat::Csv, Some(detect_delimiter(&text) as char))
+    };
+
+    Ok(SourceInfo {
+        format,
+        compression,
+        encoding: Some(encoding),
+        delimiter,
+    })
+}
+
+fn detect_encoding(head: &[u8]) -> TextEncoding {
+    if head.starts_with(&[0xef, 0xbb, 0xbf]) {
+        return TextEncoding::Utf8Bom;
+    }
+    if head.starts_with(&[0xff, 0xfe]) {
+        return TextEncoding::Utf16Le;
+    }
+    if head.starts_with(&[0xfe, 0xff]) {
+        return TextEncoding::Utf16Be;
+    }
+
+    // ASCII text in UTF-16 without a BOM has a NUL in every other byte, which is still valid UTF-8
+    let even_nuls = head.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd_nuls = head.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    let half = head.len() / 4;
+    if odd_nuls > half {
+        return TextEncoding::Utf16Le;
+    }
+    if even_nuls > half {
+        return TextEncoding::Utf16Be;
+    }
+
+    match std::str::from_utf8(head) {
+        Ok(_) => TextEncoding::Utf8,
+        // The sample may end in the middle of a multi-byte character
+        Err(e) if e.error_len().is_none() => TextEncoding::Utf8,
+        Err(_) => TextEncoding::Windows1252,
+    }
+}
+
+fn decode(bytes: &[u8], encoding: TextEncoding) -> String {
+    let (decoder, bom) = match encoding {
+        TextEncoding::Utf8 => (encoding_rs::UTF_8, 0),
+        TextEncoding::Utf8Bom => (encoding_rs::UTF_8, 3),
+        TextEncoding::Utf16Le => (encoding_rs::UTF_16LE, if bytes.starts_with(&[0xff, 0xfe]) { 2 } else { 0 }),
+        TextEncoding::Utf16Be => (encoding_rs::UTF_16BE, if bytes.starts_with(&[0xfe, 0xff]) { 2 } else { 0 }),
+        TextEncoding::Windows1252 => (encoding_rs::WINDOWS_1252, 0),
+    };
+    let (text, _) = decoder.decode_without_bom_handling(&bytes[bom.min(bytes.len())..]);
+    text.into_owned()
+}
+
+/// Picks the delimiter that splits the sampled lines into the same, largest
+/// number of fields, ignoring delimiters inside quotes.
+fn detect_delimiter(text: &str) -> u8 {
+    let mut lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    // The last sampled line may be cut off
+    if lines.len() > 1 {
+        lines.pop();
+    }
+    lines.truncate(SNIFF_LINES);
+
+    let count = |line: &str, delimiter: u8| {
+        let mut in_quotes = false;
+        line.bytes()
+            .filter(|&b| {
+                if b == b'"' {
+                    in_quotes = !in_quotes;
+                }
+                b == delimiter && !in_quotes
+            })
+            .count()
+    };
+
+    // Reversed so that ties go to the more common delimiter
+    DELIMITERS
+        .iter()
+        .rev()
+        .map(|&delimiter| {
+            let counts: Vec<usize> = lines.iter().map(|l| count(l, delimiter)).collect();
+            let consistent = counts.windows(2).all(|w| w[0] == w[1]);
+            let fields = counts.first().copied().unwrap_or(0);
+            (delimiter, consistent && fields > 0, fields)
+        })
+        .max_by_key(|&(_, consistent, fields)| (consistent, fields))
+        .filter(|&(_, _, fields)| fields > 0)
+        .map(|(delimiter, _, _)| delimiter)
+        .unwrap_or(b',')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression as GzLevel};
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_sniff_and_read_formats() {
+        let dir = tempdir().unwrap();
+
+        // Semicolon-separated, Latin-1 encoded and gzipped, with no telling extension
+        let path = dir.path().join("upload.bin");
+        let (latin1, _, _) = encoding_rs::WINDOWS_1252.encode("city;temp\nZürich;12\nMálaga;21\n");
+        let mut gz = GzEncoder::new(File::create(&path).unwrap(), GzLevel::default());
+        gz.write_all(&latin1).unwrap();
+        gz.finish().unwrap();
+
+        let reader = DatasetReader::open(&path).unwrap();
+        assert_eq!(reader.info().compression, Compression::Gzip);
+        assert_eq!(reader.info().encoding, Some(TextEncoding::Windows1252));
+        assert_eq!(reader.info().delimiter, Some(';'));
+        let df = reader.read(None).await.unwrap();
+        assert_eq!(df.shape(), (2, 2));
+        assert_eq!(df.column("city").unwrap().utf8().unwrap().get(0), Some("Zürich"));
+
+        let path = dir.path().join("events.ndjson");
+        std::fs::write(&path, "{\"a\": 1}\n{\"a\": 2}\n").unwrap();
+        let reader = DatasetReader::open(&path).unwrap();
+        assert_eq!(reader.info().format, DatasetFormat::Ndjson);
+        assert_eq!(reader.read(None).await.unwrap().height(), 2);
+
+        let path = dir.path().join("frame.arrow");
+        let mut df = df!["x" => &[1, 2, 3]].unwrap();
+        IpcWriter::new(File::create(&path).unwrap()).finish(&mut df).unwrap();
+        let reader = DatasetReader::open(&path).unwrap();
+        assert_eq!(reader.info().format, DatasetFormat::ArrowIpc);
+        assert_eq!(reader.read_head(None, 2).await.unwrap().height(), 2);
+
+        assert_eq!(detect_delimiter("a\tb\n\"x,y\"\t2\n1\t2\n"), b'\t');
+
+        // A zip archive is only a workbook if it holds one
+        let path = dir.path().join("export.xlsx");
+        let mut archive = zip::ZipWriter::new(File::create(&path).unwrap());
+        archive.start_file("data.csv", zip::write::FileOptions::default()).unwrap();
+        archive.write_all(b"a,b\n1,2\n").unwrap();
+        archive.finish().unwrap();
+        assert_eq!(workbook_kind(&path).unwrap(), None);
+        assert!(DatasetReader::open(&path).is_err());
+
+        let path = dir.path().join("upload.bin");
+        let mut archive = zip::ZipWriter::new(File::create(&path).unwrap());
+        archive.start_file("xl/workbook.xml", zip::write::FileOptions::default()).unwrap();
+        archive.finish().unwrap();
+        assert_eq!(workbook_kind(&path).unwrap(), Some(WorkbookKind::Xlsx));
+    }
+
+    #[tokio::test]
+    async fn test_read_sqlite_tables() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("shop.db");
+
+        let options = SqliteConnectOptions::new().filename(&path).create_if_missing(true);
+        let mut conn = SqliteConnection::connect_with(&options).await.unwrap();
+        sqlx::query("CREATE TABLE orders (id INTEGER, total REAL, note TEXT)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE customers (id INTEGER)").execute(&mut conn).await.unwrap();
+        sqlx::query("INSERT INTO orders VALUES (1, 9.5, 'gift'), (2, 12, NULL)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        conn.close().await.unwrap();
+
+        let reader = DatasetReader::open(&path).unwrap();
+        assert_eq!(reader.info().format, DatasetFormat::Sqlite);
+        assert_eq!(reader.tables().await.unwrap(), vec!["customers", "orders"]);
+
+        let df = reader.read(Some("orders")).await.unwrap();
+        assert_eq!(df.shape(), (2, 3));
+        assert_eq!(df.column("id").unwrap().dtype(), &DataType::Int64);
+        // Integers and reals mixed in one column become floats
+        assert_eq!(df.column("total").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(df.column("note").unwrap().null_count(), 1);
+
+        assert!(reader.read(Some("missing")).await.is_err());
+    }
+}
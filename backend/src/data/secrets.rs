@@ -0,0 +1,141 @@
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Base64-encoded 32-byte key; when unset a key is generated once and kept in `KEY_FILE`.
+const KEY_ENV: &str = "OPENMIND_SECRET_KEY";
+const KEY_FILE: &str = "data/secret.key";
+
+/// Encrypts credentials at rest with AES-256-GCM.
+///
+/// Sealed values are `base64(nonce || ciphertext || tag)`. The associated data
+/// binds a ciphertext to its owner (e.g. a connection id) so it can't be copied
+/// onto another record.
+pub struct SecretBox {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl SecretBox {
+    pub fn new(key: &[u8; 32]) -> Self {
+        let key = UnboundKey::new(&AES_256_GCM, key).expect("AES-256 key is 32 bytes");
+        Self {
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// The process-wide box, keyed from the environment or the key file.
+    pub fn global() -> Result<&'static SecretBox> {
+        static GLOBAL: OnceLock<SecretBox> = OnceLock::new();
+        if let Some(secret_box) = GLOBAL.get() {
+            return Ok(secret_box);
+        }
+        let key = load_key()?;
+        Ok(GLOBAL.get_or_init(|| SecretBox::new(&key)))
+    }
+
+    pub fn seal(&self, plaintext: &[u8], aad: &str) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("Failed to generate nonce"))?;
+
+        let mut in_out = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad.as_bytes()),
+                &mut in_out,
+            )
+            .map_err(|_| anyhow!("Failed to encrypt secret"))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&in_out);
+        Ok(STANDARD.encode(sealed))
+    }
+
+    pub fn open(&self, sealed: &str, aad: &str) -> Result<Vec<u8>> {
+        let sealed = STANDARD
+            .decode(sealed)
+            .context("Secret is not valid base64")?;
+        if sealed.len() < NONCE_LEN {
+            bail!("Secret is truncated");
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce =
+            Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("Invalid nonce"))?;
+        let mut in_out = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::from(aad.as_bytes()), &mut in_out)
+            .map_err(|_| anyhow!("Failed to decrypt secret; was the key changed?"))?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+fn load_key() -> Result<[u8; 32]> {
+    let encoded = match std::env::var(KEY_ENV) {
+        Ok(encoded) => encoded,
+        Err(_) if Path::new(KEY_FILE).exists() => std::fs::read_to_string(KEY_FILE)?,
+        Err(_) => {
+            let mut key = [0u8; 32];
+            SystemRandom::new()
+                .fill(&mut key)
+                .map_err(|_| anyhow!("Failed to generate secret key"))?;
+            let encoded = STANDARD.encode(key);
+            write_key_file(&encoded)?;
+            tracing::info!("Generated a new secret key in {}", KEY_FILE);
+            encoded
+        }
+    };
+
+    STANDARD
+        .decode(encoded.trim())
+        .context("Secret key is not valid base64")?
+        .try_into()
+        .map_err(|_| anyhow!("Secret key must be 32 bytes"))
+}
+
+fn write_key_file(encoded: &str) -> Result<()> {
+    use std::io::Write;
+
+    if let Some(dir) = Path::new(KEY_FILE).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(KEY_FILE)?.write_all(encoded.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let secret_box = SecretBox::new(&[7u8; 32]);
+        let sealed = secret_box
+            .seal(b"postgres://user:pw@db/app", "conn-1")
+            .unwrap();
+
+        assert!(!sealed.contains("pw@db"));
+        assert_eq!(
+            secret_box.open(&sealed, "conn-1").unwrap(),
+            b"postgres://user:pw@db/app"
+        );
+        // Bound to its owner and its key
+        assert!(secret_box.open(&sealed, "conn-2").is_err());
+        assert!(SecretBox::new(&[8u8; 32]).open(&sealed, "conn-1").is_err());
+    }
+}
@@ -0,0 +1,385 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Histogram {
+    /// `counts.len() + 1` edges; the last bin is closed on the right.
+    pub bin_edges: Vec<f64>,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    pub fn from_values(values: &[f64], bins: usize) -> Option<Self> {
+        let values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+        if values.is_empty() || bins == 0 {
+            return None;
+        }
+
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        // A constant column still gets a single, non-degenerate bin
+        let (min, max, bins) = if min == max {
+            (min - 0.5, max + 0.5, 1)
+        } else {
+            (min, max, bins)
+        };
+
+        let width = (max - min) / bins as f64;
+        let bin_edges = (0..=bins).map(|i| min + width * i as f64).collect();
+        let mut counts = vec![0; bins];
+
+        for v in values {
+            let idx = (((v - min) / width) as usize).min(bins - 1);
+            counts[idx] += 1;
+        }
+
+        Some(Histogram { bin_edges, counts })
+    }
+}
+
+/// Per-column distribution used for plotting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Distribution {
+    Histogram(Histogram),
+    ValueCounts { values: Vec<(String, usize)> },
+}
+
+/// Counts non-null values, most frequent first, keeping at most `limit` entries.
+pub fn value_counts<'a, I>(values: I, limit: usize) -> Vec<(String, usize)>
+where
+    I: IntoIterator<Item = Option<&'a str>>,
+{
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for v in values.into_iter().flatten() {
+        *counts.entry(v).or_insert(0) += 1;
+    }
+
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(limit);
+    counts
+}
+
+pub fn pearson(x: &[f64], y: &[f64]) -> Option<f64> {
+    let n = x.len().min(y.len());
+    if n < 2 {
+        return None;
+    }
+
+    let mean_x = x[..n].iter().sum::<f64>() / n as f64;
+    let mean_y = y[..n].iter().sum::<f64>() / n as f64;
+
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for i in 0..n {
+        let dx = x[i] - mean_x;
+        let dy = y[i] - mean_y;
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+
+    Some((cov / (var_x.sqrt() * var_y.sqrt())).clamp(-1.0, 1.0))
+}
+
+pub fn spearman(x: &[f64], y: &[f64]) -> Option<f64> {
+    pearson(&ranks(x), &ranks(y))
+}
+
+/// Ranks starting at 1, with ties given their average rank.
+pub fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for &idx in &order[i..=j] {
+            ranks[idx] = rank;
+        }
+        i = j + 1;
+    }
+    ranks
+}
+
+/// Cramér's V between two categorical variables, observed pairwise.
+pub fn cramers_v(x: &[&str], y: &[&str]) -> Option<f64> {
+    let n = x.len().min(y.len());
+    if n == 0 {
+        return None;
+    }
+
+    let mut table: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut rows: HashMap<&str, usize> = HashMap::new();
+    let mut cols: HashMap<&str, usize> = HashMap::new();
+    for i in 0..n {
+        *table.entry((x[i], y[i])).or_insert(0) += 1;
+        *rows.entry(x[i]).or_insert(0) += 1;
+        *cols.entry(y[i]).or_insert(0) += 1;
+    }
+
+    let k = rows.len().min(cols.len());
+    if k < 2 {
+        return None;
+    }
+
+    // chi2 = n * (sum(O^2 / (row * col)) - 1); empty cells contribute nothing to the sum
+    let sum: f64 = table
+        .iter()
+        .map(|((r, c), &o)| (o * o) as f64 / (rows[r] * cols[c]) as f64)
+        .sum();
+    let chi2 = n as f64 * (sum - 1.0);
+
+    Some((chi2 / (n as f64 * (k - 1) as f64)).max(0.0).sqrt().min(1.0))
+}
+
+/// Upper-tail probability of the chi-squared distribution with `dof` degrees of freedom.
+pub fn chi_squared_sf(statistic: f64, dof: f64) -> f64 {
+    if statistic <= 0.0 || dof <= 0.0 {
+        return 1.0;
+    }
+    regularized_gamma_q(dof / 2.0, statistic / 2.0)
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    // Lanczos approximation (g = 7, n = 9)
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let mut sum = COEFFS[0];
+    for (i, c) in COEFFS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Q(a, x) = Gamma(a, x) / Gamma(a), by series for small x and a continued fraction otherwise.
+fn regularized_gamma_q(a: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-14;
+    const MAX_ITER: usize = 500;
+
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut n = a;
+        for _ in 0..MAX_ITER {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+            if term.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+        let p = sum * (-x + a * x.ln() - ln_gamma(a)).exp();
+        (1.0 - p).clamp(0.0, 1.0)
+    } else {
+        // Modified Lentz's method
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..=MAX_ITER {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPS {
+                break;
+            }
+        }
+        (h * (-x + a * x.ln() - ln_gamma(a)).exp()).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationMatrix {
+    pub columns: Vec<String>,
+    /// Row-major, `values[i][j]` is the coefficient between `columns[i]` and `columns[j]`.
+    pub values: Vec<Vec<Option<f64>>>,
+}
+
+impl CorrelationMatrix {
+    /// Builds a symmetric matrix from a pairwise coefficient function.
+    pub fn build<F>(columns: Vec<String>, mut coefficient: F) -> Self
+    where
+        F: FnMut(usize, usize) -> Option<f64>,
+    {
+        let n = columns.len();
+        let mut values = vec![vec![None; n]; n];
+        for i in 0..n {
+            values[i][i] = Some(1.0);
+            for j in (i + 1)..n {
+                let c = coefficient(i, j);
+                values[i][j] = c;
+                values[j][i] = c;
+            }
+        }
+        CorrelationMatrix { columns, values }
+    }
+
+    fn pairs(&self, method: &str) -> impl Iterator<Item = CorrelatedPair> + '_ {
+        let method = method.to_string();
+        (0..self.columns.len()).flat_map(move |i| {
+            let method = method.clone();
+            ((i + 1)..self.columns.len()).filter_map(move |j| {
+                self.values[i][j].map(|coefficient| CorrelatedPair {
+                    column_a: self.columns[i].clone(),
+                    column_b: self.columns[j].clone(),
+                    method: method.clone(),
+                    coefficient,
+                })
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelatedPair {
+    pub column_a: String,
+    pub column_b: String,
+    pub method: String,
+    pub coefficient: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Correlations {
+    pub pearson: CorrelationMatrix,
+    pub spearman: CorrelationMatrix,
+    pub cramers_v: CorrelationMatrix,
+    /// Pairs whose absolute coefficient exceeds the configured threshold, strongest first.
+    pub top_pairs: Vec<CorrelatedPair>,
+}
+
+impl Correlations {
+    /// `numeric` and `categorical` hold one entry per column, aligned by row.
+    pub fn compute(
+        numeric: &[(String, Vec<Option<f64>>)],
+        categorical: &[(String, Vec<Option<String>>)],
+        threshold: f64,
+    ) -> Self {
+        let numeric_names: Vec<String> = numeric.iter().map(|(n, _)| n.clone()).collect();
+        let categorical_names: Vec<String> = categorical.iter().map(|(n, _)| n.clone()).collect();
+
+        let pearson = CorrelationMatrix::build(numeric_names.clone(), |i, j| {
+            let (x, y) = paired_numeric(&numeric[i].1, &numeric[j].1);
+            pearson(&x, &y)
+        });
+        let spearman = CorrelationMatrix::build(numeric_names, |i, j| {
+            let (x, y) = paired_numeric(&numeric[i].1, &numeric[j].1);
+            spearman(&x, &y)
+        });
+        let cramers_v = CorrelationMatrix::build(categorical_names, |i, j| {
+            let (x, y) = paired_categorical(&categorical[i].1, &categorical[j].1);
+            cramers_v(&x, &y)
+        });
+
+        let mut top_pairs: Vec<CorrelatedPair> = pearson
+            .pairs("pearson")
+            .chain(spearman.pairs("spearman"))
+            .chain(cramers_v.pairs("cramers_v"))
+            .filter(|p| p.coefficient.abs() >= threshold)
+            .collect();
+        top_pairs.sort_by(|a, b| b.coefficient.abs().total_cmp(&a.coefficient.abs()));
+
+        // A pair flagged by both Pearson and Spearman is reported once, by its strongest method
+        let mut seen = std::collections::HashSet::new();
+        top_pairs.retain(|p| seen.insert((p.column_a.clone(), p.column_b.clone())));
+
+        Correlations {
+            pearson,
+            spearman,
+            cramers_v,
+            top_pairs,
+        }
+    }
+}
+
+fn paired_numeric(x: &[Option<f64>], y: &[Option<f64>]) -> (Vec<f64>, Vec<f64>) {
+    x.iter()
+        .zip(y)
+        .filter_map(|(a, b)| match (a, b) {
+            (Some(a), Some(b)) if a.is_finite() && b.is_finite() => Some((*a, *b)),
+            _ => None,
+        })
+        .unzip()
+}
+
+fn paired_categorical<'a>(
+    x: &'a [Option<String>],
+    y: &'a [Option<String>],
+) -> (Vec<&'a str>, Vec<&'a str>) {
+    x.iter()
+        .zip(y)
+        .filter_map(|(a, b)| Some((a.as_deref()?, b.as_deref()?)))
+        .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_and_correlations() {
+        let hist = Histogram::from_values(&[1.0, 2.0, 2.0, 3.0, 4.0], 3).unwrap();
+        assert_eq!(hist.bin_edges.len(), 4);
+        assert_eq!(hist.counts.iter().sum::<usize>(), 5);
+        assert_eq!(*hist.counts.last().unwrap(), 2);
+
+        let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let y = [2.0, 4.0, 6.0, 8.0, 100.0];
+        assert!(pearson(&x, &y).unwrap() < 1.0);
+        assert!((spearman(&x, &y).unwrap() - 1.0).abs() < 1e-12);
+        assert_eq!(ranks(&[3.0, 1.0, 3.0]), vec![2.5, 1.0, 2.5]);
+
+        let a = ["x", "x", "y", "y"];
+        let b = ["p", "p", "q", "q"];
+        assert!((cramers_v(&a, &b).unwrap() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_chi_squared_sf() {
+        // Reference values from standard chi-squared tables
+        assert!((chi_squared_sf(3.841, 1.0) - 0.05).abs() < 1e-3);
+        assert!((chi_squared_sf(18.307, 10.0) - 0.05).abs() < 1e-3);
+        assert!((chi_squared_sf(0.0, 3.0) - 1.0).abs() < 1e-12);
+    }
+}
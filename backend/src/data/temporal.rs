@@ -0,0 +1,209 @@
+use chrono::{DateTime, Datelike, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const SECOND_MS: i64 = 1_000;
+const MINUTE_MS: i64 = 60 * SECOND_MS;
+const HOUR_MS: i64 = 60 * MINUTE_MS;
+const DAY_MS: i64 = 24 * HOUR_MS;
+
+/// Largest gaps reported per column.
+const MAX_REPORTED_GAPS: usize = 5;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    SubSecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+    Irregular,
+}
+
+impl Granularity {
+    fn from_step_ms(step: i64) -> Self {
+        let days = step as f64 / DAY_MS as f64;
+        match step {
+            s if s < SECOND_MS => Granularity::SubSecond,
+            s if s == SECOND_MS => Granularity::Second,
+            s if s == MINUTE_MS => Granularity::Minute,
+            s if s == HOUR_MS => Granularity::Hour,
+            s if s == DAY_MS => Granularity::Day,
+            s if s == 7 * DAY_MS => Granularity::Week,
+            _ if (28.0..=31.0).contains(&days) => Granularity::Month,
+            _ if (89.0..=92.0).contains(&days) => Granularity::Quarter,
+            _ if (365.0..=366.0).contains(&days) => Granularity::Year,
+            _ => Granularity::Irregular,
+        }
+    }
+
+    /// Steps longer than this many typical steps count as a gap; calendar
+    /// units vary in length so they get more slack.
+    fn gap_factor(&self) -> f64 {
+        match self {
+            Granularity::Month | Granularity::Quarter | Granularity::Year => 1.2,
+            _ => 1.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Monotonicity {
+    StrictlyIncreasing,
+    Increasing,
+    StrictlyDecreasing,
+    Decreasing,
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeGap {
+    pub start: String,
+    pub end: String,
+    pub duration_seconds: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemporalSummary {
+    pub range_seconds: Option<f64>,
+    pub granularity: Option<Granularity>,
+    /// Most common step between consecutive distinct timestamps
+    pub typical_step_seconds: Option<f64>,
+    pub gap_count: usize,
+    pub largest_gaps: Vec<TimeGap>,
+    /// Counts for Monday..Sunday
+    pub weekday_distribution: Vec<(String, usize)>,
+    /// Counts for hours 0..23; empty when every value is at midnight
+    pub hour_distribution: Vec<(u32, usize)>,
+    pub monotonicity: Monotonicity,
+}
+
+/// Summarizes millisecond timestamps given in row order.
+pub fn summarize(timestamps_ms: &[i64]) -> TemporalSummary {
+    let mut sorted = timestamps_ms.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let range_seconds = match (sorted.first(), sorted.last()) {
+        (Some(min), Some(max)) => Some((max - min) as f64 / 1000.0),
+        _ => None,
+    };
+
+    // Typical step = most frequent difference between consecutive distinct values
+    let diffs: Vec<i64> = sorted.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut diff_counts: HashMap<i64, usize> = HashMap::new();
+    for &d in &diffs {
+        *diff_counts.entry(d).or_insert(0) += 1;
+    }
+    let typical_step = diff_counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(step, _)| step);
+    let granularity = typical_step.map(Granularity::from_step_ms);
+
+    let mut gaps = Vec::new();
+    if let (Some(step), Some(granularity)) = (typical_step, granularity) {
+        let limit = step as f64 * granularity.gap_factor();
+        for w in sorted.windows(2) {
+            if (w[1] - w[0]) as f64 > limit {
+                gaps.push(TimeGap {
+                    start: format_ms(w[0]),
+                    end: format_ms(w[1]),
+                    duration_seconds: (w[1] - w[0]) as f64 / 1000.0,
+                });
+            }
+        }
+    }
+    let gap_count = gaps.len();
+    gaps.sort_by(|a, b| b.duration_seconds.total_cmp(&a.duration_seconds));
+    gaps.truncate(MAX_REPORTED_GAPS);
+
+    let mut weekdays = [0usize; 7];
+    let mut hours = [0usize; 24];
+    for &ts in timestamps_ms {
+        if let Some(dt) = from_ms(ts) {
+            weekdays[dt.weekday().num_days_from_monday() as usize] += 1;
+            hours[dt.hour() as usize] += 1;
+        }
+    }
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let weekday_distribution = WEEKDAYS
+        .iter()
+        .zip(weekdays)
+        .map(|(d, c)| (d.to_string(), c))
+        .collect();
+    let date_only = hours[1..].iter().all(|&c| c == 0);
+    let hour_distribution = if date_only {
+        Vec::new()
+    } else {
+        (0..24u32).zip(hours).collect()
+    };
+
+    TemporalSummary {
+        range_seconds,
+        granularity,
+        typical_step_seconds: typical_step.map(|s| s as f64 / 1000.0),
+        gap_count,
+        largest_gaps: gaps,
+        weekday_distribution,
+        hour_distribution,
+        monotonicity: monotonicity(timestamps_ms),
+    }
+}
+
+fn monotonicity(values: &[i64]) -> Monotonicity {
+    if values.len() < 2 {
+        return Monotonicity::StrictlyIncreasing;
+    }
+
+    let pairs = || values.windows(2);
+    if pairs().all(|w| w[0] < w[1]) {
+        Monotonicity::StrictlyIncreasing
+    } else if pairs().all(|w| w[0] <= w[1]) {
+        Monotonicity::Increasing
+    } else if pairs().all(|w| w[0] > w[1]) {
+        Monotonicity::StrictlyDecreasing
+    } else if pairs().all(|w| w[0] >= w[1]) {
+        Monotonicity::Decreasing
+    } else {
+        Monotonicity::None
+    }
+}
+
+fn from_ms(ts: i64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp_millis(ts).map(|dt| dt.naive_utc())
+}
+
+pub fn format_ms(ts: i64) -> String {
+    from_ms(ts)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_series_with_gap() {
+        // 2024-01-01 (a Monday) onwards, with 2024-01-04 and 2024-01-05 missing
+        let start = 1_704_067_200_000;
+        let days = [0, 1, 2, 5, 6];
+        let timestamps: Vec<i64> = days.iter().map(|d| start + d * DAY_MS).collect();
+
+        let summary = summarize(&timestamps);
+        assert_eq!(summary.granularity, Some(Granularity::Day));
+        assert_eq!(summary.gap_count, 1);
+        assert_eq!(summary.largest_gaps[0].start, "2024-01-03 00:00:00");
+        assert_eq!(summary.monotonicity, Monotonicity::StrictlyIncreasing);
+        assert_eq!(summary.weekday_distribution[0], ("Mon".to_string(), 1));
+        assert!(summary.hour_distribution.is_empty());
+        assert_eq!(summary.range_seconds, Some(6.0 * 86_400.0));
+    }
+}
@@ -0,0 +1,160 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use super::stats;
+
+/// Most frequent tokens reported per column.
+const MAX_TOP_TOKENS: usize = 10;
+
+/// Share of non-empty values that must parse as numbers to call the column numeric-as-text.
+pub const NUMERIC_STRING_THRESHOLD: f64 = 0.95;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextPattern {
+    AllDigits,
+    UuidLike,
+    JsonLike,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternMatch {
+    pub pattern: TextPattern,
+    /// Share of non-empty values matching the pattern
+    pub ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextSummary {
+    pub avg_token_count: f64,
+    pub max_token_count: usize,
+    pub top_tokens: Vec<(String, usize)>,
+    /// Share of non-null values that are empty or whitespace only
+    pub empty_rate: f64,
+    pub patterns: Vec<PatternMatch>,
+    /// Share of non-empty values that parse as numbers
+    pub numeric_string_ratio: f64,
+}
+
+impl TextSummary {
+    pub fn numbers_stored_as_strings(&self) -> bool {
+        self.numeric_string_ratio >= NUMERIC_STRING_THRESHOLD
+    }
+}
+
+pub fn summarize<'a, I>(values: I) -> TextSummary
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut total = 0usize;
+    let mut empty = 0usize;
+    let mut token_counts = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut numeric = 0usize;
+    let mut pattern_counts = [0usize; 3];
+
+    for value in values {
+        total += 1;
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            empty += 1;
+            continue;
+        }
+
+        let value_tokens: Vec<String> = trimmed
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|t| !t.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        token_counts.push(value_tokens.len());
+        tokens.extend(value_tokens);
+
+        if trimmed.replace(',', "").parse::<f64>().is_ok() {
+            numeric += 1;
+        }
+        if trimmed.bytes().all(|b| b.is_ascii_digit()) {
+            pattern_counts[0] += 1;
+        }
+        if uuid_regex().is_match(trimmed) {
+            pattern_counts[1] += 1;
+        }
+        if is_json_like(trimmed) {
+            pattern_counts[2] += 1;
+        }
+    }
+
+    let non_empty = total - empty;
+    let ratio = |count: usize| {
+        if non_empty == 0 {
+            0.0
+        } else {
+            count as f64 / non_empty as f64
+        }
+    };
+
+    let patterns = [TextPattern::AllDigits, TextPattern::UuidLike, TextPattern::JsonLike]
+        .into_iter()
+        .zip(pattern_counts)
+        .filter(|(_, count)| *count > 0)
+        .map(|(pattern, count)| PatternMatch {
+            pattern,
+            ratio: ratio(count),
+        })
+        .collect();
+
+    TextSummary {
+        avg_token_count: if token_counts.is_empty() {
+            0.0
+        } else {
+            token_counts.iter().sum::<usize>() as f64 / token_counts.len() as f64
+        },
+        max_token_count: token_counts.iter().copied().max().unwrap_or(0),
+        top_tokens: stats::value_counts(tokens.iter().map(|t| Some(t.as_str())), MAX_TOP_TOKENS),
+        empty_rate: if total == 0 {
+            0.0
+        } else {
+            empty as f64 / total as f64
+        },
+        patterns,
+        numeric_string_ratio: ratio(numeric),
+    }
+}
+
+fn uuid_regex() -> &'static Regex {
+    static UUID: OnceLock<Regex> = OnceLock::new();
+    UUID.get_or_init(|| {
+        Regex::new(r"^[0-9a-fA-F]{8}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{12}$")
+            .unwrap()
+    })
+}
+
+fn is_json_like(value: &str) -> bool {
+    let looks_like = (value.starts_with('{') && value.ends_with('}'))
+        || (value.starts_with('[') && value.ends_with(']'));
+    looks_like && serde_json::from_str::<serde_json::Value>(value).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_summary() {
+        let summary = summarize(["12", "1,024", "  ", "7"]);
+        assert!(summary.numbers_stored_as_strings());
+        assert_eq!(summary.empty_rate, 0.25);
+
+        let summary = summarize([
+            "550e8400-e29b-41d4-a716-446655440000",
+            "{\"a\": 1}",
+            "the quick fox",
+            "the lazy dog",
+        ]);
+        assert!(!summary.numbers_stored_as_strings());
+        assert_eq!(summary.top_tokens[0], ("the".to_string(), 2));
+        assert_eq!(summary.max_token_count, 5);
+        assert!(summary.patterns.iter().any(|p| p.pattern == TextPattern::UuidLike && p.ratio == 0.25));
+        assert!(summary.patterns.iter().any(|p| p.pattern == TextPattern::JsonLike));
+    }
+}
@@ -21,6 +21,12 @@ pub enum AppError {
     
     #[error("Invalid request: {0}")]
     BadRequest(String),
+    
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    
+    #[error("Internal error: {0}")]
+    Internal(#[from] anyhow::Error),
 }
 
 #[derive(Serialize)]
@@ -28,13 +34,20 @@ struct ErrorResponse {
     error: String,
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let status = match &self {
+impl AppError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
-        };
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
 
         let body = ErrorResponse {
             error: self.to_string(),
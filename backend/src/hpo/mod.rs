@@ -0,0 +1,186 @@
+//! Hyperparameter optimization: studies tuning a training script.
+//!
+//! A study is an experiment whose child experiments are its trials. The study
+//! runs as a job that samples each trial's hyperparameters, queues the trial as
+//! a training job passing them as `--<name> <value>`, and reads back the metric
+//! the trial reports to decide what to try next. With a pruner, trials whose
+//! intermediate values lag behind the others are cancelled early.
+//!
+//! ```text
+//! study job ──sample──> trial job ──metrics──> study job ──sample──> ...
+//! ```
+//! Everything a study knows lives in its trials, so a study interrupted by a
+//! restart resumes where it was.
+
+pub mod pruner;
+pub mod sampler;
+pub mod space;
+pub mod study;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+use crate::tracking::MetricGoal;
+use pruner::Pruner;
+use sampler::{Sampler, Strategy, DEFAULT_STARTUP_TRIALS};
+use space::SearchSpace;
+
+pub const DEFAULT_TRIALS: usize = 20;
+pub const MAX_TRIALS: usize = 1000;
+pub const DEFAULT_PARALLELISM: usize = 2;
+pub const MAX_PARALLELISM: usize = 32;
+/// Values the grid strategy tries per numeric parameter without a step.
+pub const DEFAULT_GRID_RESOLUTION: usize = 5;
+/// Points a grid search may have, beyond which it could never finish.
+pub const MAX_GRID_SIZE: usize = 1_000_000;
+
+/// What a study tunes and how.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StudyConfig {
+    /// Script each trial runs; it should report `metric` to its run, e.g.
+    /// with `track.log_metric`, once per step for pruning
+    pub script_path: String,
+    /// Dataset passed to every trial as `--dataset`
+    pub dataset_id: Option<String>,
+    /// Arguments passed to every trial before its hyperparameters
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub space: SearchSpace,
+    pub metric: String,
+    /// Defaults to the metric's usual goal, e.g. minimizing a loss
+    pub goal: Option<MetricGoal>,
+    #[serde(default)]
+    pub strategy: Strategy,
+    #[serde(default = "default_trials")]
+    pub n_trials: usize,
+    /// Trials running at once
+    #[serde(default = "default_parallelism")]
+    pub parallelism: usize,
+    pub pruner: Option<Pruner>,
+    /// Seeds the sampler; a random seed is picked and recorded if not given
+    pub seed: Option<u64>,
+    #[serde(default = "default_grid_resolution")]
+    pub grid_resolution: usize,
+}
+
+fn default_trials() -> usize {
+    DEFAULT_TRIALS
+}
+
+fn default_parallelism() -> usize {
+    DEFAULT_PARALLELISM
+}
+
+fn default_grid_resolution() -> usize {
+    DEFAULT_GRID_RESOLUTION
+}
+
+impl StudyConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.metric.trim().is_empty() {
+            return Err(AppError::BadRequest("A study needs a metric".to_string()));
+        }
+        if !(1..=MAX_TRIALS).contains(&self.n_trials) {
+            return Err(AppError::BadRequest(format!(
+                "n_trials must be between 1 and {}",
+                MAX_TRIALS
+            )));
+        }
+        if !(1..=MAX_PARALLELISM).contains(&self.parallelism) {
+            return Err(AppError::BadRequest(format!(
+                "parallelism must be between 1 and {}",
+                MAX_PARALLELISM
+            )));
+        }
+        if self.grid_resolution < 2 {
+            return Err(AppError::BadRequest(
+                "grid_resolution must be at least 2".to_string(),
+            ));
+        }
+        self.space.validate()?;
+        let grid_fits = matches!(
+            self.space.grid_size(self.grid_resolution),
+            Some(size) if size <= MAX_GRID_SIZE
+        );
+        if self.strategy == Strategy::Grid && !grid_fits {
+            return Err(AppError::BadRequest(format!(
+                "The grid has more than {} points; use larger steps or another strategy",
+                MAX_GRID_SIZE
+            )));
+        }
+        if let Some(pruner) = &self.pruner {
+            pruner.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn goal(&self) -> MetricGoal {
+        self.goal
+            .unwrap_or_else(|| MetricGoal::for_key(&self.metric))
+    }
+
+    pub fn sampler(&self) -> Sampler {
+        Sampler {
+            strategy: self.strategy,
+            goal: self.goal(),
+            seed: self.seed.unwrap_or_default(),
+            grid_resolution: self.grid_resolution,
+            startup_trials: DEFAULT_STARTUP_TRIALS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_study_config() {
+        let config: StudyConfig = serde_json::from_value(serde_json::json!({
+            "script_path": "train.py",
+            "metric": "val_loss",
+            "space": {"lr": {"type": "log_uniform", "low": 1e-4, "high": 0.1}},
+            "pruner": {"type": "median"}
+        }))
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.goal(), MetricGoal::Minimize);
+        assert_eq!(config.strategy, Strategy::Tpe);
+        assert_eq!(config.n_trials, DEFAULT_TRIALS);
+        assert_eq!(
+            config.pruner,
+            Some(Pruner::Median {
+                startup_trials: 5,
+                warmup_steps: 0
+            })
+        );
+
+        let invalid = |change: fn(&mut StudyConfig)| {
+            let mut config = config.clone();
+            change(&mut config);
+            config.validate().is_err()
+        };
+        assert!(invalid(|config| config.n_trials = 0));
+        assert!(invalid(|config| config.space = SearchSpace::default()));
+        assert!(invalid(|config| {
+            config.space =
+                serde_json::from_str(r#"{"lr": {"type": "log_uniform", "low": 0, "high": 1}}"#)
+                    .unwrap()
+        }));
+        assert!(invalid(|config| {
+            config.strategy = Strategy::Grid;
+            config.space = serde_json::from_str(
+                r#"{"lr": {"type": "float", "low": 0, "high": 1, "step": 1e-9}}"#,
+            )
+            .unwrap()
+        }));
+        assert!(invalid(|config| {
+            config.strategy = Strategy::Grid;
+            config.space = serde_json::from_value(serde_json::json!({
+                "seed": {"type": "int", "low": i64::MIN, "high": i64::MAX, "step": 1},
+                "n": {"type": "int", "low": 0, "high": i64::MAX, "step": 1}
+            }))
+            .unwrap()
+        }));
+    }
+}
@@ -0,0 +1,226 @@
+//! Pruners stopping trials whose intermediate results, the values of the
+//! study's metric logged at each step, show they are unlikely to win.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+use crate::tracking::MetricGoal;
+
+/// A trial's intermediate values as `(step, value)`, ordered by step.
+pub type Curve = [(i64, f64)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Pruner {
+    /// Stops a trial whose best value so far is worse than the median of the
+    /// other trials' values at the same step
+    Median {
+        /// Trials that must have reached a step before any is pruned at it
+        #[serde(default = "default_startup_trials")]
+        startup_trials: usize,
+        /// Steps every trial runs before it may be pruned
+        #[serde(default)]
+        warmup_steps: i64,
+    },
+    /// Checks trials at steps `min_steps`, `min_steps * reduction_factor`,
+    /// `min_steps * reduction_factor^2`, ... and stops those not in the top
+    /// `1 / reduction_factor` of the trials that reached the same step
+    SuccessiveHalving {
+        #[serde(default = "default_min_steps")]
+        min_steps: i64,
+        #[serde(default = "default_reduction_factor")]
+        reduction_factor: i64,
+    },
+}
+
+fn default_startup_trials() -> usize {
+    5
+}
+
+fn default_min_steps() -> i64 {
+    1
+}
+
+fn default_reduction_factor() -> i64 {
+    3
+}
+
+impl Pruner {
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            Pruner::Median { warmup_steps, .. } if warmup_steps < 0 => Err(AppError::BadRequest(
+                "warmup_steps can't be negative".to_string(),
+            )),
+            Pruner::SuccessiveHalving { min_steps, .. } if min_steps < 1 => Err(
+                AppError::BadRequest("min_steps must be at least 1".to_string()),
+            ),
+            Pruner::SuccessiveHalving {
+                reduction_factor, ..
+            } if reduction_factor < 2 => Err(AppError::BadRequest(
+                "reduction_factor must be at least 2".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Why `trial` should stop given the curves of the study's other trials,
+    /// or `None` to let it run on.
+    pub fn prune(&self, goal: MetricGoal, trial: &Curve, others: &[&Curve]) -> Option<String> {
+        let &(step, _) = trial.last()?;
+        match *self {
+            Pruner::Median {
+                startup_trials,
+                warmup_steps,
+            } => {
+                if step < warmup_steps {
+                    return None;
+                }
+                let mut values: Vec<f64> = others
+                    .iter()
+                    .filter_map(|curve| value_at(curve, step))
+                    .collect();
+                if values.is_empty() || values.len() < startup_trials {
+                    return None;
+                }
+                values.sort_by(f64::total_cmp);
+                let mid = values.len() / 2;
+                let median = if values.len().is_multiple_of(2) {
+                    (values[mid - 1] + values[mid]) / 2.0
+                } else {
+                    values[mid]
+                };
+                let best = trial
+                    .iter()
+                    .map(|&(_, value)| value)
+                    .reduce(|a, b| better(goal, a, b))?;
+                (better(goal, best, median) != best).then(|| {
+                    format!(
+                        "best value {} by step {} is worse than the median {} of {} trials",
+                        best,
+                        step,
+                        median,
+                        values.len()
+                    )
+                })
+            }
+            Pruner::SuccessiveHalving {
+                min_steps,
+                reduction_factor,
+            } => {
+                // The highest rung the trial has reached
+                let mut rung = min_steps;
+                if step < rung {
+                    return None;
+                }
+                while rung.saturating_mul(reduction_factor) <= step {
+                    rung *= reduction_factor;
+                }
+                let value = value_at(trial, rung)?;
+                let mut values: Vec<f64> = others
+                    .iter()
+                    .filter_map(|curve| value_at(curve, rung))
+                    .chain([value])
+                    .collect();
+                values.sort_by(|&a, &b| match goal {
+                    MetricGoal::Minimize => a.total_cmp(&b),
+                    MetricGoal::Maximize => b.total_cmp(&a),
+                });
+                let promoted = (values.len() / reduction_factor as usize).max(1);
+                let cutoff = values[promoted - 1];
+                (better(goal, value, cutoff) != value).then(|| {
+                    format!(
+                        "value {} at step {} is not in the top {} of {} trials",
+                        value,
+                        rung,
+                        promoted,
+                        values.len()
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// The last value logged at or before `step`, if the curve has reached it.
+fn value_at(curve: &Curve, step: i64) -> Option<f64> {
+    if curve.last()?.0 < step {
+        return None;
+    }
+    curve
+        .iter()
+        .take_while(|&&(s, _)| s <= step)
+        .last()
+        .map(|&(_, value)| value)
+}
+
+/// The better of two values; `a` on a tie.
+fn better(goal: MetricGoal, a: f64, b: f64) -> f64 {
+    match goal {
+        MetricGoal::Minimize if b < a => b,
+        MetricGoal::Maximize if b > a => b,
+        _ => a,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pruners() {
+        let others: Vec<Vec<(i64, f64)>> = vec![
+            vec![(0, 0.9), (1, 0.6), (2, 0.4), (3, 0.3)],
+            vec![(0, 0.8), (1, 0.5), (2, 0.45), (3, 0.4)],
+            vec![(0, 0.7), (1, 0.7), (2, 0.6)],
+        ];
+        let others: Vec<&Curve> = others.iter().map(Vec::as_slice).collect();
+
+        let median = Pruner::Median {
+            startup_trials: 3,
+            warmup_steps: 1,
+        };
+        // Losses at step 2 are 0.4, 0.45 and 0.6
+        assert!(median
+            .prune(
+                MetricGoal::Minimize,
+                &[(0, 1.0), (1, 0.8), (2, 0.5)],
+                &others
+            )
+            .is_some());
+        assert!(median
+            .prune(
+                MetricGoal::Minimize,
+                &[(0, 1.0), (1, 0.8), (2, 0.42)],
+                &others
+            )
+            .is_none());
+        // Still warming up, and too few trials reached step 3
+        assert!(median
+            .prune(MetricGoal::Minimize, &[(0, 5.0)], &others)
+            .is_none());
+        assert!(median
+            .prune(MetricGoal::Minimize, &[(0, 1.0), (3, 0.9)], &others)
+            .is_none());
+
+        let halving = Pruner::SuccessiveHalving {
+            min_steps: 1,
+            reduction_factor: 2,
+        };
+        // At rung 2, 0.4 and 0.45 are the top 2 of 4
+        assert!(halving
+            .prune(MetricGoal::Minimize, &[(1, 0.5), (2, 0.5)], &others)
+            .is_some());
+        assert!(halving
+            .prune(MetricGoal::Minimize, &[(1, 0.5), (2, 0.41)], &others)
+            .is_none());
+        assert!(halving
+            .prune(MetricGoal::Maximize, &[(0, 0.1)], &others)
+            .is_none());
+        assert!(Pruner::SuccessiveHalving {
+            min_steps: 1,
+            reduction_factor: 1
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! Samplers choosing the hyperparameters of a study's next trial.
+//!
+//! The TPE sampler (Tree-structured Parzen Estimator) splits finished trials
+//! into the best quarter and the rest, fits a kernel density estimate of each
+//! parameter to both groups, and picks, out of candidates drawn from the good
+//! estimate, the one most likely under it relative to the bad one. Parameters
+//! are modelled independently.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::f64::consts::PI;
+
+use super::space::{Param, Params, SearchSpace};
+use crate::tracking::MetricGoal;
+
+/// Trials sampled at random before TPE has enough results to model.
+pub const DEFAULT_STARTUP_TRIALS: usize = 10;
+/// Share of finished trials TPE treats as good.
+const GAMMA: f64 = 0.25;
+/// Candidates TPE draws from the good estimate for each parameter.
+const EI_CANDIDATES: usize = 24;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    Random,
+    /// Every combination of the parameters' grid values, in order
+    Grid,
+    #[default]
+    Tpe,
+}
+
+/// A finished trial's hyperparameters and result.
+#[derive(Debug, Clone)]
+pub struct Observation<'a> {
+    pub params: &'a Params,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    pub strategy: Strategy,
+    pub goal: MetricGoal,
+    pub seed: u64,
+    pub grid_resolution: usize,
+    pub startup_trials: usize,
+}
+
+impl Sampler {
+    /// Hyperparameters of trial `number`, given the trials finished so far;
+    /// `None` once the grid is exhausted. The same trial of the same study
+    /// always gets the same draws, so a resumed study picks up where it was.
+    pub fn sample(
+        &self,
+        space: &SearchSpace,
+        number: usize,
+        history: &[Observation],
+    ) -> Option<Params> {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(number as u64));
+        match self.strategy {
+            Strategy::Random => Some(space.sample_uniform(&mut rng)),
+            Strategy::Grid => space.grid_point(number, self.grid_resolution),
+            Strategy::Tpe if history.len() < self.startup_trials.max(2) => {
+                Some(space.sample_uniform(&mut rng))
+            }
+            Strategy::Tpe => Some(self.tpe(space, history, &mut rng)),
+        }
+    }
+
+    fn tpe(&self, space: &SearchSpace, history: &[Observation], rng: &mut StdRng) -> Params {
+        let mut sorted: Vec<&Observation> = history.iter().collect();
+        sorted.sort_by(|a, b| match self.goal {
+            MetricGoal::Minimize => a.value.total_cmp(&b.value),
+            MetricGoal::Maximize => b.value.total_cmp(&a.value),
+        });
+        let n_good = ((GAMMA * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len() - 1);
+        let (good, bad) = sorted.split_at(n_good);
+
+        space
+            .params()
+            .map(|(name, param)| {
+                let value = match param {
+                    Param::Categorical { choices } => tpe_choice(name, choices, good, bad, rng),
+                    param => tpe_number(name, param, good, bad, rng),
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// The choice, out of candidates drawn by frequency among good trials, most
+/// frequent among good trials relative to bad ones.
+fn tpe_choice(
+    name: &str,
+    choices: &[Value],
+    good: &[&Observation],
+    bad: &[&Observation],
+    rng: &mut StdRng,
+) -> Value {
+    let frequencies = |trials: &[&Observation]| {
+        // Starting from one keeps choices no trial tried in play
+        let mut counts = vec![1.0; choices.len()];
+        for trial in trials {
+            if let Some(i) = choices
+                .iter()
+                .position(|c| trial.params.get(name) == Some(c))
+            {
+                counts[i] += 1.0;
+            }
+        }
+        let total: f64 = counts.iter().sum();
+        counts
+            .into_iter()
+            .map(|count| count / total)
+            .collect::<Vec<f64>>()
+    };
+    let (l, g) = (frequencies(good), frequencies(bad));
+    let best = (0..EI_CANDIDATES)
+        .map(|_| pick(&l, rng))
+        .max_by(|&a, &b| (l[a] / g[a]).total_cmp(&(l[b] / g[b])))
+        .unwrap_or(0);
+    choices[best].clone()
+}
+
+/// The value, out of candidates drawn from the good trials' estimate, most
+/// likely under it relative to the bad trials' one.
+fn tpe_number(
+    name: &str,
+    param: &Param,
+    good: &[&Observation],
+    bad: &[&Observation],
+    rng: &mut StdRng,
+) -> Value {
+    let bounds = param.bounds().unwrap_or_default();
+    let xs = |trials: &[&Observation]| -> Vec<f64> {
+        trials
+            .iter()
+            .filter_map(|trial| param.to_internal(trial.params.get(name)?))
+            .collect()
+    };
+    let (l, g) = (
+        Parzen::new(&xs(good), bounds),
+        Parzen::new(&xs(bad), bounds),
+    );
+    let score = |x: f64| l.log_pdf(x) - g.log_pdf(x);
+    let best = (0..EI_CANDIDATES)
+        .map(|_| l.sample(rng))
+        .max_by(|&a, &b| score(a).total_cmp(&score(b)))
+        .unwrap_or(bounds.0);
+    param.from_internal(best)
+}
+
+/// Index drawn with the given probabilities.
+fn pick(probabilities: &[f64], rng: &mut impl Rng) -> usize {
+    let mut u: f64 = rng.gen();
+    for (i, p) in probabilities.iter().enumerate() {
+        if u < *p {
+            return i;
+        }
+        u -= p;
+    }
+    probabilities.len() - 1
+}
+
+/// Equally weighted Gaussians on each observation, plus a wide one on the
+/// middle of the range as a prior, restricted to the range.
+struct Parzen {
+    low: f64,
+    high: f64,
+    components: Vec<(f64, f64)>,
+}
+
+impl Parzen {
+    fn new(xs: &[f64], (low, high): (f64, f64)) -> Self {
+        let range = (high - low).max(f64::EPSILON);
+        let mut components = vec![((low + high) / 2.0, range)];
+        if !xs.is_empty() {
+            let n = xs.len() as f64;
+            let mean = xs.iter().sum::<f64>() / n;
+            let std = (xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt();
+            // Silverman's rule of thumb, kept from collapsing onto a point
+            let bandwidth = (1.06 * std * n.powf(-0.2)).clamp(range / 100.0, range);
+            components.extend(xs.iter().map(|&x| (x, bandwidth)));
+        }
+        Self {
+            low,
+            high,
+            components,
+        }
+    }
+
+    fn sample(&self, rng: &mut impl Rng) -> f64 {
+        let (mu, sigma) = self.components[rng.gen_range(0..self.components.len())];
+        // Box-Muller
+        let (u1, u2): (f64, f64) = (1.0 - rng.gen::<f64>(), rng.gen());
+        let z = (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos();
+        (mu + sigma * z).clamp(self.low, self.high)
+    }
+
+    fn log_pdf(&self, x: f64) -> f64 {
+        let density: f64 = self
+            .components
+            .iter()
+            .map(|&(mu, sigma)| {
+                (-0.5 * ((x - mu) / sigma).powi(2)).exp() / (sigma * (2.0 * PI).sqrt())
+            })
+            .sum::<f64>()
+            / self.components.len() as f64;
+        density.max(f64::MIN_POSITIVE).ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn space() -> SearchSpace {
+        serde_json::from_value(json!({
+            "depth": {"type": "int", "low": 2, "high": 10, "step": 2},
+            "lr": {"type": "log_uniform", "low": 1e-4, "high": 1.0},
+            "loss": {"type": "categorical", "choices": ["gini", "entropy"]}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_samplers() {
+        let space = space();
+        let sampler = |strategy| Sampler {
+            strategy,
+            goal: MetricGoal::Minimize,
+            seed: 7,
+            grid_resolution: 3,
+            startup_trials: 4,
+        };
+
+        // Grid: 5 depths x 2 losses x 3 learning rates, by name with the last
+        // varying fastest
+        let grid = sampler(Strategy::Grid);
+        assert_eq!(space.grid_size(3), Some(30));
+        let first = grid.sample(&space, 0, &[]).unwrap();
+        assert_eq!(first["depth"], 2);
+        assert_eq!(first["loss"], "gini");
+        let lr = grid.sample(&space, 1, &[]).unwrap()["lr"].as_f64().unwrap();
+        assert!((lr - 0.01).abs() < 1e-12);
+        assert_eq!(grid.sample(&space, 3, &[]).unwrap()["loss"], "entropy");
+        assert_eq!(grid.sample(&space, 29, &[]).unwrap()["depth"], 10);
+        assert!(grid.sample(&space, 30, &[]).is_none());
+        let wide: SearchSpace = serde_json::from_value(json!({
+            "seed": {"type": "int", "low": i64::MIN, "high": i64::MAX}
+        }))
+        .unwrap();
+        assert_eq!(wide.grid_size(3), Some(3));
+        assert_eq!(wide.grid_point(0, 3).unwrap()["seed"], i64::MIN);
+        assert_eq!(wide.grid_point(2, 3).unwrap()["seed"], i64::MAX);
+
+        let random = sampler(Strategy::Random);
+        assert_eq!(random.sample(&space, 3, &[]), random.sample(&space, 3, &[]));
+        for number in 0..50 {
+            let params = random.sample(&space, number, &[]).unwrap();
+            let depth = params["depth"].as_i64().unwrap();
+            assert!((2..=10).contains(&depth) && depth % 2 == 0);
+            let lr = params["lr"].as_f64().unwrap();
+            assert!((1e-4..=1.0).contains(&lr));
+        }
+
+        // Trials with a small learning rate and entropy did best, so TPE
+        // should mostly propose those
+        let params: Vec<Params> = (0..40)
+            .map(|n| random.sample(&space, n, &[]).unwrap())
+            .collect();
+        let history: Vec<Observation> = params
+            .iter()
+            .map(|params| Observation {
+                params,
+                value: params["lr"].as_f64().unwrap().ln()
+                    + if params["loss"] == "entropy" {
+                        0.0
+                    } else {
+                        5.0
+                    },
+            })
+            .collect();
+        let tpe = sampler(Strategy::Tpe);
+        let proposals: Vec<Params> = (40..60)
+            .map(|n| tpe.sample(&space, n, &history).unwrap())
+            .collect();
+        let small_lr = proposals
+            .iter()
+            .filter(|params| params["lr"].as_f64().unwrap() < 1e-2)
+            .count();
+        let entropy = proposals
+            .iter()
+            .filter(|params| params["loss"] == "entropy")
+            .count();
+        assert!(
+            small_lr >= 14,
+            "{} of 20 proposals had a small learning rate",
+            small_lr
+        );
+        assert!(entropy >= 14, "{} of 20 proposals used entropy", entropy);
+    }
+}
@@ -0,0 +1,281 @@
+//! Search spaces: the hyperparameters a study tunes and the values each may take.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::error::{AppError, Result};
+
+/// Values of a trial's hyperparameters, by name.
+pub type Params = serde_json::Map<String, Value>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Param {
+    /// Integers in `[low, high]`, in multiples of `step` from `low` if given
+    Int {
+        low: i64,
+        high: i64,
+        step: Option<i64>,
+    },
+    /// Floats in `[low, high]`, in multiples of `step` from `low` if given
+    Float {
+        low: f64,
+        high: f64,
+        step: Option<f64>,
+    },
+    /// Positive floats in `[low, high]`, uniform on a log scale, such as
+    /// learning rates
+    LogUniform {
+        low: f64,
+        high: f64,
+    },
+    Categorical {
+        choices: Vec<Value>,
+    },
+}
+
+impl Param {
+    fn validate(&self, name: &str) -> Result<()> {
+        let invalid = |reason: &str| {
+            Err(AppError::BadRequest(format!(
+                "Invalid hyperparameter '{}': {}",
+                name, reason
+            )))
+        };
+        match *self {
+            Param::Int { low, high, step } => {
+                if low > high {
+                    return invalid("low is above high");
+                }
+                if step.is_some_and(|step| step <= 0) {
+                    return invalid("step must be positive");
+                }
+            }
+            Param::Float { low, high, step } => {
+                if !(low.is_finite() && high.is_finite()) || low > high {
+                    return invalid("low and high must be finite with low at most high");
+                }
+                if step.is_some_and(|step| !(step.is_finite() && step > 0.0)) {
+                    return invalid("step must be positive");
+                }
+            }
+            Param::LogUniform { low, high } => {
+                if !(low > 0.0 && high.is_finite()) || low > high {
+                    return invalid("low and high must be positive with low at most high");
+                }
+            }
+            Param::Categorical { ref choices } => {
+                if choices.is_empty() {
+                    return invalid("choices is empty");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Range of a numeric parameter in the space samplers work in: log scale
+    /// for log-uniform ones. `None` for categorical ones.
+    pub fn bounds(&self) -> Option<(f64, f64)> {
+        match *self {
+            Param::Int { low, high, .. } => Some((low as f64, high as f64)),
+            Param::Float { low, high, .. } => Some((low, high)),
+            Param::LogUniform { low, high } => Some((low.ln(), high.ln())),
+            Param::Categorical { .. } => None,
+        }
+    }
+
+    /// A value of this parameter in the samplers' space.
+    pub fn to_internal(&self, value: &Value) -> Option<f64> {
+        let value = value.as_f64()?;
+        match self {
+            Param::Int { .. } | Param::Float { .. } => Some(value),
+            Param::LogUniform { .. } => (value > 0.0).then(|| value.ln()),
+            Param::Categorical { .. } => None,
+        }
+    }
+
+    /// The value nearest `x` of the samplers' space that this parameter takes.
+    pub fn from_internal(&self, x: f64) -> Value {
+        match *self {
+            Param::Int { low, high, step } => {
+                let step = step.unwrap_or(1) as f64;
+                let steps = ((x - low as f64) / step).round();
+                let value = (low as f64 + steps * step) as i64;
+                let value = if value > high {
+                    value - step as i64
+                } else {
+                    value
+                };
+                value.clamp(low, high).into()
+            }
+            Param::Float { low, high, step } => {
+                let value = match step {
+                    Some(step) => {
+                        let value = low + ((x - low) / step).round() * step;
+                        if value > high {
+                            value - step
+                        } else {
+                            value
+                        }
+                    }
+                    None => x,
+                };
+                value.clamp(low, high).into()
+            }
+            Param::LogUniform { low, high } => x.exp().clamp(low, high).into(),
+            Param::Categorical { ref choices } => {
+                let index = (x.round().max(0.0) as usize).min(choices.len() - 1);
+                choices[index].clone()
+            }
+        }
+    }
+
+    pub fn sample_uniform(&self, rng: &mut impl Rng) -> Value {
+        match self {
+            Param::Categorical { choices } => choices[rng.gen_range(0..choices.len())].clone(),
+            param => {
+                let (low, high) = param.bounds().unwrap_or_default();
+                param.from_internal(if low < high {
+                    rng.gen_range(low..=high)
+                } else {
+                    low
+                })
+            }
+        }
+    }
+
+    /// Number of values the grid strategy tries: every choice or step, or
+    /// `resolution` evenly spaced values of a parameter without steps. Counted
+    /// without building the values; `None` if it doesn't fit a `usize`.
+    pub fn grid_len(&self, resolution: usize) -> Option<usize> {
+        let resolution = resolution.max(2);
+        match *self {
+            Param::Int { low, high, step } => {
+                // i128 holds the span of any pair of i64s
+                let count = (high as i128 - low as i128) / step.unwrap_or(1) as i128 + 1;
+                if step.is_none() && count > resolution as i128 {
+                    return Some(resolution);
+                }
+                usize::try_from(count).ok()
+            }
+            Param::Float {
+                low,
+                high,
+                step: Some(step),
+            } => {
+                let count = ((high - low) / step).floor() + 1.0;
+                (count.is_finite() && count <= usize::MAX as f64).then_some(count as usize)
+            }
+            Param::Categorical { ref choices } => Some(choices.len()),
+            _ => {
+                let (low, high) = self.bounds().unwrap_or_default();
+                Some(if low == high { 1 } else { resolution })
+            }
+        }
+    }
+
+    /// The `index`th value the grid strategy tries, in increasing order for
+    /// numeric parameters; `None` past the last one.
+    pub fn grid_value(&self, index: usize, resolution: usize) -> Option<Value> {
+        let len = self.grid_len(resolution)?;
+        if index >= len {
+            return None;
+        }
+        let evenly_spaced = |(low, high): (f64, f64)| {
+            let x = if len == 1 {
+                low
+            } else {
+                low + (high - low) * index as f64 / (len - 1) as f64
+            };
+            self.from_internal(x)
+        };
+        Some(match *self {
+            Param::Int { low, high, step } => {
+                let all = (high as i128 - low as i128) / step.unwrap_or(1) as i128 + 1;
+                if all == len as i128 {
+                    let value = low as i128 + index as i128 * step.unwrap_or(1) as i128;
+                    // At most `high`, so it fits
+                    (value as i64).into()
+                } else {
+                    evenly_spaced((low as f64, high as f64))
+                }
+            }
+            Param::Float {
+                low,
+                step: Some(step),
+                ..
+            } => (low + index as f64 * step).into(),
+            Param::Categorical { ref choices } => choices[index].clone(),
+            _ => evenly_spaced(self.bounds().unwrap_or_default()),
+        })
+    }
+}
+
+/// Hyperparameters by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SearchSpace(pub BTreeMap<String, Param>);
+
+impl SearchSpace {
+    pub fn validate(&self) -> Result<()> {
+        if self.0.is_empty() {
+            return Err(AppError::BadRequest(
+                "The search space has no hyperparameters".to_string(),
+            ));
+        }
+        for (name, param) in &self.0 {
+            param.validate(name)?;
+        }
+        Ok(())
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = (&String, &Param)> {
+        self.0.iter()
+    }
+
+    pub fn sample_uniform(&self, rng: &mut impl Rng) -> Params {
+        self.params()
+            .map(|(name, param)| (name.clone(), param.sample_uniform(rng)))
+            .collect()
+    }
+
+    /// Number of points on the grid; `None` if it doesn't fit a `usize`.
+    pub fn grid_size(&self, resolution: usize) -> Option<usize> {
+        self.params().try_fold(1usize, |size, (_, param)| {
+            size.checked_mul(param.grid_len(resolution)?)
+        })
+    }
+
+    /// The `index`th point of the grid, with the last parameter varying
+    /// fastest; `None` past its end.
+    pub fn grid_point(&self, index: usize, resolution: usize) -> Option<Params> {
+        if index >= self.grid_size(resolution)? {
+            return None;
+        }
+        let mut rest = index;
+        let mut point: Vec<(String, Value)> = Vec::with_capacity(self.0.len());
+        for (name, param) in self.0.iter().rev() {
+            let len = param.grid_len(resolution)?;
+            point.push((name.clone(), param.grid_value(rest % len, resolution)?));
+            rest /= len;
+        }
+        Some(point.into_iter().rev().collect())
+    }
+}
+
+/// Command-line arguments passing `params` to a trial's script, as
+/// `--<name> <value>`.
+pub fn to_args(params: &Params) -> Vec<String> {
+    params
+        .iter()
+        .flat_map(|(name, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            [format!("--{}", name), value]
+        })
+        .collect()
+}
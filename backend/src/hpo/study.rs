@@ -0,0 +1,318 @@
+//! Running a study: launching its trials as jobs, feeding their results back
+//! to the sampler and pruning trials that fall behind.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+
+use super::pruner::Curve;
+use super::sampler::Observation;
+use super::space::{self, Params};
+use super::StudyConfig;
+use crate::error::{AppError, Result};
+use crate::jobs::{self, JobError, JobKind, TrainingJob};
+use crate::models::{Database, Experiment, Job};
+use crate::tracking::leaderboard::{Leaderboard, LeaderboardEntry};
+use crate::tracking::status::{self, ExperimentStatus};
+
+/// How often a study checks on its trials.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A study's trials ranked by its metric, and the best completed one.
+#[derive(Debug, Serialize)]
+pub struct StudyReport {
+    pub experiment: Experiment,
+    pub config: StudyConfig,
+    pub best: Option<LeaderboardEntry>,
+    pub trials: Leaderboard,
+}
+
+/// A trial as the study sees it.
+struct Trial {
+    experiment: Experiment,
+    status: ExperimentStatus,
+    params: Params,
+    /// The metric's latest value
+    value: Option<f64>,
+}
+
+impl Trial {
+    fn new(experiment: Experiment, metric: &str) -> Self {
+        let parse = |json: Option<&str>| {
+            json.and_then(|json| serde_json::from_str::<serde_json::Map<String, Value>>(json).ok())
+                .unwrap_or_default()
+        };
+        let value = parse(experiment.metrics.as_deref())
+            .get(metric)
+            .and_then(Value::as_f64)
+            .filter(|value| value.is_finite());
+        Self {
+            status: experiment
+                .status
+                .parse()
+                .unwrap_or(ExperimentStatus::Pending),
+            params: parse(experiment.parameters.as_deref()),
+            value,
+            experiment,
+        }
+    }
+}
+
+/// Creates a study's experiment and queues the study.
+pub async fn create(
+    db: &Database,
+    name: &str,
+    mut config: StudyConfig,
+) -> Result<(Job, Option<Experiment>)> {
+    config.validate()?;
+    config.script_path = jobs::resolve_script(&jobs::workspace(), &config.script_path)?;
+    if let Some(dataset_id) = &config.dataset_id {
+        db.get_dataset(dataset_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Dataset {} not found", dataset_id)))?;
+    }
+    // Recorded, so a resumed study samples the same trials
+    config.seed.get_or_insert_with(rand::random);
+
+    let parameters = serde_json::to_value(&config).map_err(|e| AppError::Internal(e.into()))?;
+    let experiment = db
+        .create_experiment(name, None, config.dataset_id.as_deref(), Some(parameters))
+        .await?;
+    jobs::enqueue(db, JobKind::Study, &config, Some(&experiment.id)).await
+}
+
+/// The study's trials and configuration; `None` if there is no such
+/// experiment or it isn't a study.
+pub async fn report(db: &Database, id: &str) -> Result<Option<StudyReport>> {
+    let Some(experiment) = db.get_experiment(id).await? else {
+        return Ok(None);
+    };
+    let Some(config) = experiment
+        .parameters
+        .as_deref()
+        .and_then(|parameters| serde_json::from_str::<StudyConfig>(parameters).ok())
+    else {
+        return Ok(None);
+    };
+
+    let runs = db.list_child_experiments(id).await?;
+    let trials = Leaderboard::rank_by(id, &config.metric, config.goal(), runs);
+    // Pruned trials have a value too, but only from part of a run
+    let best = trials
+        .entries
+        .iter()
+        .find(|entry| entry.rank.is_some() && entry.status == ExperimentStatus::Completed.as_str())
+        .cloned();
+    Ok(Some(StudyReport {
+        experiment,
+        config,
+        best,
+        trials,
+    }))
+}
+
+/// Runs a study's job until it has run all its trials or is cancelled.
+pub async fn run(
+    db: &Database,
+    job: &Job,
+    config: StudyConfig,
+) -> std::result::Result<(), JobError> {
+    let study = match &job.experiment_id {
+        Some(id) => db.get_experiment(id).await?,
+        None => None,
+    }
+    .ok_or_else(|| JobError::Invalid("A study needs its experiment".to_string()))?;
+    let sampler = config.sampler();
+    let goal = config.goal();
+
+    loop {
+        if db.is_job_cancel_requested(&job.id).await? {
+            for trial in db.list_child_experiments(&study.id).await? {
+                settle(db, &trial.id, ExperimentStatus::Cancelled).await?;
+            }
+            return Err(JobError::Cancelled);
+        }
+
+        let trials: Vec<Trial> = db
+            .list_child_experiments(&study.id)
+            .await?
+            .into_iter()
+            .map(|experiment| Trial::new(experiment, &config.metric))
+            .collect();
+        if let Some(pruner) = &config.pruner {
+            let mut curves = Vec::with_capacity(trials.len());
+            for trial in &trials {
+                let curve: Vec<(i64, f64)> = db
+                    .get_metric_series(&trial.experiment.id, &config.metric)
+                    .await?
+                    .into_iter()
+                    .map(|point| (point.step, point.value))
+                    .collect();
+                curves.push(curve);
+            }
+            for (i, trial) in trials.iter().enumerate() {
+                if trial.status != ExperimentStatus::Running {
+                    continue;
+                }
+                let others: Vec<&Curve> = curves
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, curve)| curve.as_slice())
+                    .collect();
+                if let Some(reason) = pruner.prune(goal, &curves[i], &others) {
+                    prune(db, &study, trial, &reason).await?;
+                }
+            }
+        }
+
+        // Trials created but never queued, e.g. if the study was interrupted
+        for trial in trials
+            .iter()
+            .filter(|trial| trial.status == ExperimentStatus::Pending)
+        {
+            enqueue(db, &config, &trial.experiment, &trial.params).await?;
+        }
+
+        let history: Vec<Observation> = trials
+            .iter()
+            .filter(|trial| trial.status == ExperimentStatus::Completed)
+            .filter_map(|trial| {
+                Some(Observation {
+                    params: &trial.params,
+                    value: trial.value?,
+                })
+            })
+            .collect();
+        let mut running = trials
+            .iter()
+            .filter(|trial| !trial.status.is_terminal())
+            .count();
+        let mut launched = trials.len();
+        let mut exhausted = false;
+        while launched < config.n_trials && running < config.parallelism {
+            let Some(params) = sampler.sample(&config.space, launched, &history) else {
+                exhausted = true;
+                break;
+            };
+            launch(db, &study, &config, launched, params).await?;
+            launched += 1;
+            running += 1;
+        }
+
+        if running == 0 && (launched >= config.n_trials || exhausted) {
+            break;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    finish(db, &study, &config).await
+}
+
+/// Queues trial `number` with `params` as a child run of the study.
+async fn launch(
+    db: &Database,
+    study: &Experiment,
+    config: &StudyConfig,
+    number: usize,
+    params: Params,
+) -> std::result::Result<(), JobError> {
+    let trial = db
+        .create_child_experiment(
+            study,
+            &format!("Trial {}", number),
+            Some(Value::Object(params.clone())),
+        )
+        .await?;
+    db.add_experiment_log(
+        &study.id,
+        "info",
+        &format!(
+            "Trial {} ({}) queued with {}",
+            number,
+            trial.id,
+            Value::Object(params.clone())
+        ),
+    )
+    .await?;
+    enqueue(db, config, &trial, &params).await
+}
+
+async fn enqueue(
+    db: &Database,
+    config: &StudyConfig,
+    trial: &Experiment,
+    params: &Params,
+) -> std::result::Result<(), JobError> {
+    let mut args = config.args.clone();
+    args.extend(space::to_args(params));
+    let job = TrainingJob {
+        script_path: config.script_path.clone(),
+        dataset_id: config.dataset_id.clone(),
+        args,
+        seeds: None,
+    };
+    jobs::enqueue(db, JobKind::Training, &job, Some(&trial.id)).await?;
+    Ok(())
+}
+
+/// Cancels a trial that fell behind, noting why in both logs.
+async fn prune(
+    db: &Database,
+    study: &Experiment,
+    trial: &Trial,
+    reason: &str,
+) -> std::result::Result<(), JobError> {
+    settle(db, &trial.experiment.id, ExperimentStatus::Cancelled).await?;
+    let message = format!("Pruned {}: {}", trial.experiment.name, reason);
+    db.add_experiment_log(&trial.experiment.id, "info", &message)
+        .await?;
+    db.add_experiment_log(&study.id, "info", &message).await?;
+    Ok(())
+}
+
+/// Moves a trial to `next` unless it finished meanwhile.
+async fn settle(
+    db: &Database,
+    id: &str,
+    next: ExperimentStatus,
+) -> std::result::Result<(), JobError> {
+    match status::transition(db, id, next, None).await {
+        Ok(_) | Err(AppError::Conflict(_)) | Err(AppError::NotFound(_)) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Records the best trial's metrics on the study.
+async fn finish(
+    db: &Database,
+    study: &Experiment,
+    config: &StudyConfig,
+) -> std::result::Result<(), JobError> {
+    let Some(report) = report(db, &study.id).await? else {
+        return Err(JobError::Invalid(format!("Study {} is gone", study.id)));
+    };
+    let Some(best) = report.best else {
+        return Err(JobError::Invalid(format!(
+            "No trial completed with a value of {}",
+            config.metric
+        )));
+    };
+
+    if let Some(metrics) = best.metrics {
+        db.update_experiment_metrics(&study.id, metrics).await?;
+    }
+    db.add_experiment_log(
+        &study.id,
+        "info",
+        &format!(
+            "Best trial: {} with {} = {} and {}",
+            best.name,
+            config.metric,
+            best.score.unwrap_or(f64::NAN),
+            best.parameters.unwrap_or_default()
+        ),
+    )
+    .await?;
+    Ok(())
+}
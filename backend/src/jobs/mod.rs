@@ -0,0 +1,390 @@
+//! Background jobs: AutoML searches, training scripts and notebook runs.
+//!
+//! Jobs are rows of the `jobs` table, so queued work survives a restart and
+//! work interrupted by one is picked up again. A fixed number of workers claim
+//! due jobs one at a time; failed attempts are retried with exponential backoff
+//! and running jobs can be cancelled, which kills their process.
+//!
+//! ```text
+//! queued ──> running ──> completed
+//!    │        │   │
+//!    │        │   └────> failed, once out of attempts
+//!    │        └────────> queued again, after a backoff
+//!    └────────┴────────> cancelled
+//! ```
+//! A job's experiment, if it has one, follows the job's status. Study jobs only
+//! wait on the trials they queue, so they are run apart from the workers.
+
+pub mod worker;
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::error::{AppError, Result};
+use crate::models::{Cell, Database, Experiment, Job};
+use crate::tracking::status::{self, ExperimentStatus};
+
+pub const DEFAULT_MAX_ATTEMPTS: i64 = 3;
+/// Jobs run at once unless `OPENMIND_JOB_WORKERS` says otherwise.
+pub const DEFAULT_WORKERS: usize = 2;
+const BACKOFF_BASE_SECS: i64 = 10;
+const MAX_BACKOFF_SECS: i64 = 600;
+/// Directory scripts are run from unless `OPENMIND_WORKSPACE` says otherwise.
+const DEFAULT_WORKSPACE: &str = "workspace";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Automl,
+    Training,
+    Notebook,
+    /// A hyperparameter search, queueing its trials as training jobs
+    Study,
+}
+
+impl JobKind {
+    pub const ALL: [JobKind; 4] = [
+        JobKind::Automl,
+        JobKind::Training,
+        JobKind::Notebook,
+        JobKind::Study,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobKind::Automl => "automl",
+            JobKind::Training => "training",
+            JobKind::Notebook => "notebook",
+            JobKind::Study => "study",
+        }
+    }
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown job kind '{}'", s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub const ALL: [JobStatus; 5] = [
+        JobStatus::Queued,
+        JobStatus::Running,
+        JobStatus::Completed,
+        JobStatus::Failed,
+        JobStatus::Cancelled,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Status of the job's experiment while the job is in this one.
+    pub fn experiment_status(self) -> ExperimentStatus {
+        match self {
+            JobStatus::Queued => ExperimentStatus::Queued,
+            JobStatus::Running => ExperimentStatus::Running,
+            JobStatus::Completed => ExperimentStatus::Completed,
+            JobStatus::Failed => ExperimentStatus::Failed,
+            JobStatus::Cancelled => ExperimentStatus::Cancelled,
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown job status '{}'", s)))
+    }
+}
+
+/// Runs a Python script, by default with the tracking client reporting to the
+/// job's experiment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingJob {
+    pub script_path: String,
+    /// Dataset passed as `--dataset`, with `--output` set to the experiment's
+    /// output directory
+    pub dataset_id: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// JSON object of library to seed
+    pub seeds: Option<String>,
+}
+
+/// Runs notebook code as one script, as it was when the job was queued.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotebookJob {
+    pub notebook_id: Option<String>,
+    pub code: String,
+    /// JSON object of library to seed
+    pub seeds: Option<String>,
+}
+
+impl NotebookJob {
+    /// The code cells of a notebook, in order.
+    pub fn from_cells(notebook_id: &str, cells: &[Cell], seeds: Option<String>) -> Self {
+        let code: Vec<&str> = cells
+            .iter()
+            .filter(|cell| cell.cell_type == "code")
+            .map(|cell| cell.content.as_str())
+            .collect();
+        Self {
+            notebook_id: Some(notebook_id.to_string()),
+            code: code.join("\n\n"),
+            seeds,
+        }
+    }
+}
+
+/// Bounds on a job's process, which is killed once it exceeds one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub runtime: Option<std::time::Duration>,
+    /// Resident memory of the process and its children, where the platform
+    /// reports it (Linux)
+    pub memory_mb: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Runtime,
+    Memory,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Limit::Runtime => "runtime",
+            Limit::Memory => "memory",
+        })
+    }
+}
+
+/// Why an attempt at a job didn't complete.
+#[derive(Debug, Error)]
+pub enum JobError {
+    /// Worth another attempt, e.g. the process crashed
+    #[error("{0}")]
+    Failed(String),
+    /// Retrying won't help, e.g. the dataset is gone
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Cancelled")]
+    Cancelled,
+    /// Killed for going over one of its [`Limits`]
+    #[error("Exceeded its {0} limit")]
+    LimitExceeded(Limit),
+}
+
+impl From<sqlx::Error> for JobError {
+    fn from(error: sqlx::Error) -> Self {
+        JobError::Failed(format!("Database error: {}", error))
+    }
+}
+
+impl From<AppError> for JobError {
+    fn from(error: AppError) -> Self {
+        match error {
+            AppError::NotFound(_) | AppError::BadRequest(_) | AppError::Conflict(_) => {
+                JobError::Invalid(error.to_string())
+            }
+            error => JobError::Failed(error.to_string()),
+        }
+    }
+}
+
+impl From<anyhow::Error> for JobError {
+    fn from(error: anyhow::Error) -> Self {
+        JobError::Failed(format!("{:#}", error))
+    }
+}
+
+/// Number of workers, from `OPENMIND_JOB_WORKERS`; studies don't count.
+pub fn workers() -> usize {
+    std::env::var("OPENMIND_JOB_WORKERS")
+        .ok()
+        .and_then(|workers| workers.parse().ok())
+        .filter(|&workers| workers > 0)
+        .unwrap_or(DEFAULT_WORKERS)
+}
+
+/// Wait before retrying a job whose `attempts`th attempt failed: 10s, 20s,
+/// 40s, ... up to 10 minutes.
+pub fn backoff(attempts: i64) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    Duration::seconds((BACKOFF_BASE_SECS << exponent).min(MAX_BACKOFF_SECS))
+}
+
+/// Directory scripts are run from, from `OPENMIND_WORKSPACE`.
+pub fn workspace() -> PathBuf {
+    std::env::var_os("OPENMIND_WORKSPACE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| DEFAULT_WORKSPACE.into())
+}
+
+/// Absolute path of a script given relative to `workspace`, which it must not
+/// leave, e.g. through `..` or a symlink.
+pub fn resolve_script(workspace: &Path, script_path: &str) -> Result<String> {
+    let root = workspace.canonicalize().map_err(|_| {
+        AppError::BadRequest(format!(
+            "The workspace directory {} does not exist",
+            workspace.display()
+        ))
+    })?;
+    let script = root
+        .join(script_path)
+        .canonicalize()
+        .map_err(|_| AppError::NotFound(format!("Script {} not found", script_path)))?;
+    if !script.starts_with(&root) || !script.is_file() {
+        return Err(AppError::BadRequest(format!(
+            "Script {} is not a file in the workspace",
+            script_path
+        )));
+    }
+    Ok(script.to_string_lossy().to_string())
+}
+
+/// Queues a job, moving the experiment it runs, if any, to queued in the same
+/// transaction.
+pub async fn enqueue(
+    db: &Database,
+    kind: JobKind,
+    payload: &impl Serialize,
+    experiment_id: Option<&str>,
+) -> Result<(Job, Option<Experiment>)> {
+    let payload = serde_json::to_string(payload).map_err(|e| AppError::Internal(e.into()))?;
+    let Some(id) = experiment_id else {
+        let job = db
+            .enqueue_job(kind, &payload, None, DEFAULT_MAX_ATTEMPTS)
+            .await?;
+        return Ok((job, None));
+    };
+    match db
+        .enqueue_experiment_job(kind, &payload, id, DEFAULT_MAX_ATTEMPTS)
+        .await?
+    {
+        Some((job, experiment)) => Ok((job, Some(experiment))),
+        // The transition explains why the experiment can't be queued
+        None => Err(status::transition(db, id, ExperimentStatus::Queued, None)
+            .await
+            .err()
+            .unwrap_or_else(|| {
+                AppError::Conflict(format!("Experiment {} changed status while queueing", id))
+            })),
+    }
+}
+
+/// Cancels a queued job, or has its worker stop a running one within a second.
+pub async fn cancel(db: &Database, id: &str) -> Result<Job> {
+    let Some(job) = db.request_job_cancel(id).await? else {
+        return match db.get_job(id).await? {
+            Some(job) => Err(AppError::Conflict(format!(
+                "Job {} already {}",
+                id, job.status
+            ))),
+            None => Err(AppError::NotFound(format!("Job {} not found", id))),
+        };
+    };
+
+    // Running jobs are settled by their worker
+    if job.status == JobStatus::Cancelled.as_str() {
+        if let Some(experiment_id) = &job.experiment_id {
+            match status::transition(db, experiment_id, ExperimentStatus::Cancelled, None).await {
+                Ok(_) | Err(AppError::Conflict(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_settings() {
+        assert_eq!(backoff(1), Duration::seconds(10));
+        assert_eq!(backoff(3), Duration::seconds(40));
+        assert_eq!(backoff(10), Duration::seconds(600));
+        assert_eq!(backoff(i64::MAX), Duration::seconds(600));
+
+        assert_eq!("notebook".parse::<JobKind>().unwrap(), JobKind::Notebook);
+        assert!("gpu".parse::<JobKind>().is_err());
+        assert_eq!(
+            "cancelled"
+                .parse::<JobStatus>()
+                .unwrap()
+                .experiment_status(),
+            ExperimentStatus::Cancelled
+        );
+
+        let job: TrainingJob = serde_json::from_str(r#"{"script_path": "train.py"}"#).unwrap();
+        assert!(job.args.is_empty() && job.dataset_id.is_none());
+    }
+
+    #[test]
+    fn test_scripts_stay_in_the_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("workspace");
+        std::fs::create_dir_all(workspace.join("models")).unwrap();
+        std::fs::write(workspace.join("models/train.py"), "").unwrap();
+        std::fs::write(dir.path().join("outside.py"), "").unwrap();
+
+        let script = resolve_script(&workspace, "models/train.py").unwrap();
+        assert!(script.ends_with("train.py") && Path::new(&script).is_absolute());
+        assert!(matches!(
+            resolve_script(&workspace, "../outside.py"),
+            Err(AppError::BadRequest(_))
+        ));
+        let outside = dir.path().join("outside.py");
+        assert!(resolve_script(&workspace, &outside.to_string_lossy()).is_err());
+        assert!(resolve_script(&workspace, "models").is_err());
+        assert!(matches!(
+            resolve_script(&workspace, "missing.py"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,621 @@
+//! Workers claiming and running queued jobs.
+
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader, Split};
+use tokio::process::{Child, Command};
+
+use super::{backoff, JobError, JobKind, JobStatus, Limit, Limits, NotebookJob, TrainingJob};
+use crate::automl::AutoMLService;
+use crate::error::{AppError, Result};
+use crate::hpo;
+use crate::models::{Database, Experiment, Job};
+use crate::python::PythonExecutor;
+use crate::tracking::artifacts;
+use crate::tracking::status::{self, ExperimentStatus};
+
+/// How often idle workers look for due jobs and running ones for cancellation.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lines of a job's output copied to its experiment's log.
+const MAX_OUTPUT_LINES: usize = 1000;
+
+/// Standard output of a job kept for its caller; earlier lines are dropped
+/// first, since results such as AutoML's are printed last.
+const MAX_STDOUT_BYTES: usize = 4 * 1024 * 1024;
+
+/// Settles jobs a previous process left running, then starts `workers` workers
+/// and the coordinator running studies.
+pub fn start(db: Arc<Database>, workers: usize) {
+    tokio::spawn(async move {
+        if let Err(e) = recover(&db).await {
+            tracing::error!("Failed to recover interrupted jobs: {}", e);
+        }
+        for _ in 0..workers {
+            tokio::spawn(work(db.clone()));
+        }
+        tokio::spawn(coordinate(db));
+    });
+}
+
+/// Queues jobs interrupted by a restart again, or fails them if they are out
+/// of attempts.
+pub async fn recover(db: &Database) -> Result<()> {
+    for job in db.requeue_interrupted_jobs().await? {
+        tracing::warn!("Job {} was interrupted; it is now {}", job.id, job.status);
+        if let Ok(status) = job.status.parse::<JobStatus>() {
+            settle_experiment(db, &job, status, job.error.as_deref()).await;
+        }
+    }
+    Ok(())
+}
+
+async fn work(db: Arc<Database>) {
+    loop {
+        match db.claim_next_job(false).await {
+            Ok(Some(job)) => process(&db, job).await,
+            Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+            Err(e) => {
+                tracing::error!("Failed to claim a job: {}", e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+/// Runs every due study at once. Studies spend their time waiting on their
+/// trials, which would keep those trials from a worker if studies took one.
+async fn coordinate(db: Arc<Database>) {
+    loop {
+        match db.claim_next_job(true).await {
+            Ok(Some(job)) => {
+                let db = db.clone();
+                tokio::spawn(async move { process(&db, job).await });
+            }
+            Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+            Err(e) => {
+                tracing::error!("Failed to claim a study: {}", e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+async fn process(db: &Arc<Database>, job: Job) {
+    if let Some(experiment_id) = &job.experiment_id {
+        match status::transition(db, experiment_id, ExperimentStatus::Running, None).await {
+            Ok(_) => {}
+            // Finished meanwhile, e.g. cancelled through the experiment
+            Err(AppError::Conflict(reason)) | Err(AppError::NotFound(reason)) => {
+                finish(db, &job, JobStatus::Cancelled, Some(&reason)).await;
+                return;
+            }
+            Err(e) => tracing::error!("Failed to start experiment {}: {}", experiment_id, e),
+        }
+    }
+
+    let result = match run(db, &job).await {
+        Err(JobError::Failed(_)) if db.is_job_cancel_requested(&job.id).await.unwrap_or(false) => {
+            Err(JobError::Cancelled)
+        }
+        result => result,
+    };
+    match result {
+        Ok(()) => finish(db, &job, JobStatus::Completed, None).await,
+        Err(JobError::Cancelled) => finish(db, &job, JobStatus::Cancelled, None).await,
+        Err(JobError::Failed(error)) if job.attempts < job.max_attempts => {
+            match finished_experiment(db, &job).await {
+                // Another attempt couldn't run it again
+                Some(experiment) => {
+                    let reason = experiment.failure_reason.unwrap_or(error);
+                    finish(db, &job, JobStatus::Failed, Some(&reason)).await
+                }
+                None => retry(db, &job, &error).await,
+            }
+        }
+        Err(e) => finish(db, &job, JobStatus::Failed, Some(&e.to_string())).await,
+    }
+}
+
+async fn run(db: &Arc<Database>, job: &Job) -> std::result::Result<(), JobError> {
+    let kind: JobKind = job
+        .kind
+        .parse()
+        .map_err(|e: AppError| JobError::Invalid(e.to_string()))?;
+    match kind {
+        JobKind::Automl => AutoMLService::new(db.clone()).run(job, payload(job)?).await,
+        JobKind::Training => training(db, job, payload(job)?).await,
+        JobKind::Notebook => notebook(db, job, payload(job)?).await,
+        JobKind::Study => hpo::study::run(db, job, payload(job)?).await,
+    }
+}
+
+fn payload<T: DeserializeOwned>(job: &Job) -> std::result::Result<T, JobError> {
+    serde_json::from_str(&job.payload)
+        .map_err(|e| JobError::Invalid(format!("Invalid {} job: {}", job.kind, e)))
+}
+
+fn executor(job: &Job, seeds: Option<&str>) -> PythonExecutor {
+    match &job.experiment_id {
+        Some(experiment_id) => PythonExecutor::new().with_run(experiment_id, seeds),
+        None => PythonExecutor::new(),
+    }
+}
+
+async fn training(
+    db: &Database,
+    job: &Job,
+    training: TrainingJob,
+) -> std::result::Result<(), JobError> {
+    let mut command = executor(job, training.seeds.as_deref()).command();
+    command.arg(&training.script_path).args(&training.args);
+    if let Some(dataset_id) = &training.dataset_id {
+        let dataset = db
+            .get_dataset(dataset_id)
+            .await?
+            .ok_or_else(|| JobError::Invalid(format!("Dataset {} not found", dataset_id)))?;
+        let output =
+            artifacts::experiment_output_dir(job.experiment_id.as_deref().unwrap_or(&job.id));
+        command
+            .arg("--dataset")
+            .arg(&dataset.file_path)
+            .arg("--output")
+            .arg(output);
+    }
+    run_command(db, job, command).await?;
+
+    if let Some(experiment_id) = &job.experiment_id {
+        artifacts::import_experiment_outputs(db, experiment_id).await?;
+    }
+    Ok(())
+}
+
+async fn notebook(
+    db: &Database,
+    job: &Job,
+    notebook: NotebookJob,
+) -> std::result::Result<(), JobError> {
+    let mut executor = executor(job, notebook.seeds.as_deref());
+    if let Some(notebook_id) = &notebook.notebook_id {
+        executor = executor.with_notebook(notebook_id);
+    }
+    let mut command = executor.command();
+    command.arg("-c").arg(&notebook.code);
+    run_command(db, job, command).await?;
+    Ok(())
+}
+
+/// Runs `command` to completion and returns its standard output, copying its
+/// output to the job's experiment log line by line as it runs. The process is
+/// killed if the job is cancelled meanwhile.
+pub async fn run_command(
+    db: &Database,
+    job: &Job,
+    command: Command,
+) -> std::result::Result<String, JobError> {
+    run_limited(db, job, command, Limits::default()).await
+}
+
+/// Like [`run_command`], also killing the process once it exceeds `limits`.
+/// The process runs in a process group of its own, so killing it also kills
+/// what it started, such as the workers of a model's `n_jobs`.
+pub async fn run_limited(
+    db: &Database,
+    job: &Job,
+    mut command: Command,
+    limits: Limits,
+) -> std::result::Result<String, JobError> {
+    let program = command.as_std().get_program().to_string_lossy().to_string();
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut child = command
+        // Python block-buffers piped output, which would hold lines back
+        .env("PYTHONUNBUFFERED", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| JobError::Failed(format!("Failed to start {}: {}", program, e)))?;
+
+    let mut stdout_lines = child
+        .stdout
+        .take()
+        .map(|out| BufReader::new(out).split(b'\n'));
+    let mut stderr_lines = child
+        .stderr
+        .take()
+        .map(|err| BufReader::new(err).split(b'\n'));
+    let mut log = OutputLog::new(db, job.experiment_id.as_deref());
+    let mut stdout = Tail::new(MAX_STDOUT_BYTES);
+    let mut last_error = None;
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    let started = Instant::now();
+
+    // Reading both pipes until they close keeps a chatty process from blocking
+    let status = loop {
+        tokio::select! {
+            line = next_line(&mut stdout_lines) => match line {
+                Some(line) => {
+                    log.line(&line, "info").await;
+                    stdout.push(line);
+                }
+                None => stdout_lines = None,
+            },
+            line = next_line(&mut stderr_lines) => match line {
+                Some(line) => {
+                    log.line(&line, "warning").await;
+                    if !line.trim().is_empty() {
+                        last_error = Some(line);
+                    }
+                }
+                None => stderr_lines = None,
+            },
+            status = child.wait(), if stdout_lines.is_none() && stderr_lines.is_none() => {
+                break status
+                    .map_err(|e| JobError::Failed(format!("Failed to run {}: {}", program, e)))?;
+            }
+            _ = poll.tick() => {
+                if db.is_job_cancel_requested(&job.id).await? {
+                    kill_group(&mut child).await;
+                    return Err(JobError::Cancelled);
+                }
+                let exceeded = if limits.runtime.is_some_and(|runtime| started.elapsed() >= runtime) {
+                    Some(Limit::Runtime)
+                } else {
+                    limits
+                        .memory_mb
+                        .zip(child.id())
+                        .filter(|&(memory_mb, pid)| {
+                            memory_kb(pid).is_some_and(|kb| kb / 1024 >= memory_mb)
+                        })
+                        .map(|_| Limit::Memory)
+                };
+                if let Some(limit) = exceeded {
+                    kill_group(&mut child).await;
+                    return Err(JobError::LimitExceeded(limit));
+                }
+            }
+        }
+    };
+
+    if status.success() {
+        Ok(stdout.into_string())
+    } else {
+        // The last line of a Python traceback is the exception
+        Err(JobError::Failed(format!(
+            "{} exited with {}: {}",
+            program,
+            status,
+            last_error.as_deref().unwrap_or("no output")
+        )))
+    }
+}
+
+/// Kills a process started by [`run_limited`] and the rest of its group.
+async fn kill_group(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: killpg only sends a signal; the group is the child's own
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    let _ = child.kill().await;
+}
+
+/// The last lines of a process's output, up to a number of bytes; a single
+/// longer line is kept whole.
+struct Tail {
+    lines: VecDeque<String>,
+    bytes: usize,
+    max_bytes: usize,
+}
+
+impl Tail {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            bytes: 0,
+            max_bytes,
+        }
+    }
+
+    fn push(&mut self, line: String) {
+        self.bytes += line.len() + 1;
+        self.lines.push_back(line);
+        while self.bytes > self.max_bytes && self.lines.len() > 1 {
+            if let Some(dropped) = self.lines.pop_front() {
+                self.bytes -= dropped.len() + 1;
+            }
+        }
+    }
+
+    fn into_string(self) -> String {
+        let mut output = String::with_capacity(self.bytes);
+        for line in self.lines {
+            output.push_str(&line);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Resident memory of a process and its descendants, from `/proc`; `None`
+/// where that isn't available.
+fn memory_kb(pid: u32) -> Option<u64> {
+    let status_kb = |pid: u32| -> Option<u64> {
+        let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))?
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse()
+            .ok()
+    };
+    let mut total = status_kb(pid)?;
+
+    // Parent of each process; the command in `stat` may contain spaces and
+    // parentheses, so fields are counted from its closing one
+    let mut parents = Vec::new();
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let Some(child) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        let parent = stat
+            .rsplit_once(')')
+            .and_then(|(_, fields)| fields.split_whitespace().nth(1)?.parse::<u32>().ok());
+        if let Some(parent) = parent {
+            parents.push((child, parent));
+        }
+    }
+    let mut tree = vec![pid];
+    let mut i = 0;
+    while i < tree.len() {
+        for &(child, parent) in &parents {
+            if parent == tree[i] && !tree.contains(&child) {
+                tree.push(child);
+                total += status_kb(child).unwrap_or(0);
+            }
+        }
+        i += 1;
+    }
+    Some(total)
+}
+
+/// Next line of a pipe, with bytes that aren't UTF-8 replaced so such a line
+/// doesn't end the output; pending forever once it is closed.
+async fn next_line<R: AsyncBufRead + Unpin>(lines: &mut Option<Split<R>>) -> Option<String> {
+    let Some(lines) = lines else {
+        return std::future::pending().await;
+    };
+    match lines.next_segment().await {
+        Ok(line) => line.map(|line| {
+            let line = String::from_utf8_lossy(&line);
+            line.strip_suffix('\r').unwrap_or(&line).to_string()
+        }),
+        Err(e) => {
+            tracing::warn!("Failed to read the output of a job: {}", e);
+            None
+        }
+    }
+}
+
+/// Level of a line written by Python's `logging`, in its default format or
+/// `asctime - name - LEVEL - message`.
+pub fn log_level(line: &str) -> Option<&'static str> {
+    const LEVELS: [(&str, &str); 5] = [
+        ("DEBUG", "debug"),
+        ("INFO", "info"),
+        ("WARNING", "warning"),
+        ("ERROR", "error"),
+        ("CRITICAL", "error"),
+    ];
+    LEVELS
+        .iter()
+        .find(|(name, _)| {
+            line.strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with(':'))
+                || line.contains(&format!(" - {} - ", name))
+        })
+        .map(|(_, level)| *level)
+}
+
+/// Copies a process's output into an experiment's log, up to a limit.
+struct OutputLog<'a> {
+    db: &'a Database,
+    experiment_id: Option<&'a str>,
+    logged: usize,
+}
+
+impl<'a> OutputLog<'a> {
+    fn new(db: &'a Database, experiment_id: Option<&'a str>) -> Self {
+        Self {
+            db,
+            experiment_id,
+            logged: 0,
+        }
+    }
+
+    async fn line(&mut self, line: &str, default_level: &str) {
+        let Some(experiment_id) = self.experiment_id else {
+            return;
+        };
+        self.logged += 1;
+        let (level, message) = match self.logged {
+            n if n < MAX_OUTPUT_LINES => (log_level(line).unwrap_or(default_level), line),
+            n if n == MAX_OUTPUT_LINES => {
+                ("warning", "Output truncated; further lines aren't logged")
+            }
+            _ => return,
+        };
+        if let Err(e) = self
+            .db
+            .add_experiment_log(experiment_id, level, message)
+            .await
+        {
+            tracing::error!(
+                "Failed to log the output of experiment {}: {}",
+                experiment_id,
+                e
+            );
+        }
+    }
+}
+
+/// The job's experiment if it already finished, e.g. failed by
+/// `openmind.track` when the code raised.
+async fn finished_experiment(db: &Database, job: &Job) -> Option<Experiment> {
+    let experiment = db
+        .get_experiment(job.experiment_id.as_deref()?)
+        .await
+        .ok()??;
+    experiment
+        .status
+        .parse::<ExperimentStatus>()
+        .is_ok_and(ExperimentStatus::is_terminal)
+        .then_some(experiment)
+}
+
+async fn retry(db: &Database, job: &Job, error: &str) {
+    let delay = backoff(job.attempts);
+    let run_after = chrono::Utc::now().naive_utc() + delay;
+    tracing::warn!(
+        "Attempt {} of {} at job {} failed: {}; retrying in {}s",
+        job.attempts,
+        job.max_attempts,
+        job.id,
+        error,
+        delay.num_seconds()
+    );
+    if let Err(e) = db.retry_job(&job.id, error, run_after).await {
+        tracing::error!("Failed to requeue job {}: {}", job.id, e);
+        return;
+    }
+
+    if let Some(experiment_id) = &job.experiment_id {
+        let message = format!(
+            "Attempt {} of {} failed: {}; retrying in {}s",
+            job.attempts,
+            job.max_attempts,
+            error,
+            delay.num_seconds()
+        );
+        if let Err(e) = db
+            .add_experiment_log(experiment_id, "warning", &message)
+            .await
+        {
+            tracing::error!(
+                "Failed to log the retry of experiment {}: {}",
+                experiment_id,
+                e
+            );
+        }
+    }
+    settle_experiment(db, job, JobStatus::Queued, None).await;
+}
+
+async fn finish(db: &Database, job: &Job, status: JobStatus, error: Option<&str>) {
+    if let Err(e) = db.finish_job(&job.id, status, error).await {
+        tracing::error!("Failed to record job {} as {}: {}", job.id, status, e);
+    }
+    settle_experiment(db, job, status, error).await;
+}
+
+/// Moves the job's experiment along with the job.
+async fn settle_experiment(db: &Database, job: &Job, status: JobStatus, error: Option<&str>) {
+    let Some(experiment_id) = &job.experiment_id else {
+        return;
+    };
+    match status::transition(db, experiment_id, status.experiment_status(), error).await {
+        // The code may have ended the run itself through `openmind.track`
+        Ok(_) | Err(AppError::Conflict(_)) | Err(AppError::NotFound(_)) => {}
+        Err(e) => tracing::error!(
+            "Failed to move experiment {} to {}: {}",
+            experiment_id,
+            status.experiment_status(),
+            e
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_level() {
+        assert_eq!(
+            log_level("2024-09-25 10:00:00,123 - __main__ - WARNING - Few rows"),
+            Some("warning")
+        );
+        assert_eq!(log_level("ERROR:root:Failed to load"), Some("error"));
+        assert_eq!(log_level("CRITICAL:root:Out of memory"), Some("error"));
+        assert_eq!(log_level("INFORMATION: 3 rows"), None);
+        assert_eq!(log_level("Traceback (most recent call last):"), None);
+    }
+
+    #[test]
+    fn test_stdout_keeps_its_tail() {
+        let mut tail = Tail::new(12);
+        for line in ["epoch 1", "epoch 2", "{\"ok\": 1}"] {
+            tail.push(line.to_string());
+        }
+        assert_eq!(tail.into_string(), "{\"ok\": 1}\n");
+
+        let mut tail = Tail::new(100);
+        tail.push("a".to_string());
+        tail.push("b".to_string());
+        assert_eq!(tail.into_string(), "a\nb\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_group_kills_grandchildren() {
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg("sleep 60 & echo $!; wait")
+            .stdout(Stdio::piped())
+            .process_group(0);
+        let mut child = command.spawn().unwrap();
+        let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+        let grandchild: i32 = lines.next_line().await.unwrap().unwrap().parse().unwrap();
+
+        kill_group(&mut child).await;
+        // A killed process lingers as a zombie until reaped, but is no longer sleeping
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let state = std::fs::read_to_string(format!("/proc/{}/stat", grandchild))
+            .ok()
+            .and_then(|stat| {
+                stat.rsplit_once(')')
+                    .and_then(|(_, fields)| fields.split_whitespace().next().map(str::to_string))
+            });
+        assert!(matches!(state.as_deref(), None | Some("Z")));
+    }
+
+    #[tokio::test]
+    async fn test_next_line_survives_invalid_utf8() {
+        let output: &[u8] = b"epoch 1\r\nloss \xff\xfe 0.5\nepoch 2";
+        let mut lines = Some(BufReader::new(output).split(b'\n'));
+        assert_eq!(next_line(&mut lines).await.as_deref(), Some("epoch 1"));
+        assert_eq!(
+            next_line(&mut lines).await.as_deref(),
+            Some("loss \u{fffd}\u{fffd} 0.5")
+        );
+        assert_eq!(next_line(&mut lines).await.as_deref(), Some("epoch 2"));
+        assert_eq!(next_line(&mut lines).await, None);
+    }
+}
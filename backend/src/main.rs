@@ -10,8 +10,11 @@ use tower_http::cors::{Any, CorsLayer};
 mod api;
 mod automl;
 mod error;
+mod hpo;
+mod jobs;
 mod models;
 mod python;
+mod tracking;
 
 use error::Result;
 
@@ -1,12 +1,37 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{
+    sqlite::{SqliteConnection, SqlitePoolOptions},
+    QueryBuilder,
+};
 use std::sync::Arc;
 use chrono::NaiveDateTime;
 
+use crate::jobs::{JobKind, JobStatus};
+use crate::tracking::{
+    events::{ExperimentEvent, ExperimentEvents},
+    logs::LogQuery,
+    metrics::{MetricGoal, MetricPoint, SeriesPoint},
+    search::{self, Condition},
+    ExperimentSearch, ExperimentStatus,
+};
+
+/// A run recorded once it has finished, such as a candidate of an AutoML
+/// search.
+#[derive(Debug, Clone)]
+pub struct FinishedRun {
+    pub name: String,
+    pub parameters: Option<Value>,
+    pub metrics: Value,
+    /// A terminal status
+    pub status: ExperimentStatus,
+    pub failure_reason: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Database {
     pool: Arc<sqlx::SqlitePool>,
+    events: ExperimentEvents,
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
@@ -28,7 +53,7 @@ pub struct Cell {
     pub updated_at: chrono::NaiveDateTime,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Experiment {
     pub id: String,
     pub name: String,
@@ -41,9 +66,38 @@ pub struct Experiment {
     pub model_path: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// Version of the dataset when the experiment was created
+    pub dataset_version: Option<i64>,
+    /// MLflow experiment grouping the run, for runs logged through the MLflow API
+    pub mlflow_experiment_id: Option<i64>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    /// Time from `started_at` to `finished_at`
+    pub duration_ms: Option<i64>,
+    /// Why the experiment failed, when it did
+    pub failure_reason: Option<String>,
+    /// Experiment this one is a child run of, e.g. an AutoML search
+    pub parent_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+/// An MLflow experiment: a named group of runs (rows of `experiments`).
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MlflowExperiment {
+    pub id: i64,
+    pub name: String,
+    pub artifact_location: Option<String>,
+    pub lifecycle_stage: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Tag {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ExperimentLog {
     pub id: i64,
     pub experiment_id: String,
@@ -52,13 +106,140 @@ pub struct ExperimentLog {
     pub message: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+/// Latest and best values of one metric of an experiment.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ExperimentMetricSummary {
+    pub experiment_id: String,
+    pub key: String,
+    /// Value at the highest step
+    pub value: f64,
+    pub step: i64,
+    pub best_value: f64,
+    pub min_value: f64,
+    pub max_value: f64,
+    pub count: i64,
+}
+
+/// What an experiment ran with; unknown parts are `None`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Reproducibility {
+    pub experiment_id: String,
+    /// Runtime environment the run used
+    pub environment_id: Option<String>,
+    pub python_version: Option<String>,
+    /// `pip freeze` output
+    pub packages: Option<String>,
+    pub git_commit: Option<String>,
+    /// Whether there were uncommitted changes on top of `git_commit`
+    pub git_dirty: Option<bool>,
+    /// SHA-256 of `notebook_snapshot`
+    pub notebook_revision: Option<String>,
+    /// JSON array of the notebook's cells when the run started
+    pub notebook_snapshot: Option<String>,
+    pub script_path: Option<String>,
+    pub script_sha256: Option<String>,
+    /// JSON object of library to seed, e.g. `{"numpy": 42}`
+    pub seeds: Option<String>,
+    pub dataset_version: Option<i64>,
+    pub captured_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ExperimentArtifact {
+    pub id: i64,
+    pub experiment_id: String,
+    /// Relative, `/`-separated path within the experiment
+    pub path: String,
+    /// Content hash; the file is stored once per hash
+    pub sha256: String,
+    pub size: i64,
+    pub mime_type: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Dataset {
     pub id: String,
     pub name: String,
     pub file_path: String,
     pub size: i64,
     pub created_at: chrono::NaiveDateTime,
+    pub version: i64,
+    /// Uploaded file the dataset was extracted from, for multi-table sources
+    pub source_path: Option<String>,
+    pub source_table: Option<String>,
+    /// Connection the dataset is a snapshot of, for externally sourced datasets
+    pub connection_id: Option<String>,
+    pub refreshed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Connection {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    /// Sealed connector config; see `data::connectors::ConnectorConfig::open`
+    pub config: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// A snapshot of a connection-backed dataset, one per version.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DatasetSnapshot {
+    pub dataset_id: String,
+    pub version: i64,
+    pub file_path: String,
+    pub size: i64,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DatasetProfileRecord {
+    pub dataset_id: String,
+    pub version: i64,
+    pub status: String,
+    pub profile: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub computed_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+/// What an AutoML search produced; its metrics and model path are on the experiment.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AutoMLResult {
+    pub experiment_id: String,
+    pub task_type: Option<String>,
+    pub results_path: Option<String>,
+    /// JSON object of feature to importance
+    pub feature_importances: Option<String>,
+    pub created_at: NaiveDateTime,
+    /// Metric the candidates were ranked by
+    pub metric: Option<String>,
+    /// Request budget that stopped the search early
+    pub budget_exhausted: Option<String>,
+}
+
+/// A unit of background work; see [`crate::jobs`].
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Job {
+    pub id: String,
+    pub kind: String,
+    /// JSON arguments of the kind
+    pub payload: String,
+    pub experiment_id: Option<String>,
+    pub status: String,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    /// Not started before this time; pushed back between retries
+    pub run_after: NaiveDateTime,
+    pub cancel_requested: bool,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
 }
 
 impl Database {
@@ -76,7 +257,15 @@ impl Database {
             .run(&pool)
             .await?;
             
-        Ok(Self { pool: Arc::new(pool) })
+        Ok(Self {
+            pool: Arc::new(pool),
+            events: ExperimentEvents::default(),
+        })
+    }
+
+    /// Log lines, metric points and status changes of experiments as they are written.
+    pub fn events(&self) -> &ExperimentEvents {
+        &self.events
     }
     
     // Notebook CRUD operations
@@ -100,107 +289,1619 @@ impl Database {
         .await
     }
     
-    // Experiment CRUD operations
-    pub async fn create_experiment(
+    // Dataset operations
+    pub async fn create_dataset(
         &self,
         name: &str,
-        notebook_id: Option<&str>,
-        dataset_id: Option<&str>,
-        parameters: Option<Value>,
-    ) -> Result<Experiment, sqlx::Error> {
+        file_path: &str,
+        size: i64,
+    ) -> Result<Dataset, sqlx::Error> {
         let id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().naive_utc();
-        let params_str = parameters.map(|p| p.to_string());
 
         sqlx::query_as!(
-            Experiment,
+            Dataset,
             r#"
-            INSERT INTO experiments (
-                id, name, notebook_id, dataset_id, parameters, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO datasets (id, name, file_path, size, created_at)
+            VALUES (?, ?, ?, ?, ?)
             RETURNING *
             "#,
             id,
             name,
-            notebook_id,
-            dataset_id,
-            params_str,
-            now,
+            file_path,
+            size,
             now
         )
         .fetch_one(&*self.pool)
         .await
     }
 
-    pub async fn get_experiment(&self, id: &str) -> Result<Option<Experiment>, sqlx::Error> {
+    /// Registers a dataset extracted from one table of a multi-table file.
+    pub async fn create_dataset_from_table(
+        &self,
+        name: &str,
+        file_path: &str,
+        size: i64,
+        source_path: &str,
+        source_table: &str,
+    ) -> Result<Dataset, sqlx::Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().naive_utc();
+
         sqlx::query_as!(
-            Experiment,
-            "SELECT * FROM experiments WHERE id = ?",
+            Dataset,
+            r#"
+            INSERT INTO datasets (id, name, file_path, size, created_at, source_path, source_table)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+            id,
+            name,
+            file_path,
+            size,
+            now,
+            source_path,
+            source_table
+        )
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    pub async fn get_dataset(&self, id: &str) -> Result<Option<Dataset>, sqlx::Error> {
+        sqlx::query_as!(
+            Dataset,
+            "SELECT * FROM datasets WHERE id = ?",
             id
         )
         .fetch_optional(&*self.pool)
         .await
     }
+    
+    /// Points the dataset at a newly extracted table; the content changes so the version is bumped.
+    pub async fn set_dataset_table(
+        &self,
+        id: &str,
+        file_path: &str,
+        size: i64,
+        source_table: &str,
+    ) -> Result<Dataset, sqlx::Error> {
+        sqlx::query_as!(
+            Dataset,
+            r#"
+            UPDATE datasets
+            SET file_path = ?, size = ?, source_table = ?, version = version + 1
+            WHERE id = ?
+            RETURNING *
+            "#,
+            file_path,
+            size,
+            source_table,
+            id
+        )
+        .fetch_one(&*self.pool)
+        .await
+    }
 
-    pub async fn update_experiment_metrics(
+    /// Registers a dataset snapshotted from an external connection.
+    pub async fn create_dataset_from_connection(
+        &self,
+        name: &str,
+        file_path: &str,
+        size: i64,
+        connection_id: &str,
+    ) -> Result<Dataset, sqlx::Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().naive_utc();
+
+        let mut tx = self.pool.begin().await?;
+        let dataset = sqlx::query_as!(
+            Dataset,
+            r#"
+            INSERT INTO datasets (id, name, file_path, size, created_at, connection_id, refreshed_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+            id,
+            name,
+            file_path,
+            size,
+            now,
+            connection_id,
+            now
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        Self::insert_dataset_snapshot(&mut tx, &dataset).await?;
+        tx.commit().await?;
+
+        Ok(dataset)
+    }
+
+    /// Points the dataset at a fresh snapshot of its connection and bumps the
+    /// version; the snapshots of earlier versions are kept.
+    pub async fn update_dataset_snapshot(
         &self,
         id: &str,
-        metrics: Value,
+        file_path: &str,
+        size: i64,
+    ) -> Result<Dataset, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+
+        let mut tx = self.pool.begin().await?;
+        let dataset = sqlx::query_as!(
+            Dataset,
+            r#"
+            UPDATE datasets
+            SET file_path = ?, size = ?, refreshed_at = ?, version = version + 1
+            WHERE id = ?
+            RETURNING *
+            "#,
+            file_path,
+            size,
+            now,
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        Self::insert_dataset_snapshot(&mut tx, &dataset).await?;
+        tx.commit().await?;
+
+        Ok(dataset)
+    }
+
+    async fn insert_dataset_snapshot(
+        conn: &mut SqliteConnection,
+        dataset: &Dataset,
     ) -> Result<(), sqlx::Error> {
-        let metrics_str = metrics.to_string();
+        let created_at = dataset.refreshed_at.unwrap_or(dataset.created_at);
         sqlx::query!(
-            "UPDATE experiments SET metrics = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
-            metrics_str,
-            id
+            r#"
+            INSERT INTO dataset_snapshots (dataset_id, version, file_path, size, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            dataset.id,
+            dataset.version,
+            dataset.file_path,
+            dataset.size,
+            created_at
         )
-        .execute(&*self.pool)
+        .execute(&mut *conn)
         .await?;
         Ok(())
     }
 
-    pub async fn update_experiment_status(
+    /// Snapshots of a connection-backed dataset, newest first.
+    pub async fn list_dataset_snapshots(
+        &self,
+        dataset_id: &str,
+    ) -> Result<Vec<DatasetSnapshot>, sqlx::Error> {
+        sqlx::query_as!(
+            DatasetSnapshot,
+            "SELECT * FROM dataset_snapshots WHERE dataset_id = ? ORDER BY version DESC",
+            dataset_id
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    // Connection operations
+    pub async fn create_connection(
         &self,
         id: &str,
+        name: &str,
+        kind: &str,
+        config: &str,
+    ) -> Result<Connection, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+
+        sqlx::query_as!(
+            Connection,
+            r#"
+            INSERT INTO connections (id, name, kind, config, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+            id,
+            name,
+            kind,
+            config,
+            now,
+            now
+        )
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    pub async fn get_connection(&self, id: &str) -> Result<Option<Connection>, sqlx::Error> {
+        sqlx::query_as!(
+            Connection,
+            "SELECT * FROM connections WHERE id = ?",
+            id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    pub async fn list_connections(&self) -> Result<Vec<Connection>, sqlx::Error> {
+        sqlx::query_as!(
+            Connection,
+            "SELECT * FROM connections ORDER BY created_at DESC"
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    // Dataset profile cache
+    pub async fn set_dataset_profile_status(
+        &self,
+        dataset_id: &str,
+        version: i64,
         status: &str,
     ) -> Result<(), sqlx::Error> {
         sqlx::query!(
-            "UPDATE experiments SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
-            status,
-            id
+            r#"
+            INSERT INTO dataset_profiles (dataset_id, version, status)
+            VALUES (?, ?, ?)
+            ON CONFLICT (dataset_id, version) DO UPDATE SET status = excluded.status
+            "#,
+            dataset_id,
+            version,
+            status
         )
         .execute(&*self.pool)
         .await?;
         Ok(())
     }
 
-    pub async fn add_experiment_log(
+    /// Marks the version's profile as pending unless it already is pending or
+    /// running, so concurrent requests queue one job; true if this call did.
+    pub async fn claim_dataset_profile(
         &self,
-        experiment_id: &str,
-        level: &str,
-        message: &str,
+        dataset_id: &str,
+        version: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO dataset_profiles (dataset_id, version, status)
+            VALUES (?, ?, 'pending')
+            ON CONFLICT (dataset_id, version) DO UPDATE SET status = 'pending'
+            WHERE dataset_profiles.status NOT IN ('pending', 'running')
+            "#,
+            dataset_id,
+            version
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn save_dataset_profile(
+        &self,
+        dataset_id: &str,
+        version: i64,
+        profile: &Value,
+        duration_ms: i64,
     ) -> Result<(), sqlx::Error> {
+        let profile_str = profile.to_string();
+        let now = chrono::Utc::now().naive_utc();
         sqlx::query!(
-            "INSERT INTO experiment_logs (experiment_id, level, message) VALUES (?, ?, ?)",
-            experiment_id,
-            level,
-            message
+            r#"
+            INSERT INTO dataset_profiles (dataset_id, version, status, profile, duration_ms, computed_at)
+            VALUES (?, ?, 'completed', ?, ?, ?)
+            ON CONFLICT (dataset_id, version) DO UPDATE SET
+                status = 'completed',
+                profile = excluded.profile,
+                error = NULL,
+                duration_ms = excluded.duration_ms,
+                computed_at = excluded.computed_at
+            "#,
+            dataset_id,
+            version,
+            profile_str,
+            duration_ms,
+            now
         )
         .execute(&*self.pool)
         .await?;
         Ok(())
     }
 
-    pub async fn get_experiment_logs(
+    pub async fn fail_dataset_profile(
         &self,
-        experiment_id: &str,
-        limit: Option<i64>,
-    ) -> Result<Vec<ExperimentLog>, sqlx::Error> {
-        let limit = limit.unwrap_or(100);
+        dataset_id: &str,
+        version: i64,
+        error: &str,
+        duration_ms: i64,
+    ) -> Result<(), sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+            UPDATE dataset_profiles
+            SET status = 'failed', error = ?, duration_ms = ?, computed_at = ?
+            WHERE dataset_id = ? AND version = ?
+            "#,
+            error,
+            duration_ms,
+            now,
+            dataset_id,
+            version
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_dataset_profile(
+        &self,
+        dataset_id: &str,
+        version: i64,
+    ) -> Result<Option<DatasetProfileRecord>, sqlx::Error> {
         sqlx::query_as!(
-            ExperimentLog,
-            "SELECT * FROM experiment_logs WHERE experiment_id = ? ORDER BY timestamp DESC LIMIT ?",
-            experiment_id,
-            limit
+            DatasetProfileRecord,
+            "SELECT * FROM dataset_profiles WHERE dataset_id = ? AND version = ?",
+            dataset_id,
+            version
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+    
+    // Experiment CRUD operations
+    pub async fn create_experiment(
+        &self,
+        name: &str,
+        notebook_id: Option<&str>,
+        dataset_id: Option<&str>,
+        parameters: Option<Value>,
+    ) -> Result<Experiment, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let experiment =
+            Self::insert_experiment(&mut tx, name, notebook_id, dataset_id, parameters.as_ref())
+                .await?;
+        tx.commit().await?;
+        Ok(experiment)
+    }
+
+    /// Inserts a pending experiment along with the indexed copy of its parameters.
+    async fn insert_experiment(
+        conn: &mut SqliteConnection,
+        name: &str,
+        notebook_id: Option<&str>,
+        dataset_id: Option<&str>,
+        parameters: Option<&Value>,
+    ) -> Result<Experiment, sqlx::Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().naive_utc();
+        let params_str = parameters.map(|p| p.to_string());
+
+        let experiment = sqlx::query_as!(
+            Experiment,
+            r#"
+            INSERT INTO experiments (
+                id, name, notebook_id, dataset_id, parameters, created_at, updated_at,
+                dataset_version
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, (SELECT version FROM datasets WHERE id = ?))
+            RETURNING *
+            "#,
+            id,
+            name,
+            notebook_id,
+            dataset_id,
+            params_str,
+            now,
+            now,
+            dataset_id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        // Indexed copy of the parameters for search
+        for (key, value) in parameters.map(search::param_values).unwrap_or_default() {
+            sqlx::query!(
+                "INSERT INTO experiment_params (experiment_id, key, value) VALUES (?, ?, ?)",
+                id,
+                key,
+                value
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(experiment)
+    }
+
+    pub async fn get_experiment(&self, id: &str) -> Result<Option<Experiment>, sqlx::Error> {
+        sqlx::query_as!(
+            Experiment,
+            "SELECT * FROM experiments WHERE id = ?",
+            id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// Merges `metrics` into the experiment's latest metrics. Numeric values are
+    /// also appended to their series at the next step.
+    pub async fn update_experiment_metrics(
+        &self,
+        id: &str,
+        metrics: Value,
+    ) -> Result<(), sqlx::Error> {
+        let metrics_str = metrics.to_string();
+        let points: Vec<MetricPoint> = search::metric_values(&metrics)
+            .into_iter()
+            .map(|(key, value)| MetricPoint {
+                key,
+                value,
+                step: None,
+                timestamp: None,
+            })
+            .collect();
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE experiments
+            SET metrics = json_patch(CASE WHEN json_valid(metrics) THEN metrics ELSE '{}' END, ?),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+            metrics_str,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+        let points = Self::record_metrics(&mut tx, id, &points).await?;
+        tx.commit().await?;
+        self.publish_metrics(id, points);
+        Ok(())
+    }
+
+    /// Appends points to the experiment's metric series.
+    pub async fn log_experiment_metrics(
+        &self,
+        experiment_id: &str,
+        points: &[MetricPoint],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let points = Self::record_metrics(&mut tx, experiment_id, points).await?;
+        tx.commit().await?;
+        self.publish_metrics(experiment_id, points);
+        Ok(())
+    }
+
+    fn publish_metrics(&self, experiment_id: &str, points: Vec<MetricPoint>) {
+        for point in points {
+            self.events.publish(ExperimentEvent::Metric {
+                experiment_id: experiment_id.to_string(),
+                point,
+            });
+        }
+    }
+
+    /// Inserts points, then refreshes the summaries of the keys they touch and the
+    /// latest values in `experiments.metrics`. Returns the points with their
+    /// steps and timestamps filled in.
+    async fn record_metrics(
+        conn: &mut SqliteConnection,
+        experiment_id: &str,
+        points: &[MetricPoint],
+    ) -> Result<Vec<MetricPoint>, sqlx::Error> {
+        if points.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let mut keys = std::collections::BTreeSet::new();
+        let mut recorded = Vec::with_capacity(points.len());
+        for point in points {
+            let timestamp = point.timestamp.unwrap_or(now);
+            let step = sqlx::query_scalar!(
+                r#"
+                INSERT INTO experiment_metrics (experiment_id, key, step, value, timestamp)
+                VALUES (?, ?, COALESCE(?, (
+                    SELECT COALESCE(MAX(step) + 1, 0) FROM experiment_metrics
+                    WHERE experiment_id = ? AND key = ?
+                )), ?, ?)
+                RETURNING step
+                "#,
+                experiment_id,
+                point.key,
+                point.step,
+                experiment_id,
+                point.key,
+                point.value,
+                timestamp
+            )
+            .fetch_one(&mut *conn)
+            .await?;
+            keys.insert(point.key.as_str());
+            recorded.push(MetricPoint {
+                step: Some(step),
+                timestamp: Some(timestamp),
+                ..point.clone()
+            });
+        }
+
+        for key in keys {
+            let minimize = MetricGoal::for_key(key) == MetricGoal::Minimize;
+            sqlx::query!(
+                r#"
+                INSERT INTO experiment_metric_summaries
+                    (experiment_id, key, value, step, best_value, min_value, max_value, count)
+                SELECT experiment_id, key,
+                    (SELECT l.value FROM experiment_metrics l
+                     WHERE l.experiment_id = m.experiment_id AND l.key = m.key
+                     ORDER BY l.step DESC, l.timestamp DESC, l.id DESC LIMIT 1),
+                    MAX(step),
+                    CASE WHEN ? THEN MIN(value) ELSE MAX(value) END,
+                    MIN(value), MAX(value), COUNT(*)
+                FROM experiment_metrics m
+                WHERE experiment_id = ? AND key = ?
+                GROUP BY experiment_id, key
+                ON CONFLICT (experiment_id, key) DO UPDATE SET
+                    value = excluded.value,
+                    step = excluded.step,
+                    best_value = excluded.best_value,
+                    min_value = excluded.min_value,
+                    max_value = excluded.max_value,
+                    count = excluded.count
+                "#,
+                minimize,
+                experiment_id,
+                key
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE experiments
+            SET metrics = json_patch(
+                    CASE WHEN json_valid(metrics) THEN metrics ELSE '{}' END,
+                    (SELECT json_group_object(key, value) FROM experiment_metric_summaries
+                     WHERE experiment_id = ?)
+                ),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+            experiment_id,
+            experiment_id
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(recorded)
+    }
+
+    pub async fn get_metric_summaries(
+        &self,
+        experiment_id: &str,
+    ) -> Result<Vec<ExperimentMetricSummary>, sqlx::Error> {
+        sqlx::query_as!(
+            ExperimentMetricSummary,
+            "SELECT * FROM experiment_metric_summaries WHERE experiment_id = ? ORDER BY key",
+            experiment_id
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// All points of one metric, ordered by step.
+    pub async fn get_metric_series(
+        &self,
+        experiment_id: &str,
+        key: &str,
+    ) -> Result<Vec<SeriesPoint>, sqlx::Error> {
+        sqlx::query_as!(
+            SeriesPoint,
+            r#"
+            SELECT step, value, timestamp FROM experiment_metrics
+            WHERE experiment_id = ? AND key = ?
+            ORDER BY step, timestamp, id
+            "#,
+            experiment_id,
+            key
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// One page of experiments matching the search, and the total number of matches.
+    pub async fn search_experiments(
+        &self,
+        search: &ExperimentSearch,
+    ) -> Result<(Vec<Experiment>, i64), sqlx::Error> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM experiments e");
+        search.push_where(&mut count);
+        let total: i64 = count.build_query_scalar().fetch_one(&*self.pool).await?;
+
+        let mut query = QueryBuilder::new("SELECT e.* FROM experiments e");
+        search.push_where(&mut query);
+        search.push_order_and_page(&mut query);
+        let experiments = query
+            .build_query_as::<Experiment>()
+            .fetch_all(&*self.pool)
+            .await?;
+
+        Ok((experiments, total))
+    }
+
+    pub async fn rename_experiment(&self, id: &str, name: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE experiments SET name = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            name,
+            id
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Merges a parameters object into the experiment's parameters, keeping
+    /// `parameters` and `experiment_params` in sync. Null values are ignored.
+    pub async fn log_experiment_params(&self, id: &str, params: &Value) -> Result<(), sqlx::Error> {
+        let values = search::param_values(params);
+        let params: serde_json::Map<String, Value> = params
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let params_str = Value::Object(params).to_string();
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE experiments
+            SET parameters = json_patch(
+                    CASE WHEN json_valid(parameters) THEN parameters ELSE '{}' END, ?
+                ),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+            params_str,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for (key, value) in values {
+            sqlx::query!(
+                r#"
+                INSERT INTO experiment_params (experiment_id, key, value) VALUES (?, ?, ?)
+                ON CONFLICT (experiment_id, key) DO UPDATE SET value = excluded.value
+                "#,
+                id,
+                key,
+                value
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn set_experiment_tags(
+        &self,
+        id: &str,
+        tags: &[(String, String)],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        Self::insert_experiment_tags(&mut tx, id, tags).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn insert_experiment_tags(
+        conn: &mut SqliteConnection,
+        id: &str,
+        tags: &[(String, String)],
+    ) -> Result<(), sqlx::Error> {
+        for (key, value) in tags {
+            sqlx::query!(
+                r#"
+                INSERT INTO experiment_tags (experiment_id, key, value) VALUES (?, ?, ?)
+                ON CONFLICT (experiment_id, key) DO UPDATE SET value = excluded.value
+                "#,
+                id,
+                key,
+                value
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_experiment_tags(&self, id: &str) -> Result<Vec<Tag>, sqlx::Error> {
+        sqlx::query_as!(
+            Tag,
+            "SELECT key, value FROM experiment_tags WHERE experiment_id = ? ORDER BY key",
+            id
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Latest point of each of the experiment's metrics.
+    pub async fn get_latest_metrics(
+        &self,
+        experiment_id: &str,
+    ) -> Result<Vec<MetricPoint>, sqlx::Error> {
+        sqlx::query_as!(
+            MetricPoint,
+            r#"
+            SELECT m.key, m.value, m.step AS "step?", m.timestamp AS "timestamp?"
+            FROM experiment_metric_summaries s
+            JOIN experiment_metrics m ON m.id = (
+                SELECT l.id FROM experiment_metrics l
+                WHERE l.experiment_id = s.experiment_id AND l.key = s.key
+                ORDER BY l.step DESC, l.timestamp DESC, l.id DESC LIMIT 1
+            )
+            WHERE s.experiment_id = ?
+            ORDER BY m.key
+            "#,
+            experiment_id
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Creates a running experiment as a run of an MLflow experiment.
+    pub async fn create_mlflow_run(
+        &self,
+        mlflow_experiment_id: i64,
+        name: &str,
+        started_at: NaiveDateTime,
+        tags: &[(String, String)],
+    ) -> Result<Experiment, sqlx::Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+
+        let mut tx = self.pool.begin().await?;
+        let experiment = sqlx::query_as!(
+            Experiment,
+            r#"
+            INSERT INTO experiments (id, name, status, created_at, updated_at, started_at, mlflow_experiment_id)
+            VALUES (?, ?, 'running', ?, ?, ?, ?)
+            RETURNING *
+            "#,
+            id,
+            name,
+            started_at,
+            started_at,
+            started_at,
+            mlflow_experiment_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        Self::insert_experiment_tags(&mut tx, &id, tags).await?;
+        tx.commit().await?;
+
+        Ok(experiment)
+    }
+
+    pub async fn create_mlflow_experiment(
+        &self,
+        name: &str,
+        artifact_location: Option<&str>,
+        tags: &[(String, String)],
+    ) -> Result<MlflowExperiment, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+
+        let mut tx = self.pool.begin().await?;
+        let experiment = sqlx::query_as!(
+            MlflowExperiment,
+            r#"
+            INSERT INTO mlflow_experiments (name, artifact_location, created_at, updated_at)
+            VALUES (?, ?, ?, ?)
+            RETURNING *
+            "#,
+            name,
+            artifact_location,
+            now,
+            now
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        for (key, value) in tags {
+            sqlx::query!(
+                "INSERT INTO mlflow_experiment_tags (experiment_id, key, value) VALUES (?, ?, ?)",
+                experiment.id,
+                key,
+                value
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(experiment)
+    }
+
+    pub async fn get_mlflow_experiment(
+        &self,
+        id: i64,
+    ) -> Result<Option<MlflowExperiment>, sqlx::Error> {
+        sqlx::query_as!(
+            MlflowExperiment,
+            "SELECT * FROM mlflow_experiments WHERE id = ?",
+            id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    pub async fn get_mlflow_experiment_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<MlflowExperiment>, sqlx::Error> {
+        sqlx::query_as!(
+            MlflowExperiment,
+            "SELECT * FROM mlflow_experiments WHERE name = ?",
+            name
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// MLflow experiments in the given lifecycle stages matching all conditions,
+    /// oldest first.
+    pub async fn search_mlflow_experiments(
+        &self,
+        lifecycle_stages: &[&str],
+        conditions: &[Condition],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<MlflowExperiment>, sqlx::Error> {
+        let mut query =
+            QueryBuilder::new("SELECT e.* FROM mlflow_experiments e WHERE e.lifecycle_stage IN (");
+        let mut stages = query.separated(", ");
+        for stage in lifecycle_stages {
+            stages.push_bind(stage.to_string());
+        }
+        stages.push_unseparated(")");
+        for condition in conditions {
+            query.push(" AND ");
+            condition.push_sql(&mut query);
+        }
+        query.push(" ORDER BY e.id LIMIT ");
+        query.push_bind(limit);
+        query.push(" OFFSET ");
+        query.push_bind(offset);
+
+        query
+            .build_query_as::<MlflowExperiment>()
+            .fetch_all(&*self.pool)
+            .await
+    }
+
+    pub async fn update_mlflow_experiment(
+        &self,
+        id: i64,
+        name: Option<&str>,
+        lifecycle_stage: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE mlflow_experiments
+            SET name = COALESCE(?, name),
+                lifecycle_stage = COALESCE(?, lifecycle_stage),
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+            name,
+            lifecycle_stage,
+            id
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_mlflow_experiment_tag(
+        &self,
+        id: i64,
+        key: &str,
+        value: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO mlflow_experiment_tags (experiment_id, key, value) VALUES (?, ?, ?)
+            ON CONFLICT (experiment_id, key) DO UPDATE SET value = excluded.value
+            "#,
+            id,
+            key,
+            value
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_mlflow_experiment_tags(&self, id: i64) -> Result<Vec<Tag>, sqlx::Error> {
+        sqlx::query_as!(
+            Tag,
+            "SELECT key, value FROM mlflow_experiment_tags WHERE experiment_id = ? ORDER BY key",
+            id
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Moves an experiment to `status` if the lifecycle allows it from the
+    /// current one; `None` when it doesn't or the experiment doesn't exist.
+    /// Use [`crate::tracking::status::transition`] to tell those apart.
+    pub async fn update_experiment_status(
+        &self,
+        id: &str,
+        status: ExperimentStatus,
+        failure_reason: Option<&str>,
+    ) -> Result<Option<Experiment>, sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        let from = status.previous();
+        let experiment =
+            Self::set_experiment_status(&mut conn, id, status, &from, failure_reason).await?;
+        if let Some(experiment) = &experiment {
+            self.events.publish(ExperimentEvent::status(experiment));
+        }
+        Ok(experiment)
+    }
+
+    /// Moves an experiment to `status` if it is in one of `from`.
+    async fn set_experiment_status(
+        conn: &mut SqliteConnection,
+        id: &str,
+        status: ExperimentStatus,
+        from: &[ExperimentStatus],
+        failure_reason: Option<&str>,
+    ) -> Result<Option<Experiment>, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let previous = serde_json::to_string(from).unwrap_or_default();
+        let running = status == ExperimentStatus::Running;
+        let terminal = status.is_terminal();
+        let failure_reason = failure_reason.filter(|_| status == ExperimentStatus::Failed);
+        let status = status.as_str();
+
+        // SET expressions see the row as it was before the update
+        sqlx::query_as!(
+            Experiment,
+            r#"
+            UPDATE experiments SET
+                status = ?1,
+                started_at = CASE WHEN ?2 THEN COALESCE(started_at, ?4) ELSE started_at END,
+                finished_at = CASE WHEN ?3 THEN ?4 ELSE NULL END,
+                duration_ms = CASE
+                    WHEN ?3 AND started_at IS NOT NULL
+                    THEN CAST(ROUND((julianday(?4) - julianday(started_at)) * 86400000) AS INTEGER)
+                    ELSE NULL
+                END,
+                failure_reason = ?5,
+                updated_at = ?4
+            WHERE id = ?6 AND status IN (SELECT value FROM json_each(?7))
+            RETURNING *
+            "#,
+            status,
+            running,
+            terminal,
+            now,
+            failure_reason,
+            id,
+            previous
+        )
+        .fetch_optional(&mut *conn)
+        .await
+    }
+
+    /// Stores what an experiment ran with. Parts already recorded are replaced
+    /// by the known parts of `record` if `overwrite` is set and kept otherwise.
+    pub async fn save_reproducibility(
+        &self,
+        record: &Reproducibility,
+        overwrite: bool,
+    ) -> Result<Reproducibility, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+
+        sqlx::query_as!(
+            Reproducibility,
+            r#"
+            INSERT INTO experiment_reproducibility (
+                experiment_id, environment_id, python_version, packages, git_commit, git_dirty,
+                notebook_revision, notebook_snapshot, script_path, script_sha256, seeds,
+                dataset_version, captured_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            ON CONFLICT (experiment_id) DO UPDATE SET
+                environment_id = IIF(?14, COALESCE(excluded.environment_id, environment_id), COALESCE(environment_id, excluded.environment_id)),
+                python_version = IIF(?14, COALESCE(excluded.python_version, python_version), COALESCE(python_version, excluded.python_version)),
+                packages = IIF(?14, COALESCE(excluded.packages, packages), COALESCE(packages, excluded.packages)),
+                git_commit = IIF(?14, COALESCE(excluded.git_commit, git_commit), COALESCE(git_commit, excluded.git_commit)),
+                git_dirty = IIF(?14, COALESCE(excluded.git_dirty, git_dirty), COALESCE(git_dirty, excluded.git_dirty)),
+                notebook_revision = IIF(?14, COALESCE(excluded.notebook_revision, notebook_revision), COALESCE(notebook_revision, excluded.notebook_revision)),
+                notebook_snapshot = IIF(?14, COALESCE(excluded.notebook_snapshot, notebook_snapshot), COALESCE(notebook_snapshot, excluded.notebook_snapshot)),
+                script_path = IIF(?14, COALESCE(excluded.script_path, script_path), COALESCE(script_path, excluded.script_path)),
+                script_sha256 = IIF(?14, COALESCE(excluded.script_sha256, script_sha256), COALESCE(script_sha256, excluded.script_sha256)),
+                seeds = IIF(?14, COALESCE(excluded.seeds, seeds), COALESCE(seeds, excluded.seeds)),
+                dataset_version = IIF(?14, COALESCE(excluded.dataset_version, dataset_version), COALESCE(dataset_version, excluded.dataset_version)),
+                captured_at = excluded.captured_at
+            RETURNING *
+            "#,
+            record.experiment_id,
+            record.environment_id,
+            record.python_version,
+            record.packages,
+            record.git_commit,
+            record.git_dirty,
+            record.notebook_revision,
+            record.notebook_snapshot,
+            record.script_path,
+            record.script_sha256,
+            record.seeds,
+            record.dataset_version,
+            now,
+            overwrite
+        )
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    pub async fn get_reproducibility(
+        &self,
+        experiment_id: &str,
+    ) -> Result<Option<Reproducibility>, sqlx::Error> {
+        sqlx::query_as!(
+            Reproducibility,
+            "SELECT * FROM experiment_reproducibility WHERE experiment_id = ?",
+            experiment_id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    pub async fn get_notebook_cells(&self, notebook_id: &str) -> Result<Vec<Cell>, sqlx::Error> {
+        sqlx::query_as!(
+            Cell,
+            "SELECT * FROM cells WHERE notebook_id = ? ORDER BY created_at",
+            notebook_id
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Records an artifact, replacing any earlier one at the same path.
+    pub async fn save_experiment_artifact(
+        &self,
+        experiment_id: &str,
+        path: &str,
+        sha256: &str,
+        size: i64,
+        mime_type: &str,
+    ) -> Result<ExperimentArtifact, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+
+        sqlx::query_as!(
+            ExperimentArtifact,
+            r#"
+            INSERT INTO experiment_artifacts (experiment_id, path, sha256, size, mime_type, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (experiment_id, path) DO UPDATE SET
+                sha256 = excluded.sha256,
+                size = excluded.size,
+                mime_type = excluded.mime_type,
+                created_at = excluded.created_at
+            RETURNING *
+            "#,
+            experiment_id,
+            path,
+            sha256,
+            size,
+            mime_type,
+            now
+        )
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    pub async fn list_experiment_artifacts(
+        &self,
+        experiment_id: &str,
+    ) -> Result<Vec<ExperimentArtifact>, sqlx::Error> {
+        sqlx::query_as!(
+            ExperimentArtifact,
+            "SELECT * FROM experiment_artifacts WHERE experiment_id = ? ORDER BY path",
+            experiment_id
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    pub async fn get_experiment_artifact(
+        &self,
+        experiment_id: &str,
+        path: &str,
+    ) -> Result<Option<ExperimentArtifact>, sqlx::Error> {
+        sqlx::query_as!(
+            ExperimentArtifact,
+            "SELECT * FROM experiment_artifacts WHERE experiment_id = ? AND path = ?",
+            experiment_id,
+            path
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    pub async fn add_experiment_log(
+        &self,
+        experiment_id: &str,
+        level: &str,
+        message: &str,
+    ) -> Result<ExperimentLog, sqlx::Error> {
+        let log = sqlx::query_as!(
+            ExperimentLog,
+            "INSERT INTO experiment_logs (experiment_id, level, message) VALUES (?, ?, ?) RETURNING *",
+            experiment_id,
+            level,
+            message
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+        self.events.publish(ExperimentEvent::Log(log.clone()));
+        Ok(log)
+    }
+
+    pub async fn get_experiment_logs(
+        &self,
+        experiment_id: &str,
+        query: &LogQuery,
+    ) -> Result<Vec<ExperimentLog>, sqlx::Error> {
+        query
+            .build(experiment_id)
+            .build_query_as::<ExperimentLog>()
+            .fetch_all(&*self.pool)
+            .await
+    }
+
+    /// Creates a child run of `parent`, on the same notebook and dataset; it
+    /// is never left without its parent, which a resumed study looks it up by.
+    pub async fn create_child_experiment(
+        &self,
+        parent: &Experiment,
+        name: &str,
+        parameters: Option<Value>,
+    ) -> Result<Experiment, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let child = Self::insert_experiment(
+            &mut tx,
+            name,
+            parent.notebook_id.as_deref(),
+            parent.dataset_id.as_deref(),
+            parameters.as_ref(),
+        )
+        .await?;
+        let child = sqlx::query_as!(
+            Experiment,
+            "UPDATE experiments SET parent_id = ? WHERE id = ? RETURNING *",
+            parent.id,
+            child.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(child)
+    }
+
+    /// Records runs that already finished as children of `parent`, in their
+    /// final status and with their metrics; all of them or, on error, none.
+    pub async fn create_finished_child_experiments(
+        &self,
+        parent: &Experiment,
+        runs: &[FinishedRun],
+    ) -> Result<Vec<Experiment>, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let mut tx = self.pool.begin().await?;
+        let mut created = Vec::with_capacity(runs.len());
+        let mut points = Vec::with_capacity(runs.len());
+        for run in runs {
+            let child = Self::insert_experiment(
+                &mut tx,
+                &run.name,
+                parent.notebook_id.as_deref(),
+                parent.dataset_id.as_deref(),
+                run.parameters.as_ref(),
+            )
+            .await?;
+            let status = run.status.as_str();
+            let failure_reason = run
+                .failure_reason
+                .as_deref()
+                .filter(|_| run.status == ExperimentStatus::Failed);
+            let metrics = run.metrics.to_string();
+            let child = sqlx::query_as!(
+                Experiment,
+                r#"
+                UPDATE experiments SET
+                    parent_id = ?1,
+                    status = ?2,
+                    metrics = ?3,
+                    started_at = ?4,
+                    finished_at = ?4,
+                    duration_ms = 0,
+                    failure_reason = ?5,
+                    updated_at = ?4
+                WHERE id = ?6
+                RETURNING *
+                "#,
+                parent.id,
+                status,
+                metrics,
+                now,
+                failure_reason,
+                child.id
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let series: Vec<MetricPoint> = search::metric_values(&run.metrics)
+                .into_iter()
+                .map(|(key, value)| MetricPoint {
+                    key,
+                    value,
+                    step: None,
+                    timestamp: Some(now),
+                })
+                .collect();
+            points.push(Self::record_metrics(&mut tx, &child.id, &series).await?);
+            created.push(child);
+        }
+        tx.commit().await?;
+
+        for (child, points) in created.iter().zip(points) {
+            self.events.publish(ExperimentEvent::status(child));
+            self.publish_metrics(&child.id, points);
+        }
+        Ok(created)
+    }
+
+    /// Child runs of an experiment, oldest first.
+    pub async fn list_child_experiments(
+        &self,
+        parent_id: &str,
+    ) -> Result<Vec<Experiment>, sqlx::Error> {
+        sqlx::query_as!(
+            Experiment,
+            "SELECT * FROM experiments WHERE parent_id = ? ORDER BY created_at, rowid",
+            parent_id
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    pub async fn set_experiment_model_path(
+        &self,
+        id: &str,
+        model_path: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE experiments SET model_path = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            model_path,
+            id
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn save_automl_result(
+        &self,
+        experiment_id: &str,
+        task_type: Option<&str>,
+        metric: Option<&str>,
+        results_path: Option<&str>,
+        feature_importances: Option<&Value>,
+    ) -> Result<AutoMLResult, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let feature_importances = feature_importances.map(|importances| importances.to_string());
+
+        sqlx::query_as!(
+            AutoMLResult,
+            r#"
+            INSERT INTO automl_results (
+                experiment_id, task_type, metric, results_path, feature_importances, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (experiment_id) DO UPDATE SET
+                task_type = excluded.task_type,
+                metric = excluded.metric,
+                results_path = excluded.results_path,
+                feature_importances = excluded.feature_importances,
+                created_at = excluded.created_at
+            RETURNING *
+            "#,
+            experiment_id,
+            task_type,
+            metric,
+            results_path,
+            feature_importances,
+            now
+        )
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    /// Records which budget stopped a search whose result is saved.
+    pub async fn set_automl_budget_exhausted(
+        &self,
+        experiment_id: &str,
+        budget: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE automl_results SET budget_exhausted = ? WHERE experiment_id = ?",
+            budget,
+            experiment_id
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_automl_result(
+        &self,
+        experiment_id: &str,
+    ) -> Result<Option<AutoMLResult>, sqlx::Error> {
+        sqlx::query_as!(
+            AutoMLResult,
+            "SELECT * FROM automl_results WHERE experiment_id = ?",
+            experiment_id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    pub async fn enqueue_job(
+        &self,
+        kind: JobKind,
+        payload: &str,
+        experiment_id: Option<&str>,
+        max_attempts: i64,
+    ) -> Result<Job, sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        Self::insert_job(&mut conn, kind, payload, experiment_id, max_attempts).await
+    }
+
+    /// Moves the experiment to queued and queues a job running it, together;
+    /// `None`, with nothing changed, if the experiment can't be queued.
+    pub async fn enqueue_experiment_job(
+        &self,
+        kind: JobKind,
+        payload: &str,
+        experiment_id: &str,
+        max_attempts: i64,
+    ) -> Result<Option<(Job, Experiment)>, sqlx::Error> {
+        let mut from = ExperimentStatus::Queued.previous();
+        from.push(ExperimentStatus::Queued);
+
+        let queued = ExperimentStatus::Queued;
+        let mut tx = self.pool.begin().await?;
+        let Some(experiment) =
+            Self::set_experiment_status(&mut tx, experiment_id, queued, &from, None).await?
+        else {
+            // Dropping the transaction rolls it back
+            return Ok(None);
+        };
+        let job =
+            Self::insert_job(&mut tx, kind, payload, Some(experiment_id), max_attempts).await?;
+        tx.commit().await?;
+
+        self.events.publish(ExperimentEvent::status(&experiment));
+        Ok(Some((job, experiment)))
+    }
+
+    async fn insert_job(
+        conn: &mut SqliteConnection,
+        kind: JobKind,
+        payload: &str,
+        experiment_id: Option<&str>,
+        max_attempts: i64,
+    ) -> Result<Job, sqlx::Error> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().naive_utc();
+        let kind = kind.as_str();
+
+        sqlx::query_as!(
+            Job,
+            r#"
+            INSERT INTO jobs (id, kind, payload, experiment_id, max_attempts, run_after, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+            RETURNING *
+            "#,
+            id,
+            kind,
+            payload,
+            experiment_id,
+            max_attempts,
+            now
+        )
+        .fetch_one(&mut *conn)
+        .await
+    }
+
+    /// Marks the queued job that is due longest as running and returns it,
+    /// among study jobs if `studies` is set and the others otherwise. The single
+    /// statement keeps two workers from claiming the same job.
+    pub async fn claim_next_job(&self, studies: bool) -> Result<Option<Job>, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let study = JobKind::Study.as_str();
+
+        sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE jobs SET
+                status = 'running',
+                attempts = attempts + 1,
+                started_at = ?1,
+                finished_at = NULL
+            WHERE id = (
+                SELECT id FROM jobs
+                WHERE status = 'queued' AND run_after <= ?1 AND (kind = ?2) = ?3
+                ORDER BY run_after, created_at
+                LIMIT 1
+            )
+            RETURNING *
+            "#,
+            now,
+            study,
+            studies
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    pub async fn get_job(&self, id: &str) -> Result<Option<Job>, sqlx::Error> {
+        sqlx::query_as!(Job, "SELECT * FROM jobs WHERE id = ?", id)
+            .fetch_optional(&*self.pool)
+            .await
+    }
+
+    /// Newest jobs first, optionally only those in `status` or of one experiment.
+    pub async fn list_jobs(
+        &self,
+        status: Option<JobStatus>,
+        experiment_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Job>, sqlx::Error> {
+        let status = status.map(JobStatus::as_str);
+
+        sqlx::query_as!(
+            Job,
+            r#"
+            SELECT * FROM jobs
+            WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR experiment_id = ?2)
+            ORDER BY created_at DESC
+            LIMIT ?3
+            "#,
+            status,
+            experiment_id,
+            limit
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    pub async fn finish_job(
+        &self,
+        id: &str,
+        status: JobStatus,
+        error: Option<&str>,
+    ) -> Result<Job, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let status = status.as_str();
+
+        sqlx::query_as!(
+            Job,
+            "UPDATE jobs SET status = ?, error = ?, finished_at = ? WHERE id = ? RETURNING *",
+            status,
+            error,
+            now,
+            id
+        )
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    /// Queues a failed attempt's job again, not to start before `run_after`.
+    pub async fn retry_job(
+        &self,
+        id: &str,
+        error: &str,
+        run_after: NaiveDateTime,
+    ) -> Result<Job, sqlx::Error> {
+        sqlx::query_as!(
+            Job,
+            "UPDATE jobs SET status = 'queued', error = ?, run_after = ? WHERE id = ? RETURNING *",
+            error,
+            run_after,
+            id
+        )
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    /// Cancels a queued job right away and asks the worker running a running
+    /// one to stop it. `None` if the job doesn't exist or already finished.
+    pub async fn request_job_cancel(&self, id: &str) -> Result<Option<Job>, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+
+        sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE jobs SET
+                cancel_requested = TRUE,
+                status = IIF(status = 'queued', 'cancelled', status),
+                finished_at = IIF(status = 'queued', ?1, finished_at)
+            WHERE id = ?2 AND status IN ('queued', 'running')
+            RETURNING *
+            "#,
+            now,
+            id
+        )
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// [`Self::request_job_cancel`] for every unfinished job of an experiment.
+    pub async fn cancel_experiment_jobs(
+        &self,
+        experiment_id: &str,
+    ) -> Result<Vec<Job>, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+
+        sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE jobs SET
+                cancel_requested = TRUE,
+                status = IIF(status = 'queued', 'cancelled', status),
+                finished_at = IIF(status = 'queued', ?1, finished_at)
+            WHERE experiment_id = ?2 AND status IN ('queued', 'running')
+            RETURNING *
+            "#,
+            now,
+            experiment_id
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    pub async fn is_job_cancel_requested(&self, id: &str) -> Result<bool, sqlx::Error> {
+        let cancel_requested = sqlx::query_scalar!(
+            r#"SELECT cancel_requested AS "cancel_requested: bool" FROM jobs WHERE id = ?"#,
+            id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(cancel_requested.unwrap_or(false))
+    }
+
+    /// Settles jobs left running by a previous process: queued again if they
+    /// have attempts left, cancelled if that was asked for, failed otherwise.
+    pub async fn requeue_interrupted_jobs(&self) -> Result<Vec<Job>, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+
+        sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE jobs SET
+                status = CASE
+                    WHEN cancel_requested THEN 'cancelled'
+                    WHEN attempts < max_attempts THEN 'queued'
+                    ELSE 'failed'
+                END,
+                error = 'Interrupted by a restart',
+                run_after = ?1,
+                finished_at = IIF(NOT cancel_requested AND attempts < max_attempts, NULL, ?1)
+            WHERE status = 'running'
+            RETURNING *
+            "#,
+            now
         )
         .fetch_all(&*self.pool)
         .await
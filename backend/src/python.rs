@@ -1,28 +1,137 @@
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use std::path::Path;
+use std::time::Duration;
 use tokio::process::Command;
 
+use crate::runtime::RuntimeEnvironment;
+
+/// Directory of the Python packages shipped with the backend, such as `openmind`.
+const PYTHON_PACKAGES_DIR: &str = "python";
+const DEFAULT_API_URL: &str = "http://127.0.0.1:3001/api";
+/// Interpreter used when `OPENMIND_PYTHON` doesn't name one.
+const DEFAULT_INTERPRETER: &str = "python3";
+/// Variables of the server's environment passed on to isolated code.
+const ISOLATED_ENV: &[&str] = &["PATH", "HOME", "LANG", "LC_ALL", "TMPDIR", "VIRTUAL_ENV"];
+
 #[derive(Debug)]
 pub struct PythonExecutor {
     python_path: String,
+    env: Vec<(String, String)>,
+    isolated: bool,
+    timeout: Option<Duration>,
+}
+
+/// Interpreter of the local environments: `OPENMIND_PYTHON`, or `python3`.
+pub fn default_interpreter() -> String {
+    std::env::var("OPENMIND_PYTHON").unwrap_or_else(|_| DEFAULT_INTERPRETER.to_string())
 }
 
 impl PythonExecutor {
     pub fn new() -> Self {
-        // Default to 'python3', can be overridden in config
         Self {
-            python_path: "python3".to_string(),
+            python_path: default_interpreter(),
+            env: Vec::new(),
+            isolated: false,
+            timeout: None,
+        }
+    }
+
+    /// An executor using the interpreter of `environment`.
+    pub fn for_environment(environment: &RuntimeEnvironment) -> Self {
+        Self {
+            python_path: environment.python_path.clone(),
+            ..Self::new()
+        }
+    }
+
+    /// Runs code without the server's environment, which holds its secrets,
+    /// passing on only what an interpreter needs, such as `PATH`.
+    pub fn isolated(mut self) -> Self {
+        self.isolated = true;
+        self
+    }
+
+    /// Kills code that runs longer than `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Lets code run by this executor import `openmind.track` and report runs
+    /// as belonging to the given notebook.
+    pub fn with_notebook(self, notebook_id: &str) -> Self {
+        let mut executor = self.with_tracking();
+        executor.set_env("OPENMIND_NOTEBOOK_ID", notebook_id);
+        executor
+    }
+
+    /// Makes `openmind.track.start_run` report to an existing experiment instead
+    /// of creating one, seeding random number generators with `seeds` (a JSON
+    /// object of library to seed).
+    pub fn with_run(self, experiment_id: &str, seeds: Option<&str>) -> Self {
+        let mut executor = self.with_tracking();
+        executor.set_env("OPENMIND_RUN_ID", experiment_id);
+        if let Some(seeds) = seeds {
+            executor.set_env("OPENMIND_SEEDS", seeds);
         }
+        executor
+    }
+
+    fn with_tracking(mut self) -> Self {
+        let packages = std::fs::canonicalize(PYTHON_PACKAGES_DIR)
+            .unwrap_or_else(|_| PYTHON_PACKAGES_DIR.into());
+        let mut paths = vec![packages];
+        if let Some(existing) = std::env::var_os("PYTHONPATH") {
+            paths.extend(std::env::split_paths(&existing));
+        }
+        let python_path = std::env::join_paths(paths)
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_else(|_| PYTHON_PACKAGES_DIR.to_string());
+        let api_url =
+            std::env::var("OPENMIND_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
+
+        self.set_env("PYTHONPATH", &python_path);
+        self.set_env("OPENMIND_API_URL", &api_url);
+        self
+    }
+
+    fn set_env(&mut self, key: &str, value: &str) {
+        self.env.retain(|(existing, _)| existing != key);
+        self.env.push((key.to_string(), value.to_string()));
+    }
+
+    /// Command running the interpreter with the executor's environment.
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.python_path);
+        if self.isolated {
+            command.env_clear();
+            for key in ISOLATED_ENV {
+                if let Some(value) = std::env::var_os(key) {
+                    command.env(key, value);
+                }
+            }
+        }
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        command
     }
 
     pub async fn execute_code(&self, code: &str) -> Result<String, String> {
-        let output = Command::new(&self.python_path)
-            .arg("-c")
-            .arg(code)
-            .output()
-            .await
-            .map_err(|e| format!("Failed to execute Python: {}", e))?;
+        let mut command = self.command();
+        // Dropping the output future on timeout kills the interpreter
+        command.arg("-c").arg(code).kill_on_drop(true);
+        let output = match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, command.output())
+                .await
+                .map_err(|_| {
+                    format!(
+                        "Python execution timed out after {} seconds",
+                        timeout.as_secs()
+                    )
+                })?,
+            None => command.output().await,
+        }
+        .map_err(|e| format!("Failed to execute Python: {}", e))?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -32,6 +141,35 @@ impl PythonExecutor {
         }
     }
 
+    /// Version of the interpreter, e.g. `3.11.7`.
+    pub async fn python_version(&self) -> Option<String> {
+        let output = self.command().arg("--version").output().await.ok()?;
+        // Python 2 printed the version to stderr
+        let version = if output.stdout.is_empty() {
+            output.stderr
+        } else {
+            output.stdout
+        };
+        String::from_utf8_lossy(&version)
+            .trim()
+            .strip_prefix("Python ")
+            .map(str::to_string)
+    }
+
+    /// Installed packages as `pip freeze` lists them.
+    pub async fn pip_freeze(&self) -> Option<String> {
+        let output = self
+            .command()
+            .args(["-m", "pip", "freeze"])
+            .output()
+            .await
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
     pub async fn execute_notebook_cell(
         &self,
         notebook_path: &str,
@@ -93,7 +231,8 @@ print('\n'.join(outputs))
         dataset_path: &str,
         output_path: &str,
     ) -> Result<String, String> {
-        let output = Command::new(&self.python_path)
+        let output = self
+            .command()
             .arg(script_path)
             .arg("--dataset")
             .arg(dataset_path)
@@ -123,4 +262,29 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().trim(), "4");
     }
+
+    #[tokio::test]
+    async fn test_notebook_id_is_injected() {
+        let executor = PythonExecutor::new().with_notebook("nb-1");
+        let result = executor
+            .execute_code("import os; print(os.environ['OPENMIND_NOTEBOOK_ID'])")
+            .await;
+        assert_eq!(result.unwrap().trim(), "nb-1");
+    }
+
+    #[tokio::test]
+    async fn test_isolated_code_is_limited() {
+        std::env::set_var("OPENMIND_TEST_SECRET", "hunter2");
+        let executor = PythonExecutor::new()
+            .isolated()
+            .with_notebook("nb-1")
+            .with_timeout(Duration::from_secs(1));
+        let result = executor
+            .execute_code("import os; print(os.environ.get('OPENMIND_TEST_SECRET'), os.environ['OPENMIND_NOTEBOOK_ID'])")
+            .await;
+        assert_eq!(result.unwrap().trim(), "None nb-1");
+
+        let result = executor.execute_code("import time; time.sleep(5)").await;
+        assert!(result.unwrap_err().contains("timed out"));
+    }
 }
@@ -24,6 +24,9 @@ pub struct RuntimeEnvironment {
     pub cpu_count: Option<usize>,
     pub memory_gb: Option<f64>,
     pub gpu_info: Option<GpuInfo>,
+    /// Python interpreter code run in this environment uses
+    #[serde(default = "crate::python::default_interpreter")]
+    pub python_path: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,6 +118,7 @@ impl EnvironmentManager {
             cpu_count: Some(cpu_count),
             memory_gb: Some(memory_gb),
             gpu_info: None,
+            python_path: crate::python::default_interpreter(),
         })
     }
     
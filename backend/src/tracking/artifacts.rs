@@ -0,0 +1,361 @@
+//! Experiment artifacts (models, plots, predictions) stored content-addressed on
+//! disk: each distinct file is kept once under its SHA-256, and experiments refer
+//! to it by a relative path of their choosing.
+
+use anyhow::{bail, Context, Result};
+use ring::digest;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::models::{Database, ExperimentArtifact};
+
+const ARTIFACTS_DIR: &str = "data/artifacts";
+/// Where training scripts write an experiment's outputs, one directory per experiment id.
+const EXPERIMENT_OUTPUTS_DIR: &str = "data/experiments";
+const MAX_PATH_LEN: usize = 512;
+/// Size of the chunks files are copied into the store in.
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A file written to the store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredBlob {
+    pub sha256: String,
+    pub size: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl Default for ArtifactStore {
+    fn default() -> Self {
+        Self::new(ARTIFACTS_DIR)
+    }
+}
+
+impl ArtifactStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Where the content with the given hash lives.
+    pub fn blob_path(&self, sha256: &str) -> PathBuf {
+        self.root.join(&sha256[..2]).join(sha256)
+    }
+
+    /// Starts writing a new blob; chunks are hashed as they are written.
+    pub async fn writer(&self) -> Result<BlobWriter> {
+        let tmp_dir = self.root.join("tmp");
+        tokio::fs::create_dir_all(&tmp_dir).await?;
+        let tmp_path = tmp_dir.join(Uuid::new_v4().to_string());
+        let file = tokio::fs::File::create(&tmp_path).await?;
+        Ok(BlobWriter {
+            store: self.clone(),
+            file,
+            tmp_path: TmpPath(tmp_path),
+            hasher: digest::Context::new(&digest::SHA256),
+            size: 0,
+        })
+    }
+
+    pub async fn put(&self, bytes: &[u8]) -> Result<StoredBlob> {
+        let mut writer = self.writer().await?;
+        writer.write(bytes).await?;
+        writer.finish().await
+    }
+
+    /// Stores the file at `path`, streaming it rather than reading it whole.
+    pub async fn put_file(&self, path: &Path) -> Result<StoredBlob> {
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut writer = self.writer().await?;
+        let mut chunk = vec![0; COPY_CHUNK_SIZE];
+        loop {
+            let read = file.read(&mut chunk).await?;
+            if read == 0 {
+                break;
+            }
+            writer.write(&chunk[..read]).await?;
+        }
+        writer.finish().await
+    }
+
+    /// Stores every file under `dir`, returning each one's path relative to `dir`.
+    /// Used to index what training scripts write into an experiment's output directory.
+    pub async fn import_dir(&self, dir: &Path) -> Result<Vec<(String, StoredBlob)>> {
+        let mut imported = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(current) = pending.pop() {
+            let mut entries = tokio::fs::read_dir(&current).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let relative = path
+                    .strip_prefix(dir)
+                    .expect("walked from dir")
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                imported.push((relative, self.put_file(&path).await?));
+            }
+        }
+        imported.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(imported)
+    }
+}
+
+/// Directory a training script writes an experiment's outputs to.
+pub fn experiment_output_dir(experiment_id: &str) -> PathBuf {
+    Path::new(EXPERIMENT_OUTPUTS_DIR).join(experiment_id)
+}
+
+/// Indexes the files a training script wrote to `data/experiments/<id>` as artifacts.
+pub async fn import_experiment_outputs(
+    db: &Database,
+    experiment_id: &str,
+) -> Result<Vec<ExperimentArtifact>> {
+    let dir = experiment_output_dir(experiment_id);
+    if !tokio::fs::try_exists(&dir).await? {
+        return Ok(Vec::new());
+    }
+
+    let mut artifacts = Vec::new();
+    for (path, blob) in ArtifactStore::default().import_dir(&dir).await? {
+        let artifact = db
+            .save_experiment_artifact(
+                experiment_id,
+                &path,
+                &blob.sha256,
+                blob.size,
+                guess_mime_type(&path),
+            )
+            .await?;
+        artifacts.push(artifact);
+    }
+    Ok(artifacts)
+}
+
+/// A blob being written. The temporary file is removed if the writer is
+/// dropped, or fails, before `finish` moves it into place.
+pub struct BlobWriter {
+    store: ArtifactStore,
+    file: tokio::fs::File,
+    tmp_path: TmpPath,
+    hasher: digest::Context,
+    size: i64,
+}
+
+/// Deletes the file at the path when dropped, unless it was moved away.
+struct TmpPath(PathBuf);
+
+impl Drop for TmpPath {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.0) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove {}: {}", self.0.display(), err);
+            }
+        }
+    }
+}
+
+impl BlobWriter {
+    pub async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.hasher.update(chunk);
+        self.size += chunk.len() as i64;
+        self.file.write_all(chunk).await?;
+        Ok(())
+    }
+
+    /// Moves the blob into place. Identical content already stored is reused.
+    pub async fn finish(mut self) -> Result<StoredBlob> {
+        self.file.flush().await?;
+        drop(self.file);
+
+        let sha256: String = self
+            .hasher
+            .finish()
+            .as_ref()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let blob_path = self.store.blob_path(&sha256);
+        // Content already stored is left for `tmp_path` to remove
+        if !tokio::fs::try_exists(&blob_path).await? {
+            let parent = blob_path.parent().expect("blob paths have a parent");
+            tokio::fs::create_dir_all(parent).await?;
+            tokio::fs::rename(&self.tmp_path.0, &blob_path)
+                .await
+                .context("Failed to move artifact into the store")?;
+        }
+
+        Ok(StoredBlob {
+            sha256,
+            size: self.size,
+        })
+    }
+}
+
+/// Checks an artifact path given by a client: relative, `/`-separated and
+/// without `.`/`..` segments or control characters, which couldn't be sent
+/// back in a download's `Content-Disposition`.
+pub fn normalize_path(path: &str) -> Result<String> {
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        bail!("Artifact path is empty");
+    }
+    if path.len() > MAX_PATH_LEN {
+        bail!("Artifact path is longer than {} characters", MAX_PATH_LEN);
+    }
+    if path.chars().any(|c| c == '\\' || c.is_control()) {
+        bail!("Artifact path contains invalid characters");
+    }
+    if path
+        .split('/')
+        .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        bail!("Artifact path must not contain empty, '.' or '..' segments");
+    }
+    Ok(path.to_string())
+}
+
+/// Mime type from the file extension, for clients that don't send one.
+pub fn guess_mime_type(path: &str) -> &'static str {
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "csv" => "text/csv",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "yaml" | "yml" => "application/yaml",
+        "parquet" => "application/vnd.apache.parquet",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        _ => "application/octet-stream",
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Requested range is not satisfiable")]
+pub struct RangeNotSatisfiable;
+
+/// Parses a single-range `Range` header into an inclusive byte range.
+///
+/// Returns `Ok(None)` when the whole content should be sent: no header, a unit
+/// other than bytes, or multiple ranges (which we don't serve as multipart).
+pub fn parse_range(
+    header: Option<&str>,
+    size: u64,
+) -> Result<Option<(u64, u64)>, RangeNotSatisfiable> {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return Ok(None);
+    };
+
+    let range = match (start.trim(), end.trim()) {
+        // Last N bytes
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().map_err(|_| RangeNotSatisfiable)?;
+            if suffix == 0 || size == 0 {
+                return Err(RangeNotSatisfiable);
+            }
+            (size.saturating_sub(suffix), size - 1)
+        }
+        (start, "") => {
+            let start: u64 = start.parse().map_err(|_| RangeNotSatisfiable)?;
+            (start, size.saturating_sub(1))
+        }
+        (start, end) => {
+            let start: u64 = start.parse().map_err(|_| RangeNotSatisfiable)?;
+            let end: u64 = end.parse().map_err(|_| RangeNotSatisfiable)?;
+            if end < start {
+                return Err(RangeNotSatisfiable);
+            }
+            (start, end.min(size.saturating_sub(1)))
+        }
+    };
+
+    if range.0 >= size {
+        return Err(RangeNotSatisfiable);
+    }
+    Ok(Some(range))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_store_dedupes_content() {
+        let dir = tempdir().unwrap();
+        let store = ArtifactStore::new(dir.path());
+
+        let first = store.put(b"model weights").await.unwrap();
+        let second = store.put(b"model weights").await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.size, 13);
+        assert_eq!(
+            tokio::fs::read(store.blob_path(&first.sha256))
+                .await
+                .unwrap(),
+            b"model weights"
+        );
+
+        // Files are imported by streaming them; abandoned writes leave nothing behind
+        let outputs = tempdir().unwrap();
+        tokio::fs::create_dir(outputs.path().join("plots"))
+            .await
+            .unwrap();
+        tokio::fs::write(outputs.path().join("plots/roc.png"), b"model weights")
+            .await
+            .unwrap();
+        let imported = store.import_dir(outputs.path()).await.unwrap();
+        assert_eq!(imported, vec![("plots/roc.png".to_string(), first.clone())]);
+        let mut abandoned = store.writer().await.unwrap();
+        abandoned.write(b"partial").await.unwrap();
+        drop(abandoned);
+        let mut tmp = tokio::fs::read_dir(dir.path().join("tmp")).await.unwrap();
+        assert!(tmp.next_entry().await.unwrap().is_none());
+
+        assert_eq!(parse_range(Some("bytes=0-4"), 13), Ok(Some((0, 4))));
+        assert_eq!(parse_range(Some("bytes=10-"), 13), Ok(Some((10, 12))));
+        assert_eq!(parse_range(Some("bytes=-3"), 13), Ok(Some((10, 12))));
+        assert_eq!(parse_range(Some("bytes=5-100"), 13), Ok(Some((5, 12))));
+        assert_eq!(parse_range(Some("bytes=0-1,4-5"), 13), Ok(None));
+        assert_eq!(parse_range(Some("bytes=13-"), 13), Err(RangeNotSatisfiable));
+
+        assert_eq!(normalize_path("/plots/roc.png").unwrap(), "plots/roc.png");
+        assert!(normalize_path("../secret.key").is_err());
+        assert!(normalize_path("a//b").is_err());
+        for path in [
+            "plots/roc\n.png",
+            "roc\r\n.png",
+            "model\0.pkl",
+            "logs/\x1b[31m.txt",
+        ] {
+            assert!(normalize_path(path).is_err(), "{:?}", path);
+        }
+        assert_eq!(guess_mime_type("plots/roc.PNG"), "image/png");
+    }
+}
@@ -0,0 +1,254 @@
+//! Side-by-side comparison of experiments: aligned parameters and metrics.
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::metrics::{MetricGoal, MetricSeries};
+use crate::models::{Experiment, ExperimentMetricSummary};
+
+pub const MAX_COMPARED_EXPERIMENTS: usize = 20;
+
+#[derive(Debug, Serialize)]
+pub struct ComparedExperiment {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub dataset_id: Option<String>,
+    pub dataset_version: Option<i64>,
+    pub created_at: NaiveDateTime,
+    /// Wall-clock time from start to finish; `None` while unfinished
+    pub duration_seconds: Option<f64>,
+}
+
+/// One parameter across the compared experiments, in the order of `experiments`.
+#[derive(Debug, Serialize)]
+pub struct ParameterRow {
+    pub key: String,
+    pub values: Vec<Option<Value>>,
+    /// Set when any experiment has another value or lacks the parameter
+    pub differs: bool,
+}
+
+/// One metric across the compared experiments, in the order of `experiments`.
+#[derive(Debug, Serialize)]
+pub struct MetricRow {
+    pub key: String,
+    pub goal: MetricGoal,
+    pub latest: Vec<Option<f64>>,
+    pub best: Vec<Option<f64>>,
+    /// Experiment with the best value of all
+    pub best_experiment_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentSeries {
+    pub experiment_id: String,
+    #[serde(flatten)]
+    pub series: MetricSeries,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Comparison {
+    pub experiments: Vec<ComparedExperiment>,
+    pub parameters: Vec<ParameterRow>,
+    pub metrics: Vec<MetricRow>,
+    /// Series of every compared experiment per metric key, for overlay charts
+    pub series: BTreeMap<String, Vec<ExperimentSeries>>,
+}
+
+impl Comparison {
+    /// `summaries` and `series` hold one entry per experiment, in the same order.
+    pub fn new(
+        experiments: &[Experiment],
+        summaries: &[Vec<ExperimentMetricSummary>],
+        series: Vec<Vec<MetricSeries>>,
+    ) -> Self {
+        let parameters: Vec<BTreeMap<String, Value>> = experiments
+            .iter()
+            .map(|experiment| {
+                experiment
+                    .parameters
+                    .as_deref()
+                    .and_then(|p| serde_json::from_str::<BTreeMap<String, Value>>(p).ok())
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let keys: BTreeSet<&String> = parameters.iter().flat_map(|p| p.keys()).collect();
+        let parameter_rows = keys
+            .into_iter()
+            .map(|key| {
+                let values: Vec<Option<Value>> =
+                    parameters.iter().map(|p| p.get(key).cloned()).collect();
+                let differs = values.windows(2).any(|pair| pair[0] != pair[1]);
+                ParameterRow {
+                    key: key.clone(),
+                    values,
+                    differs,
+                }
+            })
+            .collect();
+
+        let keys: BTreeSet<&String> = summaries.iter().flatten().map(|s| &s.key).collect();
+        let metric_rows = keys
+            .into_iter()
+            .map(|key| {
+                let goal = MetricGoal::for_key(key);
+                let found: Vec<Option<&ExperimentMetricSummary>> = summaries
+                    .iter()
+                    .map(|s| s.iter().find(|summary| &summary.key == key))
+                    .collect();
+                let best: Vec<Option<f64>> =
+                    found.iter().map(|s| s.map(|s| s.best_value)).collect();
+                let best_experiment_id = best
+                    .iter()
+                    .zip(experiments)
+                    .filter_map(|(value, experiment)| value.map(|v| (v, &experiment.id)))
+                    .reduce(|a, b| {
+                        let b_is_better = match goal {
+                            MetricGoal::Minimize => b.0 < a.0,
+                            MetricGoal::Maximize => b.0 > a.0,
+                        };
+                        if b_is_better {
+                            b
+                        } else {
+                            a
+                        }
+                    })
+                    .map(|(_, id)| id.clone());
+                MetricRow {
+                    key: key.clone(),
+                    goal,
+                    latest: found.iter().map(|s| s.map(|s| s.value)).collect(),
+                    best,
+                    best_experiment_id,
+                }
+            })
+            .collect();
+
+        let mut overlays: BTreeMap<String, Vec<ExperimentSeries>> = BTreeMap::new();
+        for (experiment, series) in experiments.iter().zip(series) {
+            for series in series {
+                overlays
+                    .entry(series.key.clone())
+                    .or_default()
+                    .push(ExperimentSeries {
+                        experiment_id: experiment.id.clone(),
+                        series,
+                    });
+            }
+        }
+
+        Self {
+            experiments: experiments.iter().map(ComparedExperiment::from).collect(),
+            parameters: parameter_rows,
+            metrics: metric_rows,
+            series: overlays,
+        }
+    }
+}
+
+impl From<&Experiment> for ComparedExperiment {
+    fn from(experiment: &Experiment) -> Self {
+        Self {
+            id: experiment.id.clone(),
+            name: experiment.name.clone(),
+            status: experiment.status.clone(),
+            dataset_id: experiment.dataset_id.clone(),
+            dataset_version: experiment.dataset_version,
+            created_at: experiment.created_at,
+            duration_seconds: experiment.duration_ms.map(|ms| ms as f64 / 1000.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn experiment(id: &str, status: &str, parameters: &str) -> Experiment {
+        let created_at = NaiveDateTime::default();
+        let finished = status != "running";
+        Experiment {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            notebook_id: None,
+            status: status.to_string(),
+            metrics: None,
+            parameters: Some(parameters.to_string()),
+            dataset_id: Some("sales".to_string()),
+            model_path: None,
+            created_at,
+            updated_at: created_at + chrono::Duration::seconds(90),
+            dataset_version: Some(2),
+            mlflow_experiment_id: None,
+            started_at: Some(created_at),
+            finished_at: finished.then(|| created_at + chrono::Duration::seconds(90)),
+            duration_ms: finished.then_some(90_000),
+            failure_reason: None,
+            parent_id: None,
+        }
+    }
+
+    fn summary(
+        experiment_id: &str,
+        key: &str,
+        value: f64,
+        best_value: f64,
+    ) -> ExperimentMetricSummary {
+        ExperimentMetricSummary {
+            experiment_id: experiment_id.to_string(),
+            key: key.to_string(),
+            value,
+            step: 0,
+            best_value,
+            min_value: value.min(best_value),
+            max_value: value.max(best_value),
+            count: 2,
+        }
+    }
+
+    #[test]
+    fn test_comparison_aligns_runs() {
+        let experiments = vec![
+            experiment("a", "completed", r#"{"model": "rf", "max_depth": 8}"#),
+            experiment(
+                "b",
+                "running",
+                r#"{"model": "rf", "max_depth": 4, "seed": 1}"#,
+            ),
+        ];
+        let summaries = vec![
+            vec![
+                summary("a", "accuracy", 0.90, 0.91),
+                summary("a", "loss", 0.3, 0.25),
+            ],
+            vec![summary("b", "accuracy", 0.93, 0.93)],
+        ];
+
+        let comparison = Comparison::new(&experiments, &summaries, vec![Vec::new(), Vec::new()]);
+
+        let differs: Vec<(&str, bool)> = comparison
+            .parameters
+            .iter()
+            .map(|row| (row.key.as_str(), row.differs))
+            .collect();
+        assert_eq!(
+            differs,
+            vec![("max_depth", true), ("model", false), ("seed", true)]
+        );
+
+        let accuracy = &comparison.metrics[0];
+        assert_eq!(accuracy.latest, vec![Some(0.90), Some(0.93)]);
+        assert_eq!(accuracy.best_experiment_id.as_deref(), Some("b"));
+        let loss = &comparison.metrics[1];
+        assert_eq!(loss.best, vec![Some(0.25), None]);
+        assert_eq!(loss.best_experiment_id.as_deref(), Some("a"));
+
+        assert_eq!(comparison.experiments[0].duration_seconds, Some(90.0));
+        assert_eq!(comparison.experiments[1].duration_seconds, None);
+    }
+}
@@ -0,0 +1,177 @@
+//! Live experiment events: log lines, metric points and status changes as
+//! they are written, for tailing a run.
+
+use serde::Serialize;
+use tokio::sync::{broadcast, mpsc};
+
+use super::logs::{LogCursor, LogQuery};
+use super::metrics::MetricPoint;
+use super::status::ExperimentStatus;
+use crate::models::{Database, Experiment, ExperimentLog};
+
+/// Events a slow subscriber may fall behind by before it misses some.
+const EVENT_BUFFER: usize = 1024;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExperimentEvent {
+    Log(ExperimentLog),
+    Metric {
+        experiment_id: String,
+        #[serde(flatten)]
+        point: MetricPoint,
+    },
+    Status {
+        experiment_id: String,
+        status: String,
+        failure_reason: Option<String>,
+    },
+}
+
+impl ExperimentEvent {
+    pub fn status(experiment: &Experiment) -> Self {
+        ExperimentEvent::Status {
+            experiment_id: experiment.id.clone(),
+            status: experiment.status.clone(),
+            failure_reason: experiment.failure_reason.clone(),
+        }
+    }
+
+    pub fn experiment_id(&self) -> &str {
+        match self {
+            ExperimentEvent::Log(log) => &log.experiment_id,
+            ExperimentEvent::Metric { experiment_id, .. }
+            | ExperimentEvent::Status { experiment_id, .. } => experiment_id,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExperimentEvent::Log(_) => "log",
+            ExperimentEvent::Metric { .. } => "metric",
+            ExperimentEvent::Status { .. } => "status",
+        }
+    }
+
+    /// Whether the experiment finished with this event.
+    pub fn is_final(&self) -> bool {
+        match self {
+            ExperimentEvent::Status { status, .. } => status
+                .parse::<ExperimentStatus>()
+                .map_or(false, ExperimentStatus::is_terminal),
+            _ => false,
+        }
+    }
+}
+
+/// Fans events of all experiments out to subscribers. Publishing without
+/// subscribers drops the event.
+#[derive(Debug, Clone)]
+pub struct ExperimentEvents {
+    sender: broadcast::Sender<ExperimentEvent>,
+}
+
+impl Default for ExperimentEvents {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+}
+
+impl ExperimentEvents {
+    pub fn publish(&self, event: ExperimentEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ExperimentEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// Sends an experiment's log lines matching `query` after its cursor, then its
+/// new lines, metric points and status changes as they happen. Ends with the
+/// status event of the experiment finishing, or when `tx` is closed.
+pub async fn tail(
+    db: &Database,
+    experiment_id: &str,
+    mut query: LogQuery,
+    tx: mpsc::Sender<ExperimentEvent>,
+) -> Result<(), sqlx::Error> {
+    // Subscribe before replaying so lines written meanwhile aren't missed
+    let mut events = db.events().subscribe();
+    let Some(experiment) = db.get_experiment(experiment_id).await? else {
+        return Ok(());
+    };
+    if !replay(db, experiment_id, &mut query, &tx).await? {
+        return Ok(());
+    }
+    let status = ExperimentEvent::status(&experiment);
+    if status.is_final() {
+        let _ = tx.send(status).await;
+        return Ok(());
+    }
+
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = tx.closed() => return Ok(()),
+        };
+        let event = match event {
+            Ok(event) if event.experiment_id() == experiment_id => event,
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(_)) => {
+                // Log lines and the status can be read back; skipped metric
+                // points are in the series
+                if !replay(db, experiment_id, &mut query, &tx).await? {
+                    return Ok(());
+                }
+                match db.get_experiment(experiment_id).await? {
+                    Some(experiment) => ExperimentEvent::status(&experiment),
+                    None => return Ok(()),
+                }
+            }
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        };
+
+        if let ExperimentEvent::Log(log) = &event {
+            // Lines may already have been replayed
+            let id = log.id;
+            match query.cursor {
+                LogCursor::After(after) if id <= after => continue,
+                _ => query.cursor = LogCursor::After(id),
+            }
+            if !query.matches(log) {
+                continue;
+            }
+        }
+        let finished = event.is_final();
+        if tx.send(event).await.is_err() || finished {
+            return Ok(());
+        }
+    }
+}
+
+/// Sends the stored lines after the query's cursor, moving it past them.
+/// Returns false once the receiver is gone.
+async fn replay(
+    db: &Database,
+    experiment_id: &str,
+    query: &mut LogQuery,
+    tx: &mpsc::Sender<ExperimentEvent>,
+) -> Result<bool, sqlx::Error> {
+    loop {
+        let logs = db.get_experiment_logs(experiment_id, query).await?;
+        let done = (logs.len() as i64) < query.limit();
+        if let Some(cursor) = query.next_cursor(&logs) {
+            query.cursor = LogCursor::After(cursor);
+        }
+        for log in logs {
+            if tx.send(ExperimentEvent::Log(log)).await.is_err() {
+                return Ok(false);
+            }
+        }
+        if done {
+            return Ok(true);
+        }
+    }
+}
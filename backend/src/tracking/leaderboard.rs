@@ -0,0 +1,161 @@
+//! Ranking the child runs of an experiment, such as the candidates of an
+//! AutoML search, by one of their metrics.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use super::metrics::MetricGoal;
+use crate::models::Experiment;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardEntry {
+    /// 1 for the best run; `None` for runs without the metric
+    pub rank: Option<usize>,
+    pub experiment_id: String,
+    pub name: String,
+    pub status: String,
+    /// The run's value of the ranking metric
+    pub score: Option<f64>,
+    pub parameters: Option<Value>,
+    pub metrics: Option<Value>,
+    pub failure_reason: Option<String>,
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Leaderboard {
+    pub experiment_id: String,
+    pub metric: String,
+    pub goal: MetricGoal,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    /// Ranks `runs` by `metric`, best first. Runs without it, such as failed
+    /// ones, follow unranked in their original order.
+    pub fn rank(experiment_id: &str, metric: &str, runs: Vec<Experiment>) -> Self {
+        Self::rank_by(experiment_id, metric, MetricGoal::for_key(metric), runs)
+    }
+
+    /// Like [`Leaderboard::rank`], for a metric whose goal isn't the usual one.
+    pub fn rank_by(
+        experiment_id: &str,
+        metric: &str,
+        goal: MetricGoal,
+        runs: Vec<Experiment>,
+    ) -> Self {
+        let mut entries: Vec<LeaderboardEntry> =
+            runs.into_iter().map(|run| entry(run, metric)).collect();
+        entries.sort_by(|a, b| match (a.score, b.score) {
+            (Some(a), Some(b)) => match goal {
+                MetricGoal::Minimize => a.total_cmp(&b),
+                MetricGoal::Maximize => b.total_cmp(&a),
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        for (index, entry) in entries.iter_mut().enumerate() {
+            entry.rank = entry.score.map(|_| index + 1);
+        }
+
+        Self {
+            experiment_id: experiment_id.to_string(),
+            metric: metric.to_string(),
+            goal,
+            entries,
+        }
+    }
+}
+
+fn entry(run: Experiment, metric: &str) -> LeaderboardEntry {
+    let parse = |json: Option<String>| json.and_then(|json| serde_json::from_str(&json).ok());
+    let metrics: Option<Value> = parse(run.metrics);
+    let score = metrics
+        .as_ref()
+        .and_then(|metrics| metrics.get(metric))
+        .and_then(Value::as_f64)
+        .filter(|score| score.is_finite());
+
+    LeaderboardEntry {
+        rank: None,
+        experiment_id: run.id,
+        name: run.name,
+        status: run.status,
+        score,
+        parameters: parse(run.parameters),
+        metrics,
+        failure_reason: run.failure_reason,
+        duration_ms: run.duration_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn run(id: &str, metrics: Option<&str>) -> Experiment {
+        Experiment {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            notebook_id: None,
+            status: "completed".to_string(),
+            metrics: metrics.map(str::to_string),
+            parameters: None,
+            dataset_id: None,
+            model_path: None,
+            created_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+            dataset_version: None,
+            mlflow_experiment_id: None,
+            started_at: None,
+            finished_at: None,
+            duration_ms: None,
+            failure_reason: None,
+            parent_id: Some("search".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_leaderboard_ranking() {
+        let runs = || {
+            vec![
+                run("failed", None),
+                run("rf", Some(r#"{"accuracy": 0.91, "mse": 0.3}"#)),
+                run("ridge", Some(r#"{"accuracy": 0.84, "mse": 0.2}"#)),
+                run("xgb", Some(r#"{"accuracy": 0.93}"#)),
+            ]
+        };
+
+        let leaderboard = Leaderboard::rank("search", "accuracy", runs());
+        assert_eq!(leaderboard.goal, MetricGoal::Maximize);
+        let ranked: Vec<(&str, Option<usize>)> = leaderboard
+            .entries
+            .iter()
+            .map(|entry| (entry.experiment_id.as_str(), entry.rank))
+            .collect();
+        assert_eq!(
+            ranked,
+            [
+                ("xgb", Some(1)),
+                ("rf", Some(2)),
+                ("ridge", Some(3)),
+                ("failed", None)
+            ]
+        );
+        assert_eq!(leaderboard.entries[0].score, Some(0.93));
+
+        let leaderboard = Leaderboard::rank("search", "mse", runs());
+        assert_eq!(leaderboard.goal, MetricGoal::Minimize);
+        assert_eq!(leaderboard.entries[0].experiment_id, "ridge");
+        assert_eq!(leaderboard.entries[2].rank, None);
+
+        assert!(Leaderboard::rank("search", "r2", runs())
+            .entries
+            .iter()
+            .all(|entry| entry.rank.is_none()));
+    }
+}
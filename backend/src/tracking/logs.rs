@@ -0,0 +1,162 @@
+//! Reading experiment logs a page at a time. Log ids only grow, so a line's id
+//! is the cursor to continue from.
+
+use sqlx::{QueryBuilder, Sqlite};
+
+use crate::models::ExperimentLog;
+
+pub const DEFAULT_LOG_LIMIT: i64 = 100;
+pub const MAX_LOG_LIMIT: i64 = 1000;
+
+/// Where a page of logs starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogCursor {
+    /// The newest lines, newest first
+    Latest,
+    /// Lines older than the id, newest first
+    Before(i64),
+    /// Lines newer than the id, oldest first, for following a run
+    After(i64),
+}
+
+#[derive(Debug, Clone)]
+pub struct LogQuery {
+    pub cursor: LogCursor,
+    /// Levels to include; all when empty
+    pub levels: Vec<String>,
+    /// Lines per page, between 1 and `MAX_LOG_LIMIT`
+    limit: i64,
+}
+
+impl Default for LogQuery {
+    fn default() -> Self {
+        Self {
+            cursor: LogCursor::Latest,
+            levels: Vec::new(),
+            limit: DEFAULT_LOG_LIMIT,
+        }
+    }
+}
+
+impl LogQuery {
+    /// A query for pages of `limit` lines, clamped to what a page may hold.
+    pub fn new(cursor: LogCursor, levels: Vec<String>, limit: i64) -> Self {
+        Self {
+            cursor,
+            levels,
+            limit: limit.clamp(1, MAX_LOG_LIMIT),
+        }
+    }
+
+    pub fn after(id: i64, levels: &[String]) -> Self {
+        Self::new(LogCursor::After(id), levels.to_vec(), MAX_LOG_LIMIT)
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit
+    }
+
+    pub fn matches(&self, log: &ExperimentLog) -> bool {
+        self.levels.is_empty() || self.levels.contains(&log.level.to_lowercase())
+    }
+
+    pub fn build(&self, experiment_id: &str) -> QueryBuilder<'static, Sqlite> {
+        let mut builder = QueryBuilder::new("SELECT * FROM experiment_logs WHERE experiment_id = ");
+        builder.push_bind(experiment_id.to_string());
+
+        if !self.levels.is_empty() {
+            builder.push(" AND LOWER(level) IN (");
+            let mut separated = builder.separated(", ");
+            for level in &self.levels {
+                separated.push_bind(level.clone());
+            }
+            separated.push_unseparated(")");
+        }
+
+        match self.cursor {
+            LogCursor::Latest => builder.push(" ORDER BY id DESC"),
+            LogCursor::Before(id) => builder
+                .push(" AND id < ")
+                .push_bind(id)
+                .push(" ORDER BY id DESC"),
+            LogCursor::After(id) => builder
+                .push(" AND id > ")
+                .push_bind(id)
+                .push(" ORDER BY id ASC"),
+        };
+        builder.push(" LIMIT ").push_bind(self.limit);
+        builder
+    }
+
+    /// Cursor for the page after `logs`. Going forward there always is one, to
+    /// poll for new lines; going back there is none once the oldest line is read.
+    pub fn next_cursor(&self, logs: &[ExperimentLog]) -> Option<i64> {
+        let last = logs.last().map(|log| log.id);
+        match self.cursor {
+            LogCursor::After(id) => Some(last.unwrap_or(id)),
+            LogCursor::Latest | LogCursor::Before(_) => {
+                last.filter(|_| logs.len() as i64 >= self.limit)
+            }
+        }
+    }
+}
+
+/// Levels from a comma-separated list such as `warning,error`.
+pub fn parse_levels(levels: Option<&str>) -> Vec<String> {
+    levels
+        .unwrap_or_default()
+        .split(',')
+        .map(|level| level.trim().to_lowercase())
+        .filter(|level| !level.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn log(id: i64) -> ExperimentLog {
+        ExperimentLog {
+            id,
+            experiment_id: "e".to_string(),
+            timestamp: NaiveDateTime::default(),
+            level: "info".to_string(),
+            message: format!("line {}", id),
+        }
+    }
+
+    #[test]
+    fn test_log_query() {
+        let query = LogQuery::new(
+            LogCursor::After(10),
+            parse_levels(Some("Warning, error,")),
+            2,
+        );
+        assert_eq!(query.levels, vec!["warning", "error"]);
+        assert_eq!(
+            query.build("e").sql(),
+            "SELECT * FROM experiment_logs WHERE experiment_id = ? \
+             AND LOWER(level) IN (?, ?) AND id > ? ORDER BY id ASC LIMIT ?"
+        );
+        assert!(!query.matches(&log(11)));
+        assert_eq!(query.next_cursor(&[log(11), log(12)]), Some(12));
+        assert_eq!(query.next_cursor(&[]), Some(10));
+
+        let query = LogQuery::new(LogCursor::Before(12), Vec::new(), 2);
+        assert!(query
+            .build("e")
+            .sql()
+            .ends_with("AND id < ? ORDER BY id DESC LIMIT ?"));
+        assert!(query.matches(&log(11)));
+        assert_eq!(query.next_cursor(&[log(11), log(10)]), Some(10));
+        assert_eq!(query.next_cursor(&[log(11)]), None);
+
+        // Pages never hold more than the maximum, so a full one has a next page
+        let query = LogQuery::new(LogCursor::Latest, Vec::new(), 5000);
+        assert_eq!(query.limit(), MAX_LOG_LIMIT);
+        let page: Vec<_> = (1..=MAX_LOG_LIMIT).rev().map(log).collect();
+        assert_eq!(query.next_cursor(&page), Some(1));
+        assert_eq!(LogQuery::new(LogCursor::Latest, Vec::new(), 0).limit(), 1);
+    }
+}
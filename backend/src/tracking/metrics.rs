@@ -0,0 +1,171 @@
+//! Metric time series: logged points, per-key goals and downsampling for charts.
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Upper bound on `max_points` for a series query.
+pub const MAX_SERIES_POINTS: usize = 10_000;
+
+/// One value of a metric as logged by a training run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricPoint {
+    pub key: String,
+    pub value: f64,
+    /// Defaults to the step after the key's last one
+    pub step: Option<i64>,
+    /// Defaults to the time the point is received
+    pub timestamp: Option<NaiveDateTime>,
+}
+
+/// Whether lower or higher values of a metric are better, used for its best value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricGoal {
+    Minimize,
+    Maximize,
+}
+
+impl MetricGoal {
+    /// Losses and errors are minimized; scores like accuracy or r2 are maximized.
+    pub fn for_key(key: &str) -> Self {
+        const MINIMIZED: [&str; 9] = [
+            "loss",
+            "error",
+            "mse",
+            "rmse",
+            "mae",
+            "mape",
+            "logloss",
+            "perplexity",
+            "time",
+        ];
+        let key = key.to_lowercase();
+        let minimized = key
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|part| MINIMIZED.contains(&part) || part.ends_with("loss"));
+        if minimized {
+            MetricGoal::Minimize
+        } else {
+            MetricGoal::Maximize
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SeriesPoint {
+    pub step: i64,
+    pub value: f64,
+    pub timestamp: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricSeries {
+    pub key: String,
+    pub goal: MetricGoal,
+    pub points: Vec<SeriesPoint>,
+    /// Number of logged points before downsampling
+    pub total_points: usize,
+}
+
+impl MetricSeries {
+    /// Builds a series from points ordered by step, downsampled to at most `max_points`.
+    pub fn new(key: String, points: Vec<SeriesPoint>, max_points: Option<usize>) -> Self {
+        let total_points = points.len();
+        let points = match max_points {
+            Some(max_points) => downsample(points, max_points.min(MAX_SERIES_POINTS)),
+            None => points,
+        };
+        Self {
+            goal: MetricGoal::for_key(&key),
+            key,
+            points,
+            total_points,
+        }
+    }
+}
+
+/// Largest-Triangle-Three-Buckets downsampling: keeps the first and last points
+/// and, from each bucket in between, the point forming the largest triangle with
+/// its neighbours, so the curve keeps its visual shape (spikes included).
+pub fn downsample(points: Vec<SeriesPoint>, threshold: usize) -> Vec<SeriesPoint> {
+    if threshold == 0 || threshold >= points.len() {
+        return points;
+    }
+    if threshold < 3 {
+        // Too few points for any triangles; keep the endpoints
+        let mut endpoints = vec![points[0]];
+        if threshold == 2 {
+            endpoints.push(points[points.len() - 1]);
+        }
+        return endpoints;
+    }
+
+    let x = |p: &SeriesPoint| p.step as f64;
+    let bucket_size = (points.len() - 2) as f64 / (threshold - 2) as f64;
+    let mut sampled = Vec::with_capacity(threshold);
+    sampled.push(points[0]);
+    let mut previous = 0;
+
+    for bucket in 0..threshold - 2 {
+        let start = (bucket as f64 * bucket_size) as usize + 1;
+        let end = ((bucket + 1) as f64 * bucket_size) as usize + 1;
+
+        // Average of the next bucket is the third corner of the triangle
+        let next_start = end;
+        let next_end = (((bucket + 2) as f64 * bucket_size) as usize + 1).min(points.len());
+        let next = &points[next_start..next_end.max(next_start + 1)];
+        let avg_x = next.iter().map(x).sum::<f64>() / next.len() as f64;
+        let avg_y = next.iter().map(|p| p.value).sum::<f64>() / next.len() as f64;
+
+        let (ax, ay) = (x(&points[previous]), points[previous].value);
+        let mut best = start;
+        let mut best_area = -1.0;
+        for (i, point) in points.iter().enumerate().take(end).skip(start) {
+            let area = ((ax - avg_x) * (point.value - ay) - (ax - x(point)) * (avg_y - ay)).abs();
+            if area > best_area {
+                best_area = area;
+                best = i;
+            }
+        }
+        sampled.push(points[best]);
+        previous = best;
+    }
+
+    sampled.push(points[points.len() - 1]);
+    sampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downsample_keeps_shape() {
+        let timestamp = NaiveDateTime::default();
+        let points: Vec<SeriesPoint> = (0..1000)
+            .map(|step| SeriesPoint {
+                step,
+                // Smooth decay with one spike
+                value: if step == 500 {
+                    10.0
+                } else {
+                    1.0 / (step as f64 + 1.0)
+                },
+                timestamp,
+            })
+            .collect();
+
+        let sampled = downsample(points.clone(), 50);
+        assert_eq!(sampled.len(), 50);
+        assert_eq!(sampled.first().unwrap().step, 0);
+        assert_eq!(sampled.last().unwrap().step, 999);
+        assert!(sampled.iter().any(|p| p.step == 500));
+        assert!(sampled.windows(2).all(|w| w[0].step < w[1].step));
+        assert_eq!(downsample(points.clone(), 2).len(), 2);
+        assert_eq!(downsample(points, 5000).len(), 1000);
+
+        assert_eq!(MetricGoal::for_key("val_loss"), MetricGoal::Minimize);
+        assert_eq!(MetricGoal::for_key("train/rmse"), MetricGoal::Minimize);
+        assert_eq!(MetricGoal::for_key("accuracy"), MetricGoal::Maximize);
+    }
+}
@@ -0,0 +1,416 @@
+//! MLflow tracking protocol: entity shapes of the REST API and their mapping onto
+//! our experiments. An MLflow run is a row of `experiments`; MLflow experiments
+//! only group runs.
+
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+use super::metrics::MetricPoint;
+use super::search::{self, Attribute, Condition, Field, Literal, SearchError};
+use super::status::ExperimentStatus;
+use crate::models::{Experiment, ExperimentArtifact, MlflowExperiment, Tag};
+
+/// Tag the MLflow client sets to the run name.
+pub const RUN_NAME_TAG: &str = "mlflow.runName";
+pub const USER_TAG: &str = "mlflow.user";
+
+/// MLflow run status for an experiment status.
+pub fn run_status(status: &str) -> &'static str {
+    match status {
+        "pending" | "queued" => "SCHEDULED",
+        "running" => "RUNNING",
+        "completed" | "completed_with_budget_exhausted" => "FINISHED",
+        "failed" => "FAILED",
+        "cancelled" => "KILLED",
+        _ => "RUNNING",
+    }
+}
+
+/// Experiment status for an MLflow run status.
+pub fn experiment_status(run_status: &str) -> Option<ExperimentStatus> {
+    match run_status.to_uppercase().as_str() {
+        "SCHEDULED" => Some(ExperimentStatus::Pending),
+        "RUNNING" => Some(ExperimentStatus::Running),
+        "FINISHED" => Some(ExperimentStatus::Completed),
+        "FAILED" => Some(ExperimentStatus::Failed),
+        "KILLED" => Some(ExperimentStatus::Cancelled),
+        _ => None,
+    }
+}
+
+pub fn millis(time: NaiveDateTime) -> i64 {
+    time.and_utc().timestamp_millis()
+}
+
+pub fn from_millis(millis: i64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp_millis(millis).map(|time| time.naive_utc())
+}
+
+/// Deserializes an optional int64, which protobuf's JSON mapping may send as a string.
+pub fn int64<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Int64 {
+        Number(i64),
+        Text(String),
+    }
+
+    match Option::<Int64>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Int64::Number(n)) => Ok(Some(n)),
+        Some(Int64::Text(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Run a request is about. The stock client sends its id as both `run_id` and
+/// the deprecated `run_uuid`, so the two are read separately; `run_id` wins, as
+/// on MLflow's server.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RunRef {
+    run_id: Option<String>,
+    run_uuid: Option<String>,
+}
+
+impl RunRef {
+    pub fn id(&self) -> Option<&str> {
+        self.run_id
+            .as_deref()
+            .or(self.run_uuid.as_deref())
+            .filter(|id| !id.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyValue {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<Tag> for KeyValue {
+    fn from(tag: Tag) -> Self {
+        Self {
+            key: tag.key,
+            value: tag.value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metric {
+    pub key: String,
+    pub value: f64,
+    #[serde(default, deserialize_with = "int64")]
+    pub timestamp: Option<i64>,
+    #[serde(default, deserialize_with = "int64")]
+    pub step: Option<i64>,
+}
+
+impl From<MetricPoint> for Metric {
+    fn from(point: MetricPoint) -> Self {
+        Self {
+            key: point.key,
+            value: point.value,
+            timestamp: point.timestamp.map(millis),
+            step: point.step,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentInfo {
+    pub experiment_id: String,
+    pub name: String,
+    pub artifact_location: String,
+    pub lifecycle_stage: String,
+    pub creation_time: i64,
+    pub last_update_time: i64,
+    pub tags: Vec<KeyValue>,
+}
+
+impl ExperimentInfo {
+    pub fn new(experiment: MlflowExperiment, tags: Vec<Tag>) -> Self {
+        Self {
+            experiment_id: experiment.id.to_string(),
+            artifact_location: artifact_location(&experiment),
+            name: experiment.name,
+            lifecycle_stage: experiment.lifecycle_stage,
+            creation_time: millis(experiment.created_at),
+            last_update_time: millis(experiment.updated_at),
+            tags: tags.into_iter().map(KeyValue::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunInfo {
+    pub run_id: String,
+    /// Deprecated alias of `run_id` older clients still read
+    pub run_uuid: String,
+    pub run_name: String,
+    pub experiment_id: String,
+    pub user_id: String,
+    pub status: &'static str,
+    pub start_time: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<i64>,
+    pub artifact_uri: String,
+    pub lifecycle_stage: &'static str,
+}
+
+impl RunInfo {
+    pub fn new(experiment: &Experiment, tags: &[KeyValue], artifact_location: &str) -> Self {
+        let status = run_status(&experiment.status);
+        let user_id = tags
+            .iter()
+            .find(|tag| tag.key == USER_TAG)
+            .map(|tag| tag.value.clone())
+            .unwrap_or_default();
+        Self {
+            run_id: experiment.id.clone(),
+            run_uuid: experiment.id.clone(),
+            run_name: experiment.name.clone(),
+            experiment_id: experiment
+                .mlflow_experiment_id
+                .unwrap_or_default()
+                .to_string(),
+            user_id,
+            status,
+            start_time: millis(experiment.started_at.unwrap_or(experiment.created_at)),
+            end_time: experiment.finished_at.map(millis),
+            artifact_uri: run_artifact_uri(artifact_location, &experiment.id),
+            lifecycle_stage: "active",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunData {
+    pub metrics: Vec<Metric>,
+    pub params: Vec<KeyValue>,
+    pub tags: Vec<KeyValue>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Run {
+    pub info: RunInfo,
+    pub data: RunData,
+}
+
+impl Run {
+    /// `metrics` holds the latest point of each metric.
+    pub fn new(
+        experiment: &Experiment,
+        metrics: Vec<Metric>,
+        tags: Vec<Tag>,
+        artifact_location: &str,
+    ) -> Self {
+        let tags: Vec<KeyValue> = tags.into_iter().map(KeyValue::from).collect();
+        let params = experiment
+            .parameters
+            .as_deref()
+            .and_then(|p| serde_json::from_str(p).ok())
+            .map(|p| search::param_values(&p))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| KeyValue { key, value })
+            .collect();
+        Self {
+            info: RunInfo::new(experiment, &tags, artifact_location),
+            data: RunData {
+                metrics,
+                params,
+                tags,
+            },
+        }
+    }
+}
+
+/// Where the experiment's runs store artifacts; our artifact proxy by default.
+pub fn artifact_location(experiment: &MlflowExperiment) -> String {
+    experiment
+        .artifact_location
+        .clone()
+        .unwrap_or_else(|| format!("mlflow-artifacts:/{}", experiment.id))
+}
+
+pub fn run_artifact_uri(artifact_location: &str, run_id: &str) -> String {
+    format!(
+        "{}/{}/artifacts",
+        artifact_location.trim_end_matches('/'),
+        run_id
+    )
+}
+
+/// Splits a path under the artifact proxy, `<experiment>/<run>/artifacts[/<path>]`,
+/// into the run id and the path within the run.
+pub fn parse_proxy_path(path: &str) -> Option<(&str, &str)> {
+    let mut segments = path.trim_matches('/').splitn(4, '/');
+    let _experiment = segments.next()?;
+    let run_id = segments.next().filter(|id| !id.is_empty())?;
+    if segments.next()? != "artifacts" {
+        return None;
+    }
+    Some((run_id, segments.next().unwrap_or("")))
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FileInfo {
+    pub path: String,
+    pub is_dir: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<i64>,
+}
+
+/// Direct children of `dir` among a run's artifacts, by name; nested files show
+/// up as their top-level directory.
+pub fn list_directory(artifacts: &[ExperimentArtifact], dir: &str) -> Vec<FileInfo> {
+    let dir = dir.trim_matches('/');
+    let mut entries: BTreeMap<&str, FileInfo> = BTreeMap::new();
+    for artifact in artifacts {
+        let relative = if dir.is_empty() {
+            artifact.path.as_str()
+        } else {
+            match artifact
+                .path
+                .strip_prefix(dir)
+                .and_then(|rest| rest.strip_prefix('/'))
+            {
+                Some(relative) => relative,
+                None => continue,
+            }
+        };
+        let entry = match relative.split_once('/') {
+            Some((name, _)) => (
+                name,
+                FileInfo {
+                    path: name.to_string(),
+                    is_dir: true,
+                    file_size: None,
+                },
+            ),
+            None => (
+                relative,
+                FileInfo {
+                    path: relative.to_string(),
+                    is_dir: false,
+                    file_size: Some(artifact.size),
+                },
+            ),
+        };
+        entries.entry(entry.0).or_insert(entry.1);
+    }
+    entries.into_values().collect()
+}
+
+/// Parses an MLflow search filter. Statuses are MLflow run statuses and times
+/// milliseconds since the epoch, as MLflow clients send them.
+pub fn parse_run_filter(filter: &str) -> Result<Vec<Condition>, SearchError> {
+    search::parse_filter(filter)?
+        .into_iter()
+        .map(|condition| {
+            let value = match (&condition.field, condition.value) {
+                (Field::Attribute(Attribute::Status), Literal::Text(status)) => {
+                    let status = experiment_status(&status).map(ExperimentStatus::as_str);
+                    Literal::Text(status.unwrap_or_default().to_string())
+                }
+                (
+                    Field::Attribute(Attribute::CreatedAt | Attribute::UpdatedAt),
+                    Literal::Number(millis),
+                ) => {
+                    let time = from_millis(millis as i64)
+                        .ok_or_else(|| SearchError::ExpectedNumber(condition.field.to_string()))?;
+                    Literal::Text(time.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+                }
+                (_, value) => value,
+            };
+            Condition::new(condition.field, condition.comparator, value)
+        })
+        .collect()
+}
+
+/// 1-based page encoded in a page token; the first page without one.
+pub fn page_from_token(token: Option<&str>) -> Option<i64> {
+    match token.filter(|token| !token.is_empty()) {
+        None => Some(1),
+        Some(token) => token.parse().ok().filter(|page| *page >= 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracking::search::Comparator;
+
+    fn artifact(path: &str, size: i64) -> ExperimentArtifact {
+        ExperimentArtifact {
+            id: 0,
+            experiment_id: "run".to_string(),
+            path: path.to_string(),
+            sha256: String::new(),
+            size,
+            mime_type: "application/octet-stream".to_string(),
+            created_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_mlflow_mapping() {
+        assert_eq!(
+            parse_proxy_path("3/abc/artifacts/model/model.pkl"),
+            Some(("abc", "model/model.pkl"))
+        );
+        assert_eq!(parse_proxy_path("3/abc/artifacts"), Some(("abc", "")));
+        assert_eq!(parse_proxy_path("3/abc/outputs/x"), None);
+
+        let artifacts = vec![
+            artifact("model/MLmodel", 10),
+            artifact("model/model.pkl", 2048),
+            artifact("roc.png", 512),
+        ];
+        assert_eq!(
+            list_directory(&artifacts, ""),
+            vec![
+                FileInfo {
+                    path: "model".to_string(),
+                    is_dir: true,
+                    file_size: None
+                },
+                FileInfo {
+                    path: "roc.png".to_string(),
+                    is_dir: false,
+                    file_size: Some(512)
+                },
+            ]
+        );
+        assert_eq!(list_directory(&artifacts, "model").len(), 2);
+
+        let conditions =
+            parse_run_filter("attributes.status = 'FINISHED' and start_time >= 86400000").unwrap();
+        assert_eq!(conditions[0].value, Literal::Text("completed".to_string()));
+        assert_eq!(conditions[1].comparator, Comparator::Ge);
+        assert_eq!(
+            conditions[1].value,
+            Literal::Text("1970-01-02 00:00:00".to_string())
+        );
+
+        let metric: Metric = serde_json::from_str(
+            r#"{"key": "loss", "value": 0.5, "timestamp": "1700000000000", "step": 3}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            (metric.timestamp, metric.step),
+            (Some(1_700_000_000_000), Some(3))
+        );
+        let run: RunRef = serde_json::from_str(r#"{"run_id": "abc", "run_uuid": "abc"}"#).unwrap();
+        assert_eq!(run.id(), Some("abc"));
+        let run: RunRef = serde_json::from_str(r#"{"run_uuid": "abc"}"#).unwrap();
+        assert_eq!(run.id(), Some("abc"));
+        assert_eq!(RunRef::default().id(), None);
+        assert_eq!(page_from_token(Some("2")), Some(2));
+        assert_eq!(page_from_token(Some("bogus")), None);
+    }
+}
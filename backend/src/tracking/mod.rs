@@ -0,0 +1,17 @@
+pub mod artifacts;
+pub mod compare;
+pub mod events;
+pub mod leaderboard;
+pub mod logs;
+pub mod metrics;
+pub mod mlflow;
+pub mod reproducibility;
+pub mod search;
+pub mod status;
+
+pub use artifacts::ArtifactStore;
+pub use compare::Comparison;
+pub use leaderboard::Leaderboard;
+pub use metrics::{MetricGoal, MetricPoint, MetricSeries};
+pub use search::{ExperimentSearch, OrderBy, Page, SearchError};
+pub use status::ExperimentStatus;
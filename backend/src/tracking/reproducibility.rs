@@ -0,0 +1,505 @@
+//! Reproducibility: what an experiment ran with, captured when it starts, and
+//! rerunning it with the same setup.
+//!
+//! The tracking client reports its interpreter, packages, script and seeds; the
+//! backend adds the runtime environment, git commit, notebook snapshot and
+//! dataset version, and falls back to its own interpreter for the rest.
+
+use ring::digest;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use tokio::process::Command;
+
+use crate::error::{AppError, Result};
+use crate::jobs::{self, JobKind, NotebookJob, TrainingJob};
+use crate::models::{Cell, Database, Experiment, Reproducibility};
+use crate::python::PythonExecutor;
+use crate::runtime::{EnvironmentManager, RuntimeEnvironment};
+
+/// Tag of a rerun pointing at the experiment it reran.
+pub const RERUN_OF_TAG: &str = "openmind.rerunOf";
+
+/// Jobs of an experiment looked through for the one that ran it.
+const MAX_EXPERIMENT_JOBS: i64 = 100;
+
+/// Parameters taken as random seeds when the client doesn't report any.
+const SEED_PARAMETERS: [&str; 4] = ["seed", "random_seed", "random_state", "rng_seed"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotCell {
+    pub cell_type: String,
+    pub content: String,
+}
+
+/// What a rerun executes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Program {
+    /// The code cells of the notebook snapshot, run as one script
+    Code(String),
+    Script(String),
+}
+
+#[derive(Debug, Serialize)]
+pub struct Rerun {
+    pub experiment: Experiment,
+    /// Ways the rerun's setup differs from the original run's
+    pub warnings: Vec<String>,
+}
+
+/// Records the backend's view of the setup of an experiment that just started,
+/// without replacing what the client reported. The interpreter and packages are
+/// those of the experiment's runtime environment.
+pub async fn capture(db: &Database, experiment: &Experiment) -> Result<Reproducibility> {
+    let recorded = db
+        .get_reproducibility(&experiment.id)
+        .await?
+        .and_then(|record| record.environment_id);
+    let environment = environment(recorded.as_deref()).await;
+    let executor = executor(environment.as_ref());
+    let (git_commit, git_dirty) = git_state().await;
+    let (notebook_revision, notebook_snapshot) = match &experiment.notebook_id {
+        Some(notebook_id) => {
+            let (revision, snapshot) =
+                notebook_snapshot(&db.get_notebook_cells(notebook_id).await?);
+            (Some(revision), Some(snapshot))
+        }
+        None => (None, None),
+    };
+
+    let record = Reproducibility {
+        experiment_id: experiment.id.clone(),
+        environment_id: environment.map(|environment| environment.id),
+        python_version: executor.python_version().await,
+        packages: executor.pip_freeze().await,
+        git_commit,
+        git_dirty,
+        notebook_revision,
+        notebook_snapshot,
+        seeds: seeds_from_parameters(experiment.parameters.as_deref()),
+        dataset_version: experiment.dataset_version,
+        ..Default::default()
+    };
+    Ok(db.save_reproducibility(&record, false).await?)
+}
+
+/// Runs [`capture`] without making the caller wait for `pip freeze`.
+pub fn capture_in_background(db: &Database, experiment: &Experiment) {
+    let db = db.clone();
+    let experiment = experiment.clone();
+    tokio::spawn(async move {
+        if let Err(e) = capture(&db, &experiment).await {
+            tracing::error!(
+                "Failed to capture the setup of experiment {}: {}",
+                experiment.id,
+                e
+            );
+        }
+    });
+}
+
+/// The runtime environment with the given id, or the default one.
+async fn environment(id: Option<&str>) -> Option<RuntimeEnvironment> {
+    // Detection shells out to nvidia-smi
+    let manager = tokio::task::spawn_blocking(EnvironmentManager::new)
+        .await
+        .ok()?
+        .ok()?;
+    match id {
+        Some(id) => manager.get_environment(id),
+        None => manager.get_default_environment(),
+    }
+    .cloned()
+}
+
+fn executor(environment: Option<&RuntimeEnvironment>) -> PythonExecutor {
+    environment.map_or_else(PythonExecutor::new, PythonExecutor::for_environment)
+}
+
+/// Commit checked out in the project directory and whether it has uncommitted
+/// changes; `None` outside a git repository.
+pub async fn git_state() -> (Option<String>, Option<bool>) {
+    let Some(commit) = git(&["rev-parse", "HEAD"]).await else {
+        return (None, None);
+    };
+    let dirty = git(&["status", "--porcelain"])
+        .await
+        .map(|changes| !changes.is_empty());
+    (Some(commit), dirty)
+}
+
+async fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().await.ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// SHA-256 identifying the revision of a notebook, and its cells as JSON.
+pub fn notebook_snapshot(cells: &[Cell]) -> (String, String) {
+    let cells: Vec<SnapshotCell> = cells
+        .iter()
+        .map(|cell| SnapshotCell {
+            cell_type: cell.cell_type.clone(),
+            content: cell.content.clone(),
+        })
+        .collect();
+    let snapshot = serde_json::to_string(&cells).unwrap_or_default();
+    (sha256(snapshot.as_bytes()), snapshot)
+}
+
+fn sha256(data: &[u8]) -> String {
+    digest::digest(&digest::SHA256, data)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Seeds among the parameters, as a JSON object of parameter to seed.
+pub fn seeds_from_parameters(parameters: Option<&str>) -> Option<String> {
+    let parameters: serde_json::Map<String, Value> = serde_json::from_str(parameters?).ok()?;
+    let seeds: serde_json::Map<String, Value> = parameters
+        .into_iter()
+        .filter(|(key, value)| {
+            SEED_PARAMETERS.contains(&key.to_lowercase().as_str()) && value.is_i64()
+        })
+        .collect();
+    (!seeds.is_empty()).then(|| Value::Object(seeds).to_string())
+}
+
+/// What to execute for a rerun: the notebook snapshot if there is one, else the
+/// script if it still exists.
+pub fn program(record: &Reproducibility, warnings: &mut Vec<String>) -> Option<Program> {
+    if let Some(snapshot) = &record.notebook_snapshot {
+        let cells: Vec<SnapshotCell> = serde_json::from_str(snapshot).ok()?;
+        let code: Vec<&str> = cells
+            .iter()
+            .filter(|cell| cell.cell_type == "code")
+            .map(|cell| cell.content.as_str())
+            .collect();
+        return Some(Program::Code(code.join("\n\n")));
+    }
+
+    let path = record.script_path.as_deref()?;
+    let Ok(content) = std::fs::read(path) else {
+        warnings.push(format!("The script {} no longer exists", path));
+        return None;
+    };
+    if record
+        .script_sha256
+        .as_deref()
+        .is_some_and(|hash| hash != sha256(&content))
+    {
+        warnings.push(format!("The script {} changed since the run", path));
+    }
+    Some(Program::Script(path.to_string()))
+}
+
+/// The training job that last ran the experiment, if it was run as one, with
+/// the arguments and dataset it was given.
+async fn training_job(db: &Database, experiment_id: &str) -> Result<Option<TrainingJob>> {
+    let jobs = db
+        .list_jobs(None, Some(experiment_id), MAX_EXPERIMENT_JOBS)
+        .await?;
+    Ok(jobs
+        .into_iter()
+        .find(|job| job.kind == JobKind::Training.as_str())
+        .and_then(|job| serde_json::from_str(&job.payload).ok()))
+}
+
+/// Lines of `pip freeze` output in one but not the other.
+pub fn package_differences(recorded: &str, current: &str) -> Vec<String> {
+    let recorded: BTreeSet<&str> = recorded.lines().map(str::trim).collect();
+    let current: BTreeSet<&str> = current.lines().map(str::trim).collect();
+    recorded
+        .symmetric_difference(&current)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Ways the current setup differs from the recorded one.
+async fn differences(
+    db: &Database,
+    experiment: &Experiment,
+    record: &Reproducibility,
+) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+
+    if let Some(dataset_id) = &experiment.dataset_id {
+        let recorded = record.dataset_version.or(experiment.dataset_version);
+        match db.get_dataset(dataset_id).await? {
+            None => warnings.push(format!("Dataset {} no longer exists", dataset_id)),
+            Some(dataset) if recorded.is_some_and(|version| version != dataset.version) => warnings
+                .push(format!(
+                    "Dataset {} is at version {}; the run used version {}",
+                    dataset_id,
+                    dataset.version,
+                    recorded.unwrap_or_default()
+                )),
+            Some(_) => {}
+        }
+    }
+
+    if let Some(recorded) = &record.git_commit {
+        match git_state().await.0 {
+            Some(commit) if &commit != recorded => warnings.push(format!(
+                "The project is at commit {}; the run used {}",
+                commit, recorded
+            )),
+            _ => {}
+        }
+        if record.git_dirty == Some(true) {
+            warnings.push("The run had uncommitted changes, which aren't restored".to_string());
+        }
+    }
+
+    // Compared in the environment the run used
+    let environment = environment(record.environment_id.as_deref()).await;
+    let executor = executor(environment.as_ref());
+    if let Some(recorded) = &record.python_version {
+        match executor.python_version().await {
+            Some(version) if &version != recorded => warnings.push(format!(
+                "Python is at version {}; the run used {}",
+                version, recorded
+            )),
+            _ => {}
+        }
+    }
+    if let Some(recorded) = &record.packages {
+        if let Some(current) = executor.pip_freeze().await {
+            let differences = package_differences(recorded, &current);
+            if let Some(first) = differences.first() {
+                warnings.push(format!(
+                    "{} installed packages differ from the run's, e.g. {}",
+                    differences.len(),
+                    first
+                ));
+            }
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// Creates a new experiment with the parameters of `id` and, when its code was
+/// captured, queues a job running that code with the same seeds. A script the
+/// original ran as a job gets that job's arguments and dataset again.
+pub async fn rerun(db: &Database, id: &str) -> Result<Rerun> {
+    let original = db
+        .get_experiment(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Experiment {} not found", id)))?;
+    let record = db.get_reproducibility(id).await?;
+
+    let mut warnings = Vec::new();
+    let program = match &record {
+        Some(record) => {
+            warnings.extend(differences(db, &original, record).await?);
+            program(record, &mut warnings)
+        }
+        None => {
+            warnings.push(format!("No setup was captured for experiment {}", id));
+            None
+        }
+    };
+    let original_job = training_job(db, id).await?;
+    let program = match program {
+        None => original_job
+            .as_ref()
+            .map(|job| Program::Script(job.script_path.clone())),
+        program => program,
+    };
+    // Only scripts in the workspace are run, whatever the client recorded
+    let program = match program {
+        Some(Program::Script(path)) => match jobs::resolve_script(&jobs::workspace(), &path) {
+            Ok(path) => Some(Program::Script(path)),
+            Err(e) => {
+                warnings.push(e.to_string());
+                None
+            }
+        },
+        program => program,
+    };
+
+    let parameters = original
+        .parameters
+        .as_deref()
+        .and_then(|parameters| serde_json::from_str(parameters).ok());
+    let experiment = db
+        .create_experiment(
+            &original.name,
+            original.notebook_id.as_deref(),
+            original.dataset_id.as_deref(),
+            parameters,
+        )
+        .await?;
+    db.set_experiment_tags(
+        &experiment.id,
+        &[(RERUN_OF_TAG.to_string(), id.to_string())],
+    )
+    .await?;
+
+    let seeds = record.and_then(|record| record.seeds);
+    let (_, queued) = match program {
+        Some(Program::Code(code)) => {
+            let job = NotebookJob {
+                notebook_id: experiment.notebook_id.clone(),
+                code,
+                seeds,
+            };
+            jobs::enqueue(db, JobKind::Notebook, &job, Some(&experiment.id)).await?
+        }
+        Some(Program::Script(script_path)) => {
+            let job = match original_job {
+                Some(job) => TrainingJob {
+                    script_path,
+                    seeds: seeds.or(job.seeds),
+                    ..job
+                },
+                None => {
+                    warnings.push(
+                        "The run wasn't queued as a job, so the script runs without its arguments"
+                            .to_string(),
+                    );
+                    TrainingJob {
+                        script_path,
+                        dataset_id: None,
+                        args: Vec::new(),
+                        seeds,
+                    }
+                }
+            };
+            jobs::enqueue(db, JobKind::Training, &job, Some(&experiment.id)).await?
+        }
+        None => {
+            warnings.push(
+                "No code was captured to run again; the rerun is pending with the same parameters"
+                    .to_string(),
+            );
+            return Ok(Rerun {
+                experiment,
+                warnings,
+            });
+        }
+    };
+
+    Ok(Rerun {
+        experiment: queued.unwrap_or(experiment),
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn cell(cell_type: &str, content: &str) -> Cell {
+        Cell {
+            id: content.to_string(),
+            notebook_id: "nb".to_string(),
+            cell_type: cell_type.to_string(),
+            content: content.to_string(),
+            output: None,
+            created_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_rerun_setup() {
+        let cells = [
+            cell("markdown", "# Baseline"),
+            cell("code", "import random"),
+            cell("code", "print(random.random())"),
+        ];
+        let (revision, snapshot) = notebook_snapshot(&cells);
+        assert_eq!(revision.len(), 64);
+        assert_eq!(notebook_snapshot(&cells).0, revision);
+        assert_ne!(notebook_snapshot(&cells[1..]).0, revision);
+
+        let record = Reproducibility {
+            notebook_snapshot: Some(snapshot),
+            ..Default::default()
+        };
+        let mut warnings = Vec::new();
+        assert_eq!(
+            program(&record, &mut warnings),
+            Some(Program::Code(
+                "import random\n\nprint(random.random())".to_string()
+            ))
+        );
+
+        let record = Reproducibility {
+            script_path: Some("/nonexistent/train.py".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(program(&record, &mut warnings), None);
+        assert_eq!(
+            warnings,
+            ["The script /nonexistent/train.py no longer exists"]
+        );
+
+        assert_eq!(
+            seeds_from_parameters(Some(r#"{"random_state": 42, "model": "rf", "seed": "x"}"#)),
+            Some(r#"{"random_state":42}"#.to_string())
+        );
+        assert_eq!(seeds_from_parameters(Some(r#"{"model": "rf"}"#)), None);
+
+        assert_eq!(
+            package_differences(
+                "numpy==1.26.0\npandas==2.1.0\n",
+                "numpy==1.26.4\npandas==2.1.0"
+            ),
+            ["numpy==1.26.0", "numpy==1.26.4"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rerun_repeats_the_job() {
+        let db = Database::new().await.unwrap();
+        let workspace = jobs::workspace();
+        std::fs::create_dir_all(&workspace).unwrap();
+        let script = tempfile::Builder::new()
+            .suffix(".py")
+            .tempfile_in(&workspace)
+            .unwrap();
+        let script_path = jobs::resolve_script(
+            &workspace,
+            &script.path().file_name().unwrap().to_string_lossy(),
+        )
+        .unwrap();
+
+        let dataset = db
+            .create_dataset("train.csv", "data/datasets/train.csv", 0)
+            .await
+            .unwrap();
+        let original = db
+            .create_experiment("trial", None, Some(&dataset.id), None)
+            .await
+            .unwrap();
+        let job = TrainingJob {
+            script_path: script_path.clone(),
+            dataset_id: Some(dataset.id.clone()),
+            args: vec!["--lr".to_string(), "0.1".to_string()],
+            seeds: Some(r#"{"random": 7}"#.to_string()),
+        };
+        jobs::enqueue(&db, JobKind::Training, &job, Some(&original.id))
+            .await
+            .unwrap();
+
+        let rerun = rerun(&db, &original.id).await.unwrap();
+        let rerun_job = training_job(&db, &rerun.experiment.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rerun_job.script_path, script_path);
+        assert_eq!(rerun_job.dataset_id, Some(dataset.id));
+        assert_eq!(rerun_job.args, job.args);
+        assert_eq!(rerun_job.seeds, job.seeds);
+        assert_eq!(rerun.experiment.status, "queued");
+    }
+}
@@ -0,0 +1,731 @@
+//! Experiment search: an MLflow-style filter language compiled to SQL.
+//!
+//! Filters are conditions joined by `AND`, e.g.
+//! `metrics.accuracy > 0.9 AND params.model = 'random_forest' AND status = 'completed'`.
+//! Keys with spaces or dots can be backquoted: ``metrics.`f1 score` >= 0.5``.
+//! `metrics.<key>` is the latest logged value; `best_metrics.<key>` the best one;
+//! `tags.<key>` a run tag. `LIKE` patterns escape `%`, `_` and `\` with a
+//! backslash.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{QueryBuilder, Sqlite};
+use thiserror::Error;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SearchError {
+    #[error("Invalid filter at position {position}: {message}")]
+    Syntax { position: usize, message: String },
+    #[error("Unknown attribute '{0}'")]
+    UnknownAttribute(String),
+    #[error("'{comparator}' can't be used with {field}")]
+    UnsupportedComparator { field: String, comparator: String },
+    #[error("{0} must be compared with a number")]
+    ExpectedNumber(String),
+    #[error("Invalid timestamp '{0}'; expected e.g. 2024-08-01 or 2024-08-01T10:00:00")]
+    InvalidTimestamp(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    Name,
+    Status,
+    NotebookId,
+    DatasetId,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl Attribute {
+    fn parse(name: &str) -> Result<Self, SearchError> {
+        match name.to_lowercase().as_str() {
+            "name" | "run_name" => Ok(Attribute::Name),
+            "status" => Ok(Attribute::Status),
+            "notebook_id" => Ok(Attribute::NotebookId),
+            "dataset_id" => Ok(Attribute::DatasetId),
+            "created_at" | "start_time" => Ok(Attribute::CreatedAt),
+            "updated_at" => Ok(Attribute::UpdatedAt),
+            _ => Err(SearchError::UnknownAttribute(name.to_string())),
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            Attribute::Name => "e.name",
+            Attribute::Status => "e.status",
+            Attribute::NotebookId => "e.notebook_id",
+            Attribute::DatasetId => "e.dataset_id",
+            Attribute::CreatedAt => "e.created_at",
+            Attribute::UpdatedAt => "e.updated_at",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Attribute(Attribute),
+    Param(String),
+    /// Latest value of the metric
+    Metric(String),
+    /// Best value of the metric, by its `MetricGoal`
+    BestMetric(String),
+    Tag(String),
+}
+
+impl Field {
+    fn summary_column(&self) -> Option<(&str, &'static str)> {
+        match self {
+            Field::Metric(key) => Some((key, "m.value")),
+            Field::BestMetric(key) => Some((key, "m.best_value")),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Field::Attribute(attribute) => write!(f, "{}", &attribute.column()[2..]),
+            Field::Param(key) => write!(f, "params.{}", key),
+            Field::Metric(key) => write!(f, "metrics.{}", key),
+            Field::BestMetric(key) => write!(f, "best_metrics.{}", key),
+            Field::Tag(key) => write!(f, "tags.{}", key),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Like,
+    ILike,
+}
+
+impl Comparator {
+    fn sql(self) -> &'static str {
+        match self {
+            Comparator::Eq => "=",
+            Comparator::Ne => "!=",
+            Comparator::Lt => "<",
+            Comparator::Le => "<=",
+            Comparator::Gt => ">",
+            Comparator::Ge => ">=",
+            Comparator::Like | Comparator::ILike => "LIKE",
+        }
+    }
+
+    fn is_pattern(self) -> bool {
+        matches!(self, Comparator::Like | Comparator::ILike)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: Field,
+    pub comparator: Comparator,
+    pub value: Literal,
+}
+
+impl Condition {
+    pub fn new(field: Field, comparator: Comparator, value: Literal) -> Result<Self, SearchError> {
+        let unsupported = || SearchError::UnsupportedComparator {
+            field: field.to_string(),
+            comparator: comparator.sql().to_string(),
+        };
+        let is_metric = field.summary_column().is_some();
+        match &value {
+            _ if is_metric && comparator.is_pattern() => return Err(unsupported()),
+            Literal::Text(_) if is_metric => {
+                return Err(SearchError::ExpectedNumber(field.to_string()))
+            }
+            Literal::Number(_) if comparator.is_pattern() => return Err(unsupported()),
+            _ => {}
+        }
+        Ok(Self {
+            field,
+            comparator,
+            value,
+        })
+    }
+
+    /// Appends the condition as a boolean SQL expression over `experiments e`.
+    /// Attribute conditions only need the table aliased `e` to have the column.
+    pub fn push_sql(&self, builder: &mut QueryBuilder<'_, Sqlite>) {
+        match &self.field {
+            Field::Attribute(attribute) => {
+                self.push_comparison(builder, attribute.column(), false);
+            }
+            Field::Param(key) => {
+                builder.push(
+                    "EXISTS (SELECT 1 FROM experiment_params p WHERE p.experiment_id = e.id AND p.key = ",
+                );
+                builder.push_bind(key.clone());
+                builder.push(" AND ");
+                // Params are stored as text; numeric literals compare numerically
+                self.push_comparison(builder, "p.value", true);
+                builder.push(")");
+            }
+            Field::Tag(key) => {
+                builder.push(
+                    "EXISTS (SELECT 1 FROM experiment_tags t WHERE t.experiment_id = e.id AND t.key = ",
+                );
+                builder.push_bind(key.clone());
+                builder.push(" AND ");
+                self.push_comparison(builder, "t.value", true);
+                builder.push(")");
+            }
+            Field::Metric(_) | Field::BestMetric(_) => {
+                let (key, column) = self.field.summary_column().expect("metric field");
+                builder.push(
+                    "EXISTS (SELECT 1 FROM experiment_metric_summaries m WHERE m.experiment_id = e.id AND m.key = ",
+                );
+                builder.push_bind(key.to_string());
+                builder.push(" AND ");
+                self.push_comparison(builder, column, false);
+                builder.push(")");
+            }
+        }
+    }
+
+    fn push_comparison(
+        &self,
+        builder: &mut QueryBuilder<'_, Sqlite>,
+        column: &str,
+        cast_numbers: bool,
+    ) {
+        let column = match (&self.value, cast_numbers) {
+            (Literal::Number(_), true) => format!("CAST({} AS REAL)", column),
+            _ => column.to_string(),
+        };
+        let column = match self.comparator {
+            Comparator::ILike => format!("LOWER({})", column),
+            _ => column,
+        };
+        builder.push(format!("{} {} ", column, self.comparator.sql()));
+        match &self.value {
+            Literal::Number(n) => {
+                builder.push_bind(*n);
+            }
+            Literal::Text(s) if self.comparator == Comparator::ILike => {
+                builder.push_bind(s.to_lowercase());
+            }
+            Literal::Text(s) => {
+                builder.push_bind(s.clone());
+            }
+        }
+        if self.comparator.is_pattern() {
+            builder.push(" ESCAPE '\\'");
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBy {
+    pub field: Field,
+    pub descending: bool,
+}
+
+impl OrderBy {
+    /// Parses `<field> [ASC|DESC]`, e.g. `metrics.accuracy DESC`.
+    pub fn parse(input: &str) -> Result<Self, SearchError> {
+        let mut lexer = Lexer::new(input);
+        let field = lexer.field()?;
+        let descending = match lexer.next_token()? {
+            None => false,
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("asc") => false,
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("desc") => true,
+            Some(_) => return Err(lexer.error("expected ASC or DESC")),
+        };
+        if lexer.next_token()?.is_some() {
+            return Err(lexer.error("unexpected input after sort direction"));
+        }
+        Ok(Self { field, descending })
+    }
+
+    fn push_sql(&self, builder: &mut QueryBuilder<'_, Sqlite>) {
+        match &self.field {
+            Field::Attribute(attribute) => {
+                builder.push(attribute.column());
+            }
+            Field::Param(key) => {
+                builder.push(
+                    "(SELECT p.value FROM experiment_params p WHERE p.experiment_id = e.id AND p.key = ",
+                );
+                builder.push_bind(key.clone());
+                builder.push(")");
+            }
+            Field::Tag(key) => {
+                builder.push(
+                    "(SELECT t.value FROM experiment_tags t WHERE t.experiment_id = e.id AND t.key = ",
+                );
+                builder.push_bind(key.clone());
+                builder.push(")");
+            }
+            Field::Metric(_) | Field::BestMetric(_) => {
+                let (key, column) = self.field.summary_column().expect("metric field");
+                builder.push(format!(
+                    "(SELECT {} FROM experiment_metric_summaries m WHERE m.experiment_id = e.id AND m.key = ",
+                    column
+                ));
+                builder.push_bind(key.to_string());
+                builder.push(")");
+            }
+        }
+        // Runs missing the key always sort last
+        builder.push(if self.descending {
+            " DESC NULLS LAST"
+        } else {
+            " ASC NULLS LAST"
+        });
+    }
+}
+
+/// A page of experiments matching all conditions.
+#[derive(Debug, Clone, Default)]
+pub struct ExperimentSearch {
+    pub conditions: Vec<Condition>,
+    pub order_by: Option<OrderBy>,
+    /// Restricts the search to runs of these MLflow experiments, if not empty
+    pub mlflow_experiment_ids: Vec<i64>,
+    /// 1-based
+    pub page: i64,
+    pub page_size: i64,
+}
+
+impl ExperimentSearch {
+    pub fn new(page: Option<i64>, page_size: Option<i64>) -> Self {
+        Self {
+            conditions: Vec::new(),
+            order_by: None,
+            mlflow_experiment_ids: Vec::new(),
+            page: page.unwrap_or(1).max(1),
+            page_size: page_size
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .clamp(1, MAX_PAGE_SIZE),
+        }
+    }
+
+    /// Adds the conditions of a filter expression.
+    pub fn filter(mut self, filter: &str) -> Result<Self, SearchError> {
+        self.conditions.extend(parse_filter(filter)?);
+        Ok(self)
+    }
+
+    pub fn condition(
+        mut self,
+        field: Field,
+        comparator: Comparator,
+        value: Literal,
+    ) -> Result<Self, SearchError> {
+        self.conditions
+            .push(Condition::new(field, comparator, value)?);
+        Ok(self)
+    }
+
+    /// Appends ` WHERE ...` for the conditions, if any.
+    pub fn push_where(&self, builder: &mut QueryBuilder<'_, Sqlite>) {
+        let mut separator = " WHERE ";
+        if !self.mlflow_experiment_ids.is_empty() {
+            builder.push(" WHERE e.mlflow_experiment_id IN (");
+            let mut ids = builder.separated(", ");
+            for id in &self.mlflow_experiment_ids {
+                ids.push_bind(*id);
+            }
+            ids.push_unseparated(")");
+            separator = " AND ";
+        }
+        for condition in &self.conditions {
+            builder.push(separator);
+            condition.push_sql(builder);
+            separator = " AND ";
+        }
+    }
+
+    /// Appends the ordering, newest first by default, then `LIMIT`/`OFFSET`.
+    pub fn push_order_and_page(&self, builder: &mut QueryBuilder<'_, Sqlite>) {
+        builder.push(" ORDER BY ");
+        if let Some(order_by) = &self.order_by {
+            order_by.push_sql(builder);
+            builder.push(", ");
+        }
+        builder.push("e.created_at DESC, e.id");
+        builder.push(" LIMIT ");
+        builder.push_bind(self.page_size);
+        builder.push(" OFFSET ");
+        builder.push_bind((self.page - 1) * self.page_size);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+/// `experiment_params` rows for a parameters object: strings as-is, other
+/// values serialized, nulls skipped.
+pub fn param_values(parameters: &Value) -> Vec<(String, String)> {
+    let Some(parameters) = parameters.as_object() else {
+        return Vec::new();
+    };
+    parameters
+        .iter()
+        .filter_map(|(key, value)| match value {
+            Value::Null => None,
+            Value::String(s) => Some((key.clone(), s.clone())),
+            other => Some((key.clone(), other.to_string())),
+        })
+        .collect()
+}
+
+/// `experiment_metric_summaries` rows for a metrics object; only numeric values are searchable.
+pub fn metric_values(metrics: &Value) -> Vec<(String, f64)> {
+    let Some(metrics) = metrics.as_object() else {
+        return Vec::new();
+    };
+    metrics
+        .iter()
+        .filter_map(|(key, value)| value.as_f64().map(|v| (key.clone(), v)))
+        .collect()
+}
+
+/// Escapes `text` to match itself, and nothing else, in a `LIKE` pattern.
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Normalizes a date or timestamp, e.g. ISO 8601, to the
+/// `YYYY-MM-DD HH:MM:SS` text experiments store, so comparing them as text
+/// orders them by time. Timestamps with an offset are converted to UTC.
+pub fn parse_timestamp(input: &str) -> Result<String, SearchError> {
+    let input = input.trim();
+    let timestamp = DateTime::parse_from_rfc3339(input)
+        .map(|timestamp| timestamp.naive_utc())
+        .ok()
+        .or_else(|| {
+            [
+                "%Y-%m-%dT%H:%M:%S%.f",
+                "%Y-%m-%d %H:%M:%S%.f",
+                "%Y-%m-%dT%H:%M",
+                "%Y-%m-%d %H:%M",
+            ]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| SearchError::InvalidTimestamp(input.to_string()))?;
+    Ok(timestamp.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+}
+
+pub fn parse_filter(input: &str) -> Result<Vec<Condition>, SearchError> {
+    let mut lexer = Lexer::new(input);
+    let mut conditions = Vec::new();
+    if lexer.peek_is_end()? {
+        return Ok(conditions);
+    }
+
+    loop {
+        let field = lexer.field()?;
+        let comparator = lexer.comparator()?;
+        let value = lexer.literal()?;
+        conditions.push(Condition::new(field, comparator, value)?);
+
+        match lexer.next_token()? {
+            None => break,
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("and") => continue,
+            Some(_) => return Err(lexer.error("expected AND")),
+        }
+    }
+    Ok(conditions)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Identifier or keyword, possibly dotted; backquoted parts keep their text as-is
+    Word(String),
+    Operator(String),
+    Text(String),
+    Number(f64),
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, position: 0 }
+    }
+
+    fn error(&self, message: &str) -> SearchError {
+        SearchError::Syntax {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.position = self.input.len() - trimmed.len();
+    }
+
+    fn peek_is_end(&mut self) -> Result<bool, SearchError> {
+        self.skip_whitespace();
+        Ok(self.rest().is_empty())
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token>, SearchError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let Some(first) = rest.chars().next() else {
+            return Ok(None);
+        };
+
+        if first == '\'' || first == '"' {
+            let end = rest[1..]
+                .find(first)
+                .ok_or_else(|| self.error("unterminated string"))?;
+            let text = rest[1..1 + end].to_string();
+            self.position += end + 2;
+            return Ok(Some(Token::Text(text)));
+        }
+
+        if "=!<>".contains(first) {
+            let len = if rest[1..].starts_with('=') { 2 } else { 1 };
+            let operator = rest[..len].to_string();
+            self.position += len;
+            return Ok(Some(Token::Operator(operator)));
+        }
+
+        if first.is_ascii_digit() || first == '-' || first == '.' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || "-+.".contains(c)))
+                .unwrap_or(rest.len());
+            let number = rest[..len]
+                .parse()
+                .map_err(|_| self.error("invalid number"))?;
+            self.position += len;
+            return Ok(Some(Token::Number(number)));
+        }
+
+        if first.is_alphabetic() || first == '_' || first == '`' {
+            let mut word = String::new();
+            let mut chars = rest.char_indices().peekable();
+            let mut len = rest.len();
+            while let Some((i, c)) = chars.next() {
+                if c == '`' {
+                    let close = rest[i + 1..]
+                        .find('`')
+                        .ok_or_else(|| self.error("unterminated backquote"))?;
+                    word.push_str(&rest[i + 1..i + 1 + close]);
+                    // Skip past the closing backquote
+                    while chars.peek().is_some_and(|(j, _)| *j <= i + 1 + close) {
+                        chars.next();
+                    }
+                } else if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                    word.push(c);
+                } else {
+                    len = i;
+                    break;
+                }
+            }
+            self.position += len;
+            return Ok(Some(Token::Word(word)));
+        }
+
+        Err(self.error(&format!("unexpected character '{}'", first)))
+    }
+
+    fn field(&mut self) -> Result<Field, SearchError> {
+        let Some(Token::Word(word)) = self.next_token()? else {
+            return Err(self.error("expected a field such as metrics.<key>"));
+        };
+        let (prefix, key) = match word.split_once('.') {
+            Some((prefix, key)) => (prefix.to_lowercase(), key.to_string()),
+            None => ("attributes".to_string(), word),
+        };
+        if key.is_empty() {
+            return Err(self.error("missing key"));
+        }
+        match prefix.as_str() {
+            "metrics" | "metric" => Ok(Field::Metric(key)),
+            "best_metrics" | "best_metric" => Ok(Field::BestMetric(key)),
+            "params" | "param" | "parameters" => Ok(Field::Param(key)),
+            "tags" | "tag" => Ok(Field::Tag(key)),
+            "attributes" | "attribute" | "attr" | "run" => {
+                Ok(Field::Attribute(Attribute::parse(&key)?))
+            }
+            _ => Err(self.error(&format!("unknown field prefix '{}'", prefix))),
+        }
+    }
+
+    fn comparator(&mut self) -> Result<Comparator, SearchError> {
+        match self.next_token()? {
+            Some(Token::Operator(op)) => match op.as_str() {
+                "=" | "==" => Ok(Comparator::Eq),
+                "!=" | "<>" => Ok(Comparator::Ne),
+                "<" => Ok(Comparator::Lt),
+                "<=" => Ok(Comparator::Le),
+                ">" => Ok(Comparator::Gt),
+                ">=" => Ok(Comparator::Ge),
+                _ => Err(self.error(&format!("unknown comparator '{}'", op))),
+            },
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("like") => Ok(Comparator::Like),
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("ilike") => Ok(Comparator::ILike),
+            _ => Err(self.error("expected a comparator")),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Literal, SearchError> {
+        match self.next_token()? {
+            Some(Token::Number(n)) => Ok(Literal::Number(n)),
+            Some(Token::Text(s)) => Ok(Literal::Text(s)),
+            _ => Err(self.error("expected a number or quoted string")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter_and_compile() {
+        let conditions = parse_filter(
+            "metrics.accuracy > 0.9 and params.`max depth` = '8' AND status != \"failed\" and name ILIKE '%baseline%'",
+        )
+        .unwrap();
+        assert_eq!(
+            conditions[0],
+            Condition {
+                field: Field::Metric("accuracy".to_string()),
+                comparator: Comparator::Gt,
+                value: Literal::Number(0.9),
+            }
+        );
+        assert_eq!(conditions[1].field, Field::Param("max depth".to_string()));
+        assert_eq!(conditions[2].field, Field::Attribute(Attribute::Status));
+        assert_eq!(conditions[3].comparator, Comparator::ILike);
+        assert!(parse_filter("  ").unwrap().is_empty());
+        assert_eq!(
+            parse_filter("best_metrics.val_loss < 0.2").unwrap()[0].field,
+            Field::BestMetric("val_loss".to_string())
+        );
+
+        let mut search = ExperimentSearch::new(Some(2), Some(10));
+        search.conditions = conditions;
+        search.order_by = Some(OrderBy::parse("metrics.accuracy DESC").unwrap());
+        let mut builder = QueryBuilder::new("SELECT e.* FROM experiments e");
+        search.push_where(&mut builder);
+        search.push_order_and_page(&mut builder);
+        let sql = builder.sql();
+        assert!(sql.contains("m.key = ? AND m.value > ?"));
+        assert!(sql.contains("AND e.status != ?"));
+        assert!(sql.contains("LOWER(e.name) LIKE ? ESCAPE '\\'"));
+        assert!(sql.ends_with("DESC NULLS LAST, e.created_at DESC, e.id LIMIT ? OFFSET ?"));
+
+        assert!(matches!(
+            parse_filter("metrics.loss = 'low'"),
+            Err(SearchError::ExpectedNumber(_))
+        ));
+        assert!(matches!(
+            parse_filter("owner = 'me'"),
+            Err(SearchError::UnknownAttribute(_))
+        ));
+        assert!(matches!(
+            parse_filter("metrics.loss < 0.1 or metrics.loss > 2"),
+            Err(SearchError::Syntax { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        for input in [
+            "2024-08-01T10:00:00",
+            "2024-08-01 10:00:00",
+            "2024-08-01T12:00:00+02:00",
+            "2024-08-01T10:00:00Z",
+        ] {
+            assert_eq!(
+                parse_timestamp(input).unwrap(),
+                "2024-08-01 10:00:00",
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            parse_timestamp("2024-08-01").unwrap(),
+            "2024-08-01 00:00:00"
+        );
+        assert_eq!(
+            parse_timestamp("2024-08-01T10:00:00.250").unwrap(),
+            "2024-08-01 10:00:00.250"
+        );
+        assert!(matches!(
+            parse_timestamp("last tuesday"),
+            Err(SearchError::InvalidTimestamp(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_name_pattern_matches_literally() {
+        let db = crate::models::Database::new().await.unwrap();
+        let prefix = uuid::Uuid::new_v4().simple().to_string();
+        for name in ["a_b", "axb", "50%", "500"] {
+            db.create_experiment(&format!("{}-{}", prefix, name), None, None, None)
+                .await
+                .unwrap();
+        }
+
+        for (substring, expected) in [("a_b", "a_b"), ("50%", "50%")] {
+            let pattern = format!("%{}%", escape_like(&format!("{}-{}", prefix, substring)));
+            let search = ExperimentSearch::new(None, None)
+                .condition(
+                    Field::Attribute(Attribute::Name),
+                    Comparator::ILike,
+                    Literal::Text(pattern),
+                )
+                .unwrap();
+            let (experiments, _) = db.search_experiments(&search).await.unwrap();
+            let names: Vec<&str> = experiments
+                .iter()
+                .map(|e| &e.name[prefix.len() + 1..])
+                .collect();
+            assert_eq!(names, [expected]);
+        }
+        assert_eq!(escape_like("10\\%_"), "10\\\\\\%\\_");
+    }
+}
@@ -0,0 +1,213 @@
+//! Experiment lifecycle: the statuses an experiment can be in and the moves
+//! allowed between them.
+//!
+//! ```text
+//! pending ──> queued ──> running ──> completed | completed_with_budget_exhausted
+//!    │          │           │
+//!    └──────────┴───────────┴──────> failed | cancelled
+//! ```
+//! `pending` may also go straight to `running`, and `running` goes back to
+//! `queued` when its job is retried. Runs stopped by a budget, such as an AutoML
+//! search out of time, complete with what they found as
+//! `completed_with_budget_exhausted`. Finished experiments never change status
+//! again; rerun them instead.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::reproducibility;
+use crate::error::{AppError, Result};
+use crate::models::{Database, Experiment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentStatus {
+    Pending,
+    Queued,
+    Running,
+    Completed,
+    CompletedWithBudgetExhausted,
+    Failed,
+    Cancelled,
+}
+
+impl ExperimentStatus {
+    pub const ALL: [ExperimentStatus; 7] = [
+        ExperimentStatus::Pending,
+        ExperimentStatus::Queued,
+        ExperimentStatus::Running,
+        ExperimentStatus::Completed,
+        ExperimentStatus::CompletedWithBudgetExhausted,
+        ExperimentStatus::Failed,
+        ExperimentStatus::Cancelled,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExperimentStatus::Pending => "pending",
+            ExperimentStatus::Queued => "queued",
+            ExperimentStatus::Running => "running",
+            ExperimentStatus::Completed => "completed",
+            ExperimentStatus::CompletedWithBudgetExhausted => "completed_with_budget_exhausted",
+            ExperimentStatus::Failed => "failed",
+            ExperimentStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether the experiment has finished, successfully or not.
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            ExperimentStatus::Completed
+                | ExperimentStatus::CompletedWithBudgetExhausted
+                | ExperimentStatus::Failed
+                | ExperimentStatus::Cancelled
+        )
+    }
+
+    /// Statuses an experiment in this status may move to.
+    pub fn next(self) -> &'static [ExperimentStatus] {
+        use ExperimentStatus::*;
+        match self {
+            Pending => &[Queued, Running, Failed, Cancelled],
+            Queued => &[Running, Failed, Cancelled],
+            Running => &[
+                Queued,
+                Completed,
+                CompletedWithBudgetExhausted,
+                Failed,
+                Cancelled,
+            ],
+            Completed | CompletedWithBudgetExhausted | Failed | Cancelled => &[],
+        }
+    }
+
+    pub fn can_transition_to(self, next: ExperimentStatus) -> bool {
+        self.next().contains(&next)
+    }
+
+    /// Statuses an experiment may move to this one from.
+    pub fn previous(self) -> Vec<ExperimentStatus> {
+        Self::ALL
+            .into_iter()
+            .filter(|status| status.can_transition_to(self))
+            .collect()
+    }
+}
+
+impl fmt::Display for ExperimentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ExperimentStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Unknown status '{}'; expected one of {}",
+                    s,
+                    join(&Self::ALL)
+                ))
+            })
+    }
+}
+
+fn join(statuses: &[ExperimentStatus]) -> String {
+    statuses
+        .iter()
+        .map(|status| status.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Moves an experiment to `next`, recording when it started and finished and,
+/// as it starts, what it runs with. Cancelling it also cancels its jobs.
+///
+/// Setting the status it already has is a no-op. Moves the lifecycle doesn't
+/// allow fail with [`AppError::Conflict`].
+pub async fn transition(
+    db: &Database,
+    id: &str,
+    next: ExperimentStatus,
+    failure_reason: Option<&str>,
+) -> Result<Experiment> {
+    let experiment = db
+        .get_experiment(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Experiment {} not found", id)))?;
+    if experiment.status == next.as_str() {
+        return Ok(experiment);
+    }
+
+    // The update only applies from an allowed status, so a concurrent change
+    // between the read above and here is reported rather than overwritten
+    if let Some(experiment) = db
+        .update_experiment_status(id, next, failure_reason)
+        .await?
+    {
+        match next {
+            ExperimentStatus::Running => reproducibility::capture_in_background(db, &experiment),
+            ExperimentStatus::Cancelled => {
+                db.cancel_experiment_jobs(id).await?;
+            }
+            _ => {}
+        }
+        return Ok(experiment);
+    }
+
+    let current = db
+        .get_experiment(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Experiment {} not found", id)))?
+        .status;
+    let allowed = match current.parse::<ExperimentStatus>() {
+        Ok(status) if status.is_terminal() => {
+            format!("{} experiments can't change status", status)
+        }
+        Ok(status) => format!("{} can only move to {}", status, join(status.next())),
+        Err(_) => format!("'{}' is not a known status", current),
+    };
+    Err(AppError::Conflict(format!(
+        "Cannot change the status of experiment {} from {} to {}: {}",
+        id, current, next, allowed
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_transitions() {
+        use ExperimentStatus::*;
+
+        assert_eq!("running".parse::<ExperimentStatus>().unwrap(), Running);
+        let error = "complted".parse::<ExperimentStatus>().unwrap_err();
+        assert!(error.to_string().contains("Unknown status 'complted'"));
+
+        assert!(Pending.can_transition_to(Running));
+        assert!(Queued.can_transition_to(Cancelled));
+        assert!(Running.can_transition_to(Completed));
+        assert!(Running.can_transition_to(CompletedWithBudgetExhausted));
+        assert!(!Queued.can_transition_to(CompletedWithBudgetExhausted));
+        assert!(Running.can_transition_to(Queued));
+        assert!(!Pending.can_transition_to(Completed));
+        assert!(!Completed.can_transition_to(Pending));
+        assert!(!Failed.can_transition_to(Running));
+        assert!(ExperimentStatus::ALL
+            .iter()
+            .filter(|s| s.is_terminal())
+            .all(|s| s.next().is_empty()));
+
+        assert_eq!(Running.previous(), vec![Pending, Queued]);
+        assert_eq!(Queued.previous(), vec![Pending, Running]);
+        assert_eq!(Cancelled.previous(), vec![Pending, Queued, Running]);
+    }
+}